@@ -0,0 +1,326 @@
+use crate::query_dsl;
+use crate::response::ApiResponse;
+use crate::AppRoute;
+use cynic::{MutationBuilder, QueryBuilder};
+use serde::Deserialize;
+use yew::{
+    format::Json,
+    prelude::*,
+    services::fetch::{FetchService, FetchTask, Request, Response},
+    services::storage::{Area, StorageService},
+};
+use yew_router::{agent::RouteRequest::ChangeRoute, prelude::*};
+
+#[derive(cynic::QueryFragment, Debug, Deserialize)]
+#[cynic(schema_path = "schema.graphql", query_module = "query_dsl")]
+pub struct Invitation {
+    email: String,
+}
+
+#[derive(cynic::FragmentArguments)]
+pub struct InvitationArguments {
+    token: String,
+}
+
+#[derive(cynic::QueryFragment, Debug, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "QueryRoot",
+    argument_struct = "InvitationArguments"
+)]
+pub struct InvitationConnection {
+    #[arguments(token = args.token.clone())]
+    invitation: Option<Invitation>,
+}
+
+#[derive(cynic::FragmentArguments)]
+pub struct AcceptInvitationArguments {
+    token: String,
+    name: String,
+    password: String,
+}
+
+#[derive(cynic::QueryFragment, Debug, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "MutationRoot",
+    argument_struct = "AcceptInvitationArguments"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct AcceptInvitationConnection {
+    #[arguments(token = args.token.clone(), name = args.name.clone(), password = args.password.clone())]
+    accept_invitation: String,
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct AcceptInvitationProps {
+    pub token: String,
+}
+
+#[derive(Debug)]
+pub enum Msg {
+    ReceiveInvitation(Result<ApiResponse<InvitationConnection>, anyhow::Error>),
+    Accept,
+    ReceiveResponse(Result<ApiResponse<AcceptInvitationConnection>, anyhow::Error>),
+    NameInputReceived(String),
+    PasswordInputReceived(String),
+    ConfirmationInputReceived(String),
+    Ignore,
+}
+
+pub struct AcceptInvitationModel {
+    props: AcceptInvitationProps,
+    fetch_task: Option<FetchTask>,
+    link: ComponentLink<Self>,
+    loaded: bool,
+    invitation: Option<Invitation>,
+    name: String,
+    name_error: Option<String>,
+    password: String,
+    password_error: Option<String>,
+    confirmation: String,
+    error: Option<String>,
+    router_agent: Box<dyn Bridge<RouteAgent>>,
+}
+
+impl AcceptInvitationModel {
+    fn load_invitation(&mut self) {
+        let operation = InvitationConnection::build(InvitationArguments {
+            token: self.props.token.clone(),
+        });
+        let request = Request::post("/graphql")
+            .header("Content-Type", "application/json")
+            .body(Ok(serde_json::to_string(&operation).unwrap()))
+            .expect("Failed to build request.");
+        let callback = self.link.callback(
+            |response: Response<Json<Result<ApiResponse<InvitationConnection>, anyhow::Error>>>| {
+                let Json(data) = response.into_body();
+                Msg::ReceiveInvitation(data)
+            },
+        );
+        let task = FetchService::fetch(request, callback).expect("failed to start request");
+        self.fetch_task = Some(task);
+    }
+
+    fn view_field_error(message: &Option<String>) -> Html {
+        if let Some(ref message) = message {
+            html! {
+                <p class="mt-1 text-red-500 text-sm">{ message }</p>
+            }
+        } else {
+            html! {}
+        }
+    }
+
+    fn view_form(&self, invitation: &Invitation) -> Html {
+        html! {
+                <div class="mt-8 space-y-6">
+                  <p class="text-sm text-gray-500">
+                    { format!("Create the account for {}.", invitation.email) }
+                  </p>
+                  <div>
+                    <label htmlFor="name" class="block text-sm font-medium text-gray-700">
+                      {"Name"}
+                    </label>
+                    <div class="mt-1">
+                      <input
+                        id="name"
+                        name="name"
+                        type="text"
+                        autoComplete="name"
+                        class="appearance-none block w-full px-3 py-2 border border-gray-300 rounded-md shadow-sm placeholder-gray-400 focus:outline-none focus:ring-indigo-500 focus:border-indigo-500 sm:text-sm"
+                        oninput=self.link.callback(|input_data: InputData| Msg::NameInputReceived(input_data.value))
+                        value=&self.name
+                      />
+                    </div>
+                    { Self::view_field_error(&self.name_error) }
+                  </div>
+                  <div>
+                    <label htmlFor="password" class="block text-sm font-medium text-gray-700">
+                      {"Password"}
+                    </label>
+                    <div class="mt-1">
+                      <input
+                        id="password"
+                        name="password"
+                        type="password"
+                        autoComplete="new-password"
+                        class="appearance-none block w-full px-3 py-2 border border-gray-300 rounded-md shadow-sm placeholder-gray-400 focus:outline-none focus:ring-indigo-500 focus:border-indigo-500 sm:text-sm"
+                        oninput=self.link.callback(|input_data: InputData| Msg::PasswordInputReceived(input_data.value))
+                        value=&self.password
+                      />
+                    </div>
+                  </div>
+                  <div>
+                    <label htmlFor="confirmation" class="block text-sm font-medium text-gray-700">
+                      {"Confirm password"}
+                    </label>
+                    <div class="mt-1">
+                      <input
+                        id="confirmation"
+                        name="confirmation"
+                        type="password"
+                        autoComplete="new-password"
+                        class="appearance-none block w-full px-3 py-2 border border-gray-300 rounded-md shadow-sm placeholder-gray-400 focus:outline-none focus:ring-indigo-500 focus:border-indigo-500 sm:text-sm"
+                        oninput=self.link.callback(|input_data: InputData| Msg::ConfirmationInputReceived(input_data.value))
+                        value=&self.confirmation
+                      />
+                    </div>
+                    { Self::view_field_error(&self.password_error) }
+                  </div>
+                  <div>
+                    <button
+                      class="w-full flex justify-center py-3 px-4 border border-transparent rounded-md shadow-sm text-sm font-medium text-white bg-indigo-600 hover:bg-indigo-700 focus:outline-none focus:ring-2 focus:ring-offset-2 focus:ring-indigo-500"
+                      onclick=self.link.callback(|_| Msg::Accept)
+                    >
+                      { if self.fetch_task.is_some() { "Creating..." } else { "Create account" } }
+                    </button>
+                    { Self::view_field_error(&self.error) }
+                  </div>
+                </div>
+        }
+    }
+}
+
+impl Component for AcceptInvitationModel {
+    type Message = Msg;
+    type Properties = AcceptInvitationProps;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut model = Self {
+            props,
+            fetch_task: None,
+            loaded: false,
+            invitation: None,
+            name: String::from(""),
+            name_error: None,
+            password: String::from(""),
+            password_error: None,
+            confirmation: String::from(""),
+            error: None,
+            router_agent: RouteAgent::bridge(link.callback(|_| Msg::Ignore)),
+            link,
+        };
+        model.load_invitation();
+        model
+    }
+
+    fn change(&mut self, props: Self::Properties) -> bool {
+        if self.props != props {
+            self.props = props;
+            self.loaded = false;
+            self.load_invitation();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> bool {
+        match msg {
+            Msg::ReceiveInvitation(response) => {
+                match response {
+                    Ok(graphql_response) => {
+                        self.error = graphql_response.other_errors();
+                        if let Some(data) = graphql_response.data {
+                            self.invitation = data.invitation;
+                        }
+                    }
+                    Err(error) => self.error = Some(error.to_string()),
+                }
+                self.loaded = true;
+                self.fetch_task = None;
+            }
+            Msg::Accept => {
+                if self.password != self.confirmation {
+                    self.password_error = Some("The passwords do not match".into());
+                    return true;
+                }
+
+                let operation = AcceptInvitationConnection::build(AcceptInvitationArguments {
+                    token: self.props.token.clone(),
+                    name: self.name.clone(),
+                    password: self.password.clone(),
+                });
+                let request = Request::post("/graphql")
+                    .header("Content-Type", "application/json")
+                    .body(Ok(serde_json::to_string(&operation).unwrap()))
+                    .expect("Failed to build request.");
+                let callback = self.link.callback(
+                    |response: Response<
+                        Json<Result<ApiResponse<AcceptInvitationConnection>, anyhow::Error>>,
+                    >| {
+                        let Json(data) = response.into_body();
+                        Msg::ReceiveResponse(data)
+                    },
+                );
+                let task = FetchService::fetch(request, callback).expect("failed to start request");
+                self.fetch_task = Some(task);
+            }
+            Msg::ReceiveResponse(response) => {
+                match response {
+                    Ok(graphql_response) => {
+                        self.name_error = graphql_response.field_error("name");
+                        self.password_error = graphql_response.field_error("password");
+                        // The form has no email field, the address comes with the invitation.
+                        self.error = graphql_response
+                            .field_error("email")
+                            .or_else(|| graphql_response.other_errors());
+                        if let Some(data) = graphql_response.data {
+                            let mut storage = StorageService::new(Area::Local).unwrap();
+                            storage.store("auth_token", Ok(data.accept_invitation));
+                            self.router_agent.send(ChangeRoute(AppRoute::Index.into()));
+                        }
+                    }
+                    Err(error) => self.error = Some(error.to_string()),
+                }
+                self.fetch_task = None;
+            }
+            Msg::NameInputReceived(value) => {
+                self.error = None;
+                self.name_error = None;
+                self.name = value;
+            }
+            Msg::PasswordInputReceived(value) => {
+                self.error = None;
+                self.password_error = None;
+                self.password = value;
+            }
+            Msg::ConfirmationInputReceived(value) => {
+                self.error = None;
+                self.password_error = None;
+                self.confirmation = value;
+            }
+            Msg::Ignore => return false,
+        }
+        true
+    }
+
+    fn view(&self) -> Html {
+        let content = match self.invitation {
+            Some(ref invitation) => self.view_form(invitation),
+            None if self.loaded => html! {
+              <p class="mt-8 text-sm text-gray-500">
+                {"This invitation is invalid or has expired. Ask an administrator to send a new one."}
+              </p>
+            },
+            None => html! {},
+        };
+        html! {
+          <div class="min-h-screen bg-white flex">
+            <div class="flex-1 flex flex-col justify-center py-12 px-4 sm:px-6 lg:flex-none lg:px-20 xl:px-24">
+              <div class="mx-auto w-full max-w-sm lg:w-96">
+                <div>
+                  <h2 class="mt-6 text-3xl font-extrabold text-gray-900">
+                    {"Accept your invitation"}
+                  </h2>
+                </div>
+                { content }
+              </div>
+            </div>
+          </div>
+        }
+    }
+}
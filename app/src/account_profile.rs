@@ -0,0 +1,747 @@
+use crate::author::Author;
+use crate::authorization::authorized_request;
+use crate::components::toolbar::ToolbarModel;
+use crate::query_dsl;
+use crate::response::ApiResponse;
+use crate::AppRoute;
+use crate::DateTime;
+use cynic::{MutationBuilder, QueryBuilder};
+use serde::Deserialize;
+use yew::{
+    format::Json,
+    prelude::*,
+    services::fetch::{FetchService, FetchTask, Response},
+    services::storage::{Area, StorageService},
+};
+use yew_router::{agent::RouteRequest::ChangeRoute, prelude::*};
+
+#[derive(cynic::QueryFragment, Deserialize)]
+#[cynic(schema_path = "schema.graphql", query_module = "query_dsl")]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    email: String,
+    email_verified_at: Option<DateTime>,
+    author: Author,
+}
+
+#[derive(cynic::QueryFragment, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "QueryRoot"
+)]
+pub struct ProfileConnection {
+    profile: Profile,
+}
+
+#[derive(cynic::FragmentArguments)]
+pub struct UpdateProfileArguments {
+    display_name: String,
+    bio: String,
+    avatar_url: Option<String>,
+    website_url: Option<String>,
+    social_links: Vec<String>,
+}
+
+#[derive(cynic::QueryFragment, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "MutationRoot",
+    argument_struct = "UpdateProfileArguments"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateProfileConnection {
+    #[arguments(
+        display_name = args.display_name.clone(),
+        bio = args.bio.clone(),
+        avatar_url = args.avatar_url.clone(),
+        website_url = args.website_url.clone(),
+        social_links = args.social_links.clone()
+    )]
+    update_profile: Author,
+}
+
+#[derive(cynic::FragmentArguments)]
+pub struct ChangeEmailArguments {
+    new_email: String,
+    password: String,
+}
+
+#[derive(cynic::QueryFragment, Debug, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "MutationRoot",
+    argument_struct = "ChangeEmailArguments"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeEmailConnection {
+    #[arguments(new_email = args.new_email.clone(), password = args.password.clone())]
+    change_email: String,
+}
+
+#[derive(cynic::FragmentArguments)]
+pub struct ChangePasswordArguments {
+    current_password: String,
+    new_password: String,
+}
+
+#[derive(cynic::QueryFragment, Debug, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "MutationRoot",
+    argument_struct = "ChangePasswordArguments"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangePasswordConnection {
+    #[arguments(
+        current_password = args.current_password.clone(),
+        new_password = args.new_password.clone()
+    )]
+    change_password: String,
+}
+
+#[derive(cynic::QueryFragment, Debug, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "MutationRoot"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportMyDataConnection {
+    export_my_data: String,
+}
+
+#[derive(cynic::FragmentArguments)]
+pub struct DeleteAccountArguments {
+    password: String,
+    reassign_posts_to: Option<i32>,
+}
+
+#[derive(cynic::QueryFragment, Debug, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "MutationRoot",
+    argument_struct = "DeleteAccountArguments"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteAccountConnection {
+    #[arguments(password = args.password.clone(), reassign_posts_to = args.reassign_posts_to)]
+    delete_account: String,
+}
+
+#[derive(Clone, Copy)]
+pub enum Field {
+    DisplayName,
+    Bio,
+    AvatarUrl,
+    WebsiteUrl,
+    SocialLinks,
+    NewEmail,
+    EmailPassword,
+    CurrentPassword,
+    NewPassword,
+    Confirmation,
+    DeletePassword,
+    ReassignPostsTo,
+}
+
+pub enum Msg {
+    ReceiveProfile(Result<ApiResponse<ProfileConnection>, anyhow::Error>),
+    InputReceived(Field, String),
+    SaveProfile,
+    ReceiveProfileUpdate(Result<ApiResponse<UpdateProfileConnection>, anyhow::Error>),
+    ChangeEmail,
+    ReceiveEmailChange(Result<ApiResponse<ChangeEmailConnection>, anyhow::Error>),
+    ChangePassword,
+    ReceivePasswordChange(Result<ApiResponse<ChangePasswordConnection>, anyhow::Error>),
+    ExportData,
+    ReceiveExport(Result<ApiResponse<ExportMyDataConnection>, anyhow::Error>),
+    DeleteAccount,
+    ReceiveDeletion(Result<ApiResponse<DeleteAccountConnection>, anyhow::Error>),
+    Ignore,
+}
+
+#[derive(Default)]
+struct Errors {
+    display_name: Option<String>,
+    bio: Option<String>,
+    avatar_url: Option<String>,
+    website_url: Option<String>,
+    social_links: Option<String>,
+    new_email: Option<String>,
+    email_password: Option<String>,
+    current_password: Option<String>,
+    new_password: Option<String>,
+    delete_password: Option<String>,
+    reassign_posts_to: Option<String>,
+    other: Option<String>,
+}
+
+pub struct AccountProfileModel {
+    fetch_task: Option<FetchTask>,
+    link: ComponentLink<Self>,
+    email: Option<String>,
+    email_verified: bool,
+    author_href: Option<String>,
+    display_name: String,
+    bio: String,
+    avatar_url: String,
+    website_url: String,
+    social_links: String,
+    new_email: String,
+    email_password: String,
+    current_password: String,
+    new_password: String,
+    confirmation: String,
+    export: Option<String>,
+    delete_password: String,
+    reassign_posts_to: String,
+    errors: Errors,
+    notice: Option<String>,
+    router_agent: Box<dyn Bridge<RouteAgent>>,
+}
+
+fn optional(value: &str) -> Option<String> {
+    match value.trim() {
+        "" => None,
+        value => Some(value.to_string()),
+    }
+}
+
+// The export is handed to the browser as a data URL so it can be saved as a file.
+fn data_url(json: &str) -> String {
+    let mut url = String::from("data:application/json;charset=utf-8,");
+    for byte in json.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-_.~".contains(&byte) {
+            url.push(byte as char);
+        } else {
+            url.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    url
+}
+
+impl AccountProfileModel {
+    fn load_profile(&mut self) {
+        let operation = ProfileConnection::build(());
+        let request = authorized_request(serde_json::to_string(&operation).unwrap());
+        let callback = self.link.callback(
+            |response: Response<Json<Result<ApiResponse<ProfileConnection>, anyhow::Error>>>| {
+                let Json(data) = response.into_body();
+                Msg::ReceiveProfile(data)
+            },
+        );
+        let task = FetchService::fetch(request, callback).expect("failed to start request");
+        self.fetch_task = Some(task);
+    }
+
+    fn set_author(&mut self, author: Author) {
+        self.author_href = Some(author.href());
+        self.display_name = author.display_name;
+        self.bio = author.bio;
+        self.avatar_url = author.avatar_url.unwrap_or_default();
+        self.website_url = author.website_url.unwrap_or_default();
+        self.social_links = author.social_links.join("\n");
+    }
+
+    fn view_field_error(message: &Option<String>) -> Html {
+        if let Some(ref message) = message {
+            html! {
+                <p class="mt-1 text-red-500 text-sm">{ message }</p>
+            }
+        } else {
+            html! {}
+        }
+    }
+
+    fn view_input(
+        &self,
+        field: Field,
+        label: &str,
+        input_type: &str,
+        value: &str,
+        error: &Option<String>,
+    ) -> Html {
+        html! {
+          <div>
+            <label class="block text-sm font-medium text-gray-700">
+              { label }
+            </label>
+            <div class="mt-1">
+              <input
+                type=input_type.to_string()
+                class="appearance-none block w-full px-3 py-2 border border-gray-300 rounded-md shadow-sm placeholder-gray-400 focus:outline-none focus:ring-indigo-500 focus:border-indigo-500 sm:text-sm"
+                oninput=self.link.callback(move |input_data: InputData| Msg::InputReceived(field, input_data.value))
+                value=value.to_string()
+              />
+            </div>
+            { Self::view_field_error(error) }
+          </div>
+        }
+    }
+
+    fn view_textarea(
+        &self,
+        field: Field,
+        label: &str,
+        value: &str,
+        error: &Option<String>,
+    ) -> Html {
+        html! {
+          <div>
+            <label class="block text-sm font-medium text-gray-700">
+              { label }
+            </label>
+            <div class="mt-1">
+              <textarea
+                rows="4"
+                class="appearance-none block w-full px-3 py-2 border border-gray-300 rounded-md shadow-sm placeholder-gray-400 focus:outline-none focus:ring-indigo-500 focus:border-indigo-500 sm:text-sm"
+                oninput=self.link.callback(move |input_data: InputData| Msg::InputReceived(field, input_data.value))
+                value=value.to_string()
+              />
+            </div>
+            { Self::view_field_error(error) }
+          </div>
+        }
+    }
+
+    fn view_button(&self, label: &str, msg: fn() -> Msg) -> Html {
+        html! {
+          <button
+            class="w-full flex justify-center py-3 px-4 border border-transparent rounded-md shadow-sm text-sm font-medium text-white bg-indigo-600 hover:bg-indigo-700 focus:outline-none focus:ring-2 focus:ring-offset-2 focus:ring-indigo-500"
+            disabled=self.fetch_task.is_some()
+            onclick=self.link.callback(move |_| msg())
+          >
+            { label }
+          </button>
+        }
+    }
+
+    fn view_profile_form(&self) -> Html {
+        html! {
+          <div class="space-y-6">
+            <h2 class="text-2xl font-extrabold text-gray-900">{"Public profile"}</h2>
+            {
+                match self.author_href {
+                    Some(ref href) => html! {
+                      <a href=href.clone() class="text-sm text-indigo-600 hover:text-indigo-500">
+                        {"View your author page"}
+                      </a>
+                    },
+                    None => html! {},
+                }
+            }
+            { self.view_input(Field::DisplayName, "Display name", "text", &self.display_name, &self.errors.display_name) }
+            { self.view_textarea(Field::Bio, "Bio", &self.bio, &self.errors.bio) }
+            { self.view_input(Field::AvatarUrl, "Avatar URL", "url", &self.avatar_url, &self.errors.avatar_url) }
+            { self.view_input(Field::WebsiteUrl, "Website", "url", &self.website_url, &self.errors.website_url) }
+            { self.view_textarea(Field::SocialLinks, "Social links, one per line", &self.social_links, &self.errors.social_links) }
+            { self.view_button("Save profile", || Msg::SaveProfile) }
+          </div>
+        }
+    }
+
+    fn view_email_form(&self) -> Html {
+        let current = match self.email {
+            Some(ref email) if self.email_verified => format!("Your email address is {}.", email),
+            Some(ref email) => format!("Your email address is {} and is not verified yet.", email),
+            None => String::from(""),
+        };
+        html! {
+          <div class="space-y-6">
+            <h2 class="text-2xl font-extrabold text-gray-900">{"Email address"}</h2>
+            <p class="text-sm text-gray-500">{ current }</p>
+            { self.view_input(Field::NewEmail, "New email address", "email", &self.new_email, &self.errors.new_email) }
+            { self.view_input(Field::EmailPassword, "Password", "password", &self.email_password, &self.errors.email_password) }
+            { self.view_button("Change email", || Msg::ChangeEmail) }
+          </div>
+        }
+    }
+
+    fn view_password_form(&self) -> Html {
+        html! {
+          <div class="space-y-6">
+            <h2 class="text-2xl font-extrabold text-gray-900">{"Password"}</h2>
+            <p class="text-sm text-gray-500">
+              {"Changing your password signs you out everywhere else."}
+            </p>
+            { self.view_input(Field::CurrentPassword, "Current password", "password", &self.current_password, &self.errors.current_password) }
+            { self.view_input(Field::NewPassword, "New password", "password", &self.new_password, &self.errors.new_password) }
+            { self.view_input(Field::Confirmation, "Confirm new password", "password", &self.confirmation, &None) }
+            { self.view_button("Change password", || Msg::ChangePassword) }
+          </div>
+        }
+    }
+
+    fn view_data_form(&self) -> Html {
+        html! {
+          <div class="space-y-6">
+            <h2 class="text-2xl font-extrabold text-gray-900">{"Your data"}</h2>
+            <p class="text-sm text-gray-500">
+              {"Download your profile, posts, sessions and API tokens as a JSON file."}
+            </p>
+            {
+                match self.export {
+                    Some(ref json) => html! {
+                      <a
+                        href=data_url(json)
+                        download="my-data.json"
+                        class="w-full flex justify-center py-3 px-4 border border-gray-300 rounded-md shadow-sm text-sm font-medium text-gray-700 bg-white hover:bg-gray-50"
+                      >
+                        {"Save my-data.json"}
+                      </a>
+                    },
+                    None => self.view_button("Export my data", || Msg::ExportData),
+                }
+            }
+          </div>
+        }
+    }
+
+    fn view_delete_form(&self) -> Html {
+        html! {
+          <div class="space-y-6">
+            <h2 class="text-2xl font-extrabold text-gray-900">{"Delete account"}</h2>
+            <p class="text-sm text-gray-500">
+              {"Your account is removed for good. Your posts stay up without an author unless you give them to another author."}
+            </p>
+            { self.view_input(Field::ReassignPostsTo, "Give my posts to author ID (optional)", "number", &self.reassign_posts_to, &self.errors.reassign_posts_to) }
+            { self.view_input(Field::DeletePassword, "Password", "password", &self.delete_password, &self.errors.delete_password) }
+            <button
+              class="w-full flex justify-center py-3 px-4 border border-transparent rounded-md shadow-sm text-sm font-medium text-white bg-red-600 hover:bg-red-700 focus:outline-none focus:ring-2 focus:ring-offset-2 focus:ring-red-500"
+              disabled=self.fetch_task.is_some()
+              onclick=self.link.callback(|_| Msg::DeleteAccount)
+            >
+              {"Delete my account"}
+            </button>
+          </div>
+        }
+    }
+}
+
+impl Component for AccountProfileModel {
+    type Message = Msg;
+    type Properties = ();
+
+    fn create(_props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut model = Self {
+            fetch_task: None,
+            email: None,
+            email_verified: false,
+            author_href: None,
+            display_name: String::from(""),
+            bio: String::from(""),
+            avatar_url: String::from(""),
+            website_url: String::from(""),
+            social_links: String::from(""),
+            new_email: String::from(""),
+            email_password: String::from(""),
+            current_password: String::from(""),
+            new_password: String::from(""),
+            confirmation: String::from(""),
+            export: None,
+            delete_password: String::from(""),
+            reassign_posts_to: String::from(""),
+            errors: Errors::default(),
+            notice: None,
+            router_agent: RouteAgent::bridge(link.callback(|_| Msg::Ignore)),
+            link,
+        };
+        model.load_profile();
+        model
+    }
+
+    fn change(&mut self, _props: Self::Properties) -> bool {
+        false
+    }
+
+    fn update(&mut self, msg: Self::Message) -> bool {
+        match msg {
+            Msg::ReceiveProfile(response) => {
+                match response {
+                    Ok(graphql_response) => {
+                        self.errors.other = graphql_response.other_errors();
+                        if let Some(data) = graphql_response.data {
+                            self.email = Some(data.profile.email);
+                            self.email_verified = data.profile.email_verified_at.is_some();
+                            self.set_author(data.profile.author);
+                        }
+                    }
+                    Err(error) => self.errors.other = Some(error.to_string()),
+                }
+                self.fetch_task = None;
+            }
+            Msg::InputReceived(field, value) => {
+                self.notice = None;
+                self.errors.other = None;
+                match field {
+                    Field::DisplayName => {
+                        self.errors.display_name = None;
+                        self.display_name = value;
+                    }
+                    Field::Bio => {
+                        self.errors.bio = None;
+                        self.bio = value;
+                    }
+                    Field::AvatarUrl => {
+                        self.errors.avatar_url = None;
+                        self.avatar_url = value;
+                    }
+                    Field::WebsiteUrl => {
+                        self.errors.website_url = None;
+                        self.website_url = value;
+                    }
+                    Field::SocialLinks => {
+                        self.errors.social_links = None;
+                        self.social_links = value;
+                    }
+                    Field::NewEmail => {
+                        self.errors.new_email = None;
+                        self.new_email = value;
+                    }
+                    Field::EmailPassword => {
+                        self.errors.email_password = None;
+                        self.email_password = value;
+                    }
+                    Field::CurrentPassword => {
+                        self.errors.current_password = None;
+                        self.current_password = value;
+                    }
+                    Field::NewPassword => {
+                        self.errors.new_password = None;
+                        self.new_password = value;
+                    }
+                    Field::Confirmation => {
+                        self.errors.new_password = None;
+                        self.confirmation = value;
+                    }
+                    Field::DeletePassword => {
+                        self.errors.delete_password = None;
+                        self.delete_password = value;
+                    }
+                    Field::ReassignPostsTo => {
+                        self.errors.reassign_posts_to = None;
+                        self.reassign_posts_to = value;
+                    }
+                }
+            }
+            Msg::SaveProfile => {
+                let operation = UpdateProfileConnection::build(UpdateProfileArguments {
+                    display_name: self.display_name.clone(),
+                    bio: self.bio.clone(),
+                    avatar_url: optional(&self.avatar_url),
+                    website_url: optional(&self.website_url),
+                    social_links: self
+                        .social_links
+                        .lines()
+                        .map(|link| link.trim().to_string())
+                        .filter(|link| !link.is_empty())
+                        .collect(),
+                });
+                let request = authorized_request(serde_json::to_string(&operation).unwrap());
+                let callback = self.link.callback(
+                    |response: Response<
+                        Json<Result<ApiResponse<UpdateProfileConnection>, anyhow::Error>>,
+                    >| {
+                        let Json(data) = response.into_body();
+                        Msg::ReceiveProfileUpdate(data)
+                    },
+                );
+                let task = FetchService::fetch(request, callback).expect("failed to start request");
+                self.fetch_task = Some(task);
+            }
+            Msg::ReceiveProfileUpdate(response) => {
+                match response {
+                    Ok(graphql_response) => {
+                        self.errors.display_name = graphql_response.field_error("displayName");
+                        self.errors.bio = graphql_response.field_error("bio");
+                        self.errors.avatar_url = graphql_response.field_error("avatarUrl");
+                        self.errors.website_url = graphql_response.field_error("websiteUrl");
+                        self.errors.social_links = graphql_response.field_error("socialLinks");
+                        self.errors.other = graphql_response.other_errors();
+                        if let Some(data) = graphql_response.data {
+                            self.set_author(data.update_profile);
+                            self.notice = Some(String::from("Your profile has been saved."));
+                        }
+                    }
+                    Err(error) => self.errors.other = Some(error.to_string()),
+                }
+                self.fetch_task = None;
+            }
+            Msg::ChangeEmail => {
+                let operation = ChangeEmailConnection::build(ChangeEmailArguments {
+                    new_email: self.new_email.clone(),
+                    password: self.email_password.clone(),
+                });
+                let request = authorized_request(serde_json::to_string(&operation).unwrap());
+                let callback = self.link.callback(
+                    |response: Response<
+                        Json<Result<ApiResponse<ChangeEmailConnection>, anyhow::Error>>,
+                    >| {
+                        let Json(data) = response.into_body();
+                        Msg::ReceiveEmailChange(data)
+                    },
+                );
+                let task = FetchService::fetch(request, callback).expect("failed to start request");
+                self.fetch_task = Some(task);
+            }
+            Msg::ReceiveEmailChange(response) => {
+                match response {
+                    Ok(graphql_response) => {
+                        self.errors.new_email = graphql_response.field_error("newEmail");
+                        self.errors.email_password = graphql_response.field_error("password");
+                        self.errors.other = graphql_response.other_errors();
+                        if graphql_response.data.is_some() {
+                            self.email = Some(self.new_email.trim().to_string());
+                            self.email_verified = false;
+                            self.new_email = String::from("");
+                            self.email_password = String::from("");
+                            self.notice = Some(String::from(
+                                "Check your inbox for a link to verify your new email address.",
+                            ));
+                        }
+                    }
+                    Err(error) => self.errors.other = Some(error.to_string()),
+                }
+                self.fetch_task = None;
+            }
+            Msg::ChangePassword => {
+                if self.new_password != self.confirmation {
+                    self.errors.new_password = Some("The passwords do not match".into());
+                    return true;
+                }
+
+                let operation = ChangePasswordConnection::build(ChangePasswordArguments {
+                    current_password: self.current_password.clone(),
+                    new_password: self.new_password.clone(),
+                });
+                let request = authorized_request(serde_json::to_string(&operation).unwrap());
+                let callback = self.link.callback(
+                    |response: Response<
+                        Json<Result<ApiResponse<ChangePasswordConnection>, anyhow::Error>>,
+                    >| {
+                        let Json(data) = response.into_body();
+                        Msg::ReceivePasswordChange(data)
+                    },
+                );
+                let task = FetchService::fetch(request, callback).expect("failed to start request");
+                self.fetch_task = Some(task);
+            }
+            Msg::ReceivePasswordChange(response) => {
+                match response {
+                    Ok(graphql_response) => {
+                        self.errors.current_password =
+                            graphql_response.field_error("currentPassword");
+                        self.errors.new_password = graphql_response.field_error("newPassword");
+                        self.errors.other = graphql_response.other_errors();
+                        if graphql_response.data.is_some() {
+                            self.current_password = String::from("");
+                            self.new_password = String::from("");
+                            self.confirmation = String::from("");
+                            self.notice = Some(String::from("Your password has been changed."));
+                        }
+                    }
+                    Err(error) => self.errors.other = Some(error.to_string()),
+                }
+                self.fetch_task = None;
+            }
+            Msg::ExportData => {
+                let operation = ExportMyDataConnection::build(());
+                let request = authorized_request(serde_json::to_string(&operation).unwrap());
+                let callback = self.link.callback(
+                    |response: Response<
+                        Json<Result<ApiResponse<ExportMyDataConnection>, anyhow::Error>>,
+                    >| {
+                        let Json(data) = response.into_body();
+                        Msg::ReceiveExport(data)
+                    },
+                );
+                let task = FetchService::fetch(request, callback).expect("failed to start request");
+                self.fetch_task = Some(task);
+            }
+            Msg::ReceiveExport(response) => {
+                match response {
+                    Ok(graphql_response) => {
+                        self.errors.other = graphql_response.other_errors();
+                        self.export = graphql_response.data.map(|data| data.export_my_data);
+                    }
+                    Err(error) => self.errors.other = Some(error.to_string()),
+                }
+                self.fetch_task = None;
+            }
+            Msg::DeleteAccount => {
+                let reassign_posts_to = match self.reassign_posts_to.trim() {
+                    "" => None,
+                    value => match value.parse::<i32>() {
+                        Ok(author_id) => Some(author_id),
+                        Err(_error) => {
+                            self.errors.reassign_posts_to =
+                                Some(String::from("Enter the number of an author."));
+                            return true;
+                        }
+                    },
+                };
+                let operation = DeleteAccountConnection::build(DeleteAccountArguments {
+                    password: self.delete_password.clone(),
+                    reassign_posts_to,
+                });
+                let request = authorized_request(serde_json::to_string(&operation).unwrap());
+                let callback = self.link.callback(
+                    |response: Response<
+                        Json<Result<ApiResponse<DeleteAccountConnection>, anyhow::Error>>,
+                    >| {
+                        let Json(data) = response.into_body();
+                        Msg::ReceiveDeletion(data)
+                    },
+                );
+                let task = FetchService::fetch(request, callback).expect("failed to start request");
+                self.fetch_task = Some(task);
+            }
+            Msg::ReceiveDeletion(response) => {
+                match response {
+                    Ok(graphql_response) => {
+                        self.errors.delete_password = graphql_response.field_error("password");
+                        self.errors.reassign_posts_to =
+                            graphql_response.field_error("reassignPostsTo");
+                        self.errors.other = graphql_response.other_errors();
+                        if graphql_response.data.is_some() {
+                            let mut storage = StorageService::new(Area::Local).unwrap();
+                            storage.remove("auth_token");
+                            self.router_agent.send(ChangeRoute(AppRoute::Index.into()));
+                        }
+                    }
+                    Err(error) => self.errors.other = Some(error.to_string()),
+                }
+                self.fetch_task = None;
+            }
+            Msg::Ignore => return false,
+        }
+        true
+    }
+
+    fn view(&self) -> Html {
+        html! {
+          <div>
+            <ToolbarModel />
+            <div class="max-w-xl mx-auto py-12 px-4 sm:px-6 lg:px-8 space-y-12">
+              {
+                  match self.notice {
+                      Some(ref notice) => html! { <p class="text-sm text-green-600">{ notice }</p> },
+                      None => html! {},
+                  }
+              }
+              { Self::view_field_error(&self.errors.other) }
+              { self.view_profile_form() }
+              { self.view_email_form() }
+              { self.view_password_form() }
+              { self.view_data_form() }
+              { self.view_delete_form() }
+            </div>
+          </div>
+        }
+    }
+}
@@ -0,0 +1,558 @@
+use crate::authorization::authorized_request;
+use crate::components::toolbar::ToolbarModel;
+use crate::query_dsl;
+use crate::response::ApiResponse;
+use crate::DateTime;
+use cynic::{MutationBuilder, QueryBuilder};
+use serde::Deserialize;
+use web_sys::Node;
+use yew::virtual_dom::VNode;
+use yew::{
+    format::Json,
+    prelude::*,
+    services::fetch::{FetchService, FetchTask, Response},
+};
+
+#[derive(cynic::QueryFragment, Deserialize)]
+#[cynic(schema_path = "schema.graphql", query_module = "query_dsl")]
+#[serde(rename_all = "camelCase")]
+pub struct TwoFactorStatus {
+    enabled_at: Option<DateTime>,
+    recovery_codes_left: i32,
+}
+
+#[derive(cynic::QueryFragment, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "QueryRoot"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct TwoFactorStatusConnection {
+    two_factor: TwoFactorStatus,
+    single_sign_on: Option<SingleSignOn>,
+}
+
+#[derive(cynic::QueryFragment, Deserialize)]
+#[cynic(schema_path = "schema.graphql", query_module = "query_dsl")]
+#[serde(rename_all = "camelCase")]
+pub struct SingleSignOn {
+    name: String,
+}
+
+#[derive(cynic::QueryFragment, Debug, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "MutationRoot"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkSingleSignOnConnection {
+    link_single_sign_on: String,
+}
+
+#[derive(cynic::QueryFragment, Debug, Deserialize)]
+#[cynic(schema_path = "schema.graphql", query_module = "query_dsl")]
+#[serde(rename_all = "camelCase")]
+pub struct TwoFactorEnrollment {
+    secret: String,
+    qr_code_svg: String,
+}
+
+#[derive(cynic::QueryFragment, Debug, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "MutationRoot"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct BeginTwoFactorEnrollmentConnection {
+    begin_two_factor_enrollment: TwoFactorEnrollment,
+}
+
+#[derive(cynic::FragmentArguments)]
+pub struct CodeArguments {
+    code: String,
+}
+
+#[derive(cynic::QueryFragment, Debug, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "MutationRoot",
+    argument_struct = "CodeArguments"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfirmTwoFactorEnrollmentConnection {
+    #[arguments(code = args.code.clone())]
+    confirm_two_factor_enrollment: Vec<String>,
+}
+
+#[derive(cynic::QueryFragment, Debug, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "MutationRoot",
+    argument_struct = "CodeArguments"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct RegenerateRecoveryCodesConnection {
+    #[arguments(code = args.code.clone())]
+    regenerate_recovery_codes: Vec<String>,
+}
+
+#[derive(cynic::QueryFragment, Debug, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "MutationRoot",
+    argument_struct = "CodeArguments"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct DisableTwoFactorConnection {
+    #[arguments(code = args.code.clone())]
+    disable_two_factor: String,
+}
+
+pub enum Msg {
+    ReceiveStatus(Result<ApiResponse<TwoFactorStatusConnection>, anyhow::Error>),
+    BeginEnrollment,
+    ReceiveEnrollment(Result<ApiResponse<BeginTwoFactorEnrollmentConnection>, anyhow::Error>),
+    ConfirmEnrollment,
+    ReceiveConfirmation(Result<ApiResponse<ConfirmTwoFactorEnrollmentConnection>, anyhow::Error>),
+    RegenerateRecoveryCodes,
+    ReceiveRecoveryCodes(Result<ApiResponse<RegenerateRecoveryCodesConnection>, anyhow::Error>),
+    DismissRecoveryCodes,
+    Disable,
+    ReceiveDisable(Result<ApiResponse<DisableTwoFactorConnection>, anyhow::Error>),
+    LinkSingleSignOn,
+    ReceiveLinkSingleSignOn(Result<ApiResponse<LinkSingleSignOnConnection>, anyhow::Error>),
+    CodeInputReceived(String),
+}
+
+pub struct AccountSecurityModel {
+    fetch_task: Option<FetchTask>,
+    link: ComponentLink<Self>,
+    status: Option<TwoFactorStatus>,
+    single_sign_on: Option<SingleSignOn>,
+    enrollment: Option<TwoFactorEnrollment>,
+    recovery_codes: Option<Vec<String>>,
+    code: String,
+    code_error: Option<String>,
+    error: Option<String>,
+}
+
+impl AccountSecurityModel {
+    fn load_status(&mut self) {
+        let operation = TwoFactorStatusConnection::build(());
+        let request = authorized_request(serde_json::to_string(&operation).unwrap());
+        let callback = self.link.callback(
+            |response: Response<
+                Json<Result<ApiResponse<TwoFactorStatusConnection>, anyhow::Error>>,
+            >| {
+                let Json(data) = response.into_body();
+                Msg::ReceiveStatus(data)
+            },
+        );
+        let task = FetchService::fetch(request, callback).expect("failed to start request");
+        self.fetch_task = Some(task);
+    }
+
+    fn code_arguments(&self) -> CodeArguments {
+        CodeArguments {
+            code: self.code.clone(),
+        }
+    }
+
+    fn qr_code_node(&self, svg: &str) -> Html {
+        let div = web_sys::window()
+            .unwrap()
+            .document()
+            .unwrap()
+            .create_element("div")
+            .unwrap();
+
+        div.set_inner_html(svg);
+        let node = Node::from(div);
+        let vnode = VNode::VRef(node);
+
+        html! {{vnode}}
+    }
+
+    fn view_code_input(&self, label: &str) -> Html {
+        html! {
+          <div>
+            <label htmlFor="code" class="block text-sm font-medium text-gray-700">
+              { label }
+            </label>
+            <div class="mt-1">
+              <input
+                id="code"
+                name="code"
+                type="text"
+                autoComplete="one-time-code"
+                class="appearance-none block w-full px-3 py-2 border border-gray-300 rounded-md shadow-sm placeholder-gray-400 focus:outline-none focus:ring-indigo-500 focus:border-indigo-500 sm:text-sm"
+                placeholder="123456"
+                oninput=self.link.callback(|input_data: InputData| Msg::CodeInputReceived(input_data.value))
+                value=&self.code
+              />
+            </div>
+            {
+                if let Some(ref message) = self.code_error {
+                    html! { <p class="mt-1 text-red-500 text-sm">{ message }</p> }
+                } else {
+                    html! {}
+                }
+            }
+          </div>
+        }
+    }
+
+    fn view_error(&self) -> Html {
+        if let Some(ref error) = self.error {
+            html! {
+                <p class="mt-1 text-red-500 text-sm">{ error }</p>
+            }
+        } else {
+            html! {}
+        }
+    }
+
+    fn view_recovery_codes(&self, codes: &[String]) -> Html {
+        html! {
+          <div class="space-y-4">
+            <p class="text-sm text-gray-700">
+              {"Save these recovery codes somewhere safe. Each of them can be used once to sign in if you lose access to your authenticator app. They will not be shown again."}
+            </p>
+            <ul class="grid grid-cols-2 gap-2 font-mono text-sm text-gray-900">
+              { for codes.iter().map(|code| html! { <li>{ code }</li> }) }
+            </ul>
+            <button
+              class="w-full flex justify-center py-3 px-4 border border-gray-300 rounded-md shadow-sm text-sm font-medium text-gray-700 bg-white hover:bg-gray-50 focus:outline-none focus:ring-2 focus:ring-offset-2 focus:ring-indigo-500"
+              onclick=self.link.callback(|_| Msg::DismissRecoveryCodes)
+            >
+              {"I have saved these codes"}
+            </button>
+          </div>
+        }
+    }
+
+    fn view_enrollment(&self, enrollment: &TwoFactorEnrollment) -> Html {
+        html! {
+          <div class="space-y-6">
+            <p class="text-sm text-gray-700">
+              {"Scan this QR code with your authenticator app, then enter the code it shows to finish setting up two-factor authentication."}
+            </p>
+            { self.qr_code_node(&enrollment.qr_code_svg) }
+            <p class="text-sm text-gray-500">
+              {"Can't scan it? Enter this key instead: "}
+              <span class="font-mono text-gray-900">{ &enrollment.secret }</span>
+            </p>
+            { self.view_code_input("Authentication code") }
+            <button
+              class="w-full flex justify-center py-3 px-4 border border-transparent rounded-md shadow-sm text-sm font-medium text-white bg-indigo-600 hover:bg-indigo-700 focus:outline-none focus:ring-2 focus:ring-offset-2 focus:ring-indigo-500"
+              onclick=self.link.callback(|_| Msg::ConfirmEnrollment)
+            >
+              {"Enable two-factor authentication"}
+            </button>
+          </div>
+        }
+    }
+
+    fn view_status(&self, status: &TwoFactorStatus) -> Html {
+        if status.enabled_at.is_none() {
+            return html! {
+              <div class="space-y-6">
+                <p class="text-sm text-gray-700">
+                  {"Two-factor authentication is off. Turn it on to require a code from an authenticator app every time you sign in."}
+                </p>
+                <button
+                  class="w-full flex justify-center py-3 px-4 border border-transparent rounded-md shadow-sm text-sm font-medium text-white bg-indigo-600 hover:bg-indigo-700 focus:outline-none focus:ring-2 focus:ring-offset-2 focus:ring-indigo-500"
+                  onclick=self.link.callback(|_| Msg::BeginEnrollment)
+                >
+                  {"Set up two-factor authentication"}
+                </button>
+              </div>
+            };
+        }
+
+        html! {
+          <div class="space-y-6">
+            <p class="text-sm text-gray-700">
+              { format!("Two-factor authentication is on. You have {} unused recovery codes left.", status.recovery_codes_left) }
+            </p>
+            { self.view_code_input("Enter a code from your authenticator app or a recovery code to make changes") }
+            <div class="flex space-x-4">
+              <button
+                class="flex-1 py-3 px-4 border border-gray-300 rounded-md shadow-sm text-sm font-medium text-gray-700 bg-white hover:bg-gray-50 focus:outline-none focus:ring-2 focus:ring-offset-2 focus:ring-indigo-500"
+                onclick=self.link.callback(|_| Msg::RegenerateRecoveryCodes)
+              >
+                {"New recovery codes"}
+              </button>
+              <button
+                class="flex-1 py-3 px-4 border border-transparent rounded-md shadow-sm text-sm font-medium text-white bg-red-600 hover:bg-red-700 focus:outline-none focus:ring-2 focus:ring-offset-2 focus:ring-red-500"
+                onclick=self.link.callback(|_| Msg::Disable)
+              >
+                {"Disable"}
+              </button>
+            </div>
+          </div>
+        }
+    }
+
+    fn view_single_sign_on(&self) -> Html {
+        match self.single_sign_on {
+            Some(ref single_sign_on) => html! {
+              <div class="mt-12">
+                <h2 class="text-3xl font-extrabold text-gray-900">
+                  {"Single sign-on"}
+                </h2>
+                <p class="mt-8 text-sm text-gray-700">
+                  { format!("Link your {} account to sign in with it instead of your password.", single_sign_on.name) }
+                </p>
+                <button
+                  class="mt-6 w-full flex justify-center py-3 px-4 border border-gray-300 rounded-md shadow-sm text-sm font-medium text-gray-700 bg-white hover:bg-gray-50 focus:outline-none focus:ring-2 focus:ring-offset-2 focus:ring-indigo-500"
+                  onclick=self.link.callback(|_| Msg::LinkSingleSignOn)
+                >
+                  { format!("Link {}", single_sign_on.name) }
+                </button>
+              </div>
+            },
+            None => html! {},
+        }
+    }
+
+    fn view_content(&self) -> Html {
+        if let Some(ref codes) = self.recovery_codes {
+            return self.view_recovery_codes(codes);
+        }
+        if let Some(ref enrollment) = self.enrollment {
+            return self.view_enrollment(enrollment);
+        }
+        match self.status {
+            Some(ref status) => self.view_status(status),
+            None if self.fetch_task.is_some() => html! { <p>{"Loading..."}</p> },
+            None => html! {},
+        }
+    }
+}
+
+impl Component for AccountSecurityModel {
+    type Message = Msg;
+    type Properties = ();
+
+    fn create(_props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut model = Self {
+            fetch_task: None,
+            link,
+            status: None,
+            single_sign_on: None,
+            enrollment: None,
+            recovery_codes: None,
+            code: String::from(""),
+            code_error: None,
+            error: None,
+        };
+        model.load_status();
+        model
+    }
+
+    fn change(&mut self, _props: Self::Properties) -> bool {
+        false
+    }
+
+    fn update(&mut self, msg: Self::Message) -> bool {
+        match msg {
+            Msg::ReceiveStatus(response) => {
+                match response {
+                    Ok(graphql_response) => {
+                        self.error = graphql_response.other_errors();
+                        if let Some(data) = graphql_response.data {
+                            self.status = Some(data.two_factor);
+                            self.single_sign_on = data.single_sign_on;
+                        }
+                    }
+                    Err(error) => self.error = Some(error.to_string()),
+                }
+                self.fetch_task = None;
+            }
+            Msg::BeginEnrollment => {
+                let operation = BeginTwoFactorEnrollmentConnection::build(());
+                let request = authorized_request(serde_json::to_string(&operation).unwrap());
+                let callback = self.link.callback(
+                    |response: Response<
+                        Json<
+                            Result<ApiResponse<BeginTwoFactorEnrollmentConnection>, anyhow::Error>,
+                        >,
+                    >| {
+                        let Json(data) = response.into_body();
+                        Msg::ReceiveEnrollment(data)
+                    },
+                );
+                let task = FetchService::fetch(request, callback).expect("failed to start request");
+                self.fetch_task = Some(task);
+            }
+            Msg::ReceiveEnrollment(response) => {
+                match response {
+                    Ok(graphql_response) => {
+                        self.error = graphql_response.other_errors();
+                        self.enrollment = graphql_response
+                            .data
+                            .map(|data| data.begin_two_factor_enrollment);
+                    }
+                    Err(error) => self.error = Some(error.to_string()),
+                }
+                self.fetch_task = None;
+            }
+            Msg::ConfirmEnrollment => {
+                let operation = ConfirmTwoFactorEnrollmentConnection::build(self.code_arguments());
+                let request = authorized_request(serde_json::to_string(&operation).unwrap());
+                let callback = self.link.callback(
+                    |response: Response<
+                        Json<
+                            Result<
+                                ApiResponse<ConfirmTwoFactorEnrollmentConnection>,
+                                anyhow::Error,
+                            >,
+                        >,
+                    >| {
+                        let Json(data) = response.into_body();
+                        Msg::ReceiveConfirmation(data)
+                    },
+                );
+                let task = FetchService::fetch(request, callback).expect("failed to start request");
+                self.fetch_task = Some(task);
+            }
+            Msg::ReceiveConfirmation(response) => {
+                self.fetch_task = None;
+                match response {
+                    Ok(graphql_response) => {
+                        self.code_error = graphql_response.field_error("code");
+                        self.error = graphql_response.other_errors();
+                        if let Some(data) = graphql_response.data {
+                            self.enrollment = None;
+                            self.code = String::from("");
+                            self.recovery_codes = Some(data.confirm_two_factor_enrollment);
+                            self.load_status();
+                        }
+                    }
+                    Err(error) => self.error = Some(error.to_string()),
+                }
+            }
+            Msg::RegenerateRecoveryCodes => {
+                let operation = RegenerateRecoveryCodesConnection::build(self.code_arguments());
+                let request = authorized_request(serde_json::to_string(&operation).unwrap());
+                let callback = self.link.callback(
+                    |response: Response<
+                        Json<Result<ApiResponse<RegenerateRecoveryCodesConnection>, anyhow::Error>>,
+                    >| {
+                        let Json(data) = response.into_body();
+                        Msg::ReceiveRecoveryCodes(data)
+                    },
+                );
+                let task = FetchService::fetch(request, callback).expect("failed to start request");
+                self.fetch_task = Some(task);
+            }
+            Msg::ReceiveRecoveryCodes(response) => {
+                match response {
+                    Ok(graphql_response) => {
+                        self.code_error = graphql_response.field_error("code");
+                        self.error = graphql_response.other_errors();
+                        if let Some(data) = graphql_response.data {
+                            self.code = String::from("");
+                            self.recovery_codes = Some(data.regenerate_recovery_codes);
+                        }
+                    }
+                    Err(error) => self.error = Some(error.to_string()),
+                }
+                self.fetch_task = None;
+            }
+            Msg::DismissRecoveryCodes => {
+                self.recovery_codes = None;
+            }
+            Msg::Disable => {
+                let operation = DisableTwoFactorConnection::build(self.code_arguments());
+                let request = authorized_request(serde_json::to_string(&operation).unwrap());
+                let callback = self.link.callback(
+                    |response: Response<
+                        Json<Result<ApiResponse<DisableTwoFactorConnection>, anyhow::Error>>,
+                    >| {
+                        let Json(data) = response.into_body();
+                        Msg::ReceiveDisable(data)
+                    },
+                );
+                let task = FetchService::fetch(request, callback).expect("failed to start request");
+                self.fetch_task = Some(task);
+            }
+            Msg::ReceiveDisable(response) => {
+                self.fetch_task = None;
+                match response {
+                    Ok(graphql_response) => {
+                        self.code_error = graphql_response.field_error("code");
+                        self.error = graphql_response.other_errors();
+                        if graphql_response.data.is_some() {
+                            self.code = String::from("");
+                            self.load_status();
+                        }
+                    }
+                    Err(error) => self.error = Some(error.to_string()),
+                }
+            }
+            Msg::LinkSingleSignOn => {
+                let operation = LinkSingleSignOnConnection::build(());
+                let request = authorized_request(serde_json::to_string(&operation).unwrap());
+                let callback = self.link.callback(
+                    |response: Response<
+                        Json<Result<ApiResponse<LinkSingleSignOnConnection>, anyhow::Error>>,
+                    >| {
+                        let Json(data) = response.into_body();
+                        Msg::ReceiveLinkSingleSignOn(data)
+                    },
+                );
+                let task = FetchService::fetch(request, callback).expect("failed to start request");
+                self.fetch_task = Some(task);
+            }
+            Msg::ReceiveLinkSingleSignOn(response) => {
+                self.fetch_task = None;
+                match response {
+                    Ok(graphql_response) => {
+                        self.error = graphql_response.other_errors();
+                        // The provider sends the browser back to this page once linked.
+                        if let Some(data) = graphql_response.data {
+                            web_sys::window()
+                                .unwrap()
+                                .location()
+                                .set_href(&data.link_single_sign_on)
+                                .unwrap();
+                        }
+                    }
+                    Err(error) => self.error = Some(error.to_string()),
+                }
+            }
+            Msg::CodeInputReceived(value) => {
+                self.error = None;
+                self.code_error = None;
+                self.code = value;
+            }
+        }
+        true
+    }
+
+    fn view(&self) -> Html {
+        html! {
+          <div>
+            <ToolbarModel />
+            <div class="max-w-xl mx-auto py-12 px-4 sm:px-6 lg:px-8">
+              <h2 class="text-3xl font-extrabold text-gray-900">
+                {"Two-factor authentication"}
+              </h2>
+              <div class="mt-8">
+                { self.view_content() }
+                { self.view_error() }
+              </div>
+              { self.view_single_sign_on() }
+            </div>
+          </div>
+        }
+    }
+}
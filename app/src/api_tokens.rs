@@ -0,0 +1,365 @@
+use crate::authorization::authorized_request;
+use crate::components::toolbar::ToolbarModel;
+use crate::query_dsl;
+use crate::response::ApiResponse;
+use crate::settings::SiteSettings;
+use crate::DateTime;
+use cynic::{MutationBuilder, QueryBuilder};
+use serde::Deserialize;
+use yew::{
+    format::Json,
+    prelude::*,
+    services::fetch::{FetchService, FetchTask, Response},
+};
+
+const SCOPES: &[(&str, &str)] = &[
+    ("posts:read", "Read drafts and other unpublished posts"),
+    ("posts:write", "Create and update posts"),
+    ("settings:read", "Read the site settings"),
+    ("settings:write", "Change the site settings"),
+    ("users:write", "Add users and change their roles"),
+];
+
+#[derive(cynic::QueryFragment, Deserialize)]
+#[cynic(schema_path = "schema.graphql", query_module = "query_dsl")]
+#[serde(rename_all = "camelCase")]
+pub struct ApiToken {
+    id: i32,
+    name: String,
+    scopes: Vec<String>,
+    last_used_at: Option<DateTime>,
+    created_at: DateTime,
+}
+
+#[derive(cynic::QueryFragment, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "QueryRoot"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiTokensConnection {
+    api_tokens: Vec<ApiToken>,
+    site_settings: SiteSettings,
+}
+
+#[derive(cynic::QueryFragment, Deserialize)]
+#[cynic(schema_path = "schema.graphql", query_module = "query_dsl")]
+pub struct CreatedApiToken {
+    token: String,
+}
+
+#[derive(cynic::FragmentArguments)]
+pub struct CreateApiTokenArguments {
+    name: String,
+    scopes: Vec<String>,
+}
+
+#[derive(cynic::QueryFragment, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "MutationRoot",
+    argument_struct = "CreateApiTokenArguments"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateApiTokenConnection {
+    #[arguments(name = args.name.clone(), scopes = args.scopes.clone())]
+    create_api_token: CreatedApiToken,
+}
+
+#[derive(cynic::FragmentArguments)]
+pub struct RevokeApiTokenArguments {
+    api_token_id: i32,
+}
+
+#[derive(cynic::QueryFragment, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "MutationRoot",
+    argument_struct = "RevokeApiTokenArguments"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct RevokeApiTokenConnection {
+    #[arguments(api_token_id = args.api_token_id)]
+    revoke_api_token: String,
+}
+
+pub enum Msg {
+    ReceiveTokens(Result<ApiResponse<ApiTokensConnection>, anyhow::Error>),
+    NameInputReceived(String),
+    ToggleScope(&'static str),
+    Create,
+    ReceiveCreated(Result<ApiResponse<CreateApiTokenConnection>, anyhow::Error>),
+    Revoke(i32),
+    ReceiveRevoked(Result<ApiResponse<RevokeApiTokenConnection>, anyhow::Error>),
+}
+
+pub struct ApiTokensModel {
+    fetch_task: Option<FetchTask>,
+    link: ComponentLink<Self>,
+    api_tokens: Vec<ApiToken>,
+    site_settings: SiteSettings,
+    name: String,
+    name_error: Option<String>,
+    scopes: Vec<String>,
+    scopes_error: Option<String>,
+    created_token: Option<String>,
+    error: Option<String>,
+}
+
+impl ApiTokensModel {
+    fn load_tokens(&mut self) {
+        let operation = ApiTokensConnection::build(());
+        let request = authorized_request(serde_json::to_string(&operation).unwrap());
+        let callback = self.link.callback(
+            |response: Response<Json<Result<ApiResponse<ApiTokensConnection>, anyhow::Error>>>| {
+                let Json(data) = response.into_body();
+                Msg::ReceiveTokens(data)
+            },
+        );
+        let task = FetchService::fetch(request, callback).expect("failed to start request");
+        self.fetch_task = Some(task);
+    }
+
+    fn view_field_error(message: &Option<String>) -> Html {
+        if let Some(ref message) = message {
+            html! {
+                <p class="mt-1 text-red-500 text-sm">{ message }</p>
+            }
+        } else {
+            html! {}
+        }
+    }
+
+    fn view_created_token(&self) -> Html {
+        if let Some(ref token) = self.created_token {
+            html! {
+              <div class="mt-6 p-4 rounded-md bg-green-50">
+                <p class="text-sm text-gray-700">
+                  {"Copy your new token now. It will not be shown again."}
+                </p>
+                <p class="mt-2 font-mono text-sm text-gray-900 break-all">{ token }</p>
+              </div>
+            }
+        } else {
+            html! {}
+        }
+    }
+
+    fn view_scope(&self, scope: &'static str, description: &'static str) -> Html {
+        let checked = self.scopes.iter().any(|selected| selected == scope);
+        html! {
+          <div class="flex items-start">
+            <input
+              id=scope
+              type="checkbox"
+              class="mt-1 h-4 w-4 text-indigo-600 focus:ring-indigo-500 border-gray-300 rounded"
+              checked=checked
+              onclick=self.link.callback(move |_| Msg::ToggleScope(scope))
+            />
+            <label htmlFor=scope class="ml-3 text-sm">
+              <span class="font-mono text-gray-900">{ scope }</span>
+              <span class="block text-gray-500">{ description }</span>
+            </label>
+          </div>
+        }
+    }
+
+    fn view_api_token(&self, api_token: &ApiToken) -> Html {
+        let id = api_token.id;
+        let last_used = match api_token.last_used_at {
+            Some(ref last_used_at) => {
+                format!("Last used {}", self.site_settings.format_date(last_used_at))
+            }
+            None => String::from("Never used"),
+        };
+        html! {
+          <li class="py-4 flex items-center justify-between">
+            <div>
+              <p class="text-sm font-medium text-gray-900">{ &api_token.name }</p>
+              <p class="text-sm font-mono text-gray-500">{ api_token.scopes.join(" ") }</p>
+              <p class="text-sm text-gray-500">
+                { format!("Created {} · {}", self.site_settings.format_date(&api_token.created_at), last_used) }
+              </p>
+            </div>
+            <button
+              class="ml-4 px-3 py-2 border border-gray-300 text-sm font-medium rounded-md text-gray-700 bg-white hover:bg-gray-50"
+              onclick=self.link.callback(move |_| Msg::Revoke(id))
+            >
+              {"Revoke"}
+            </button>
+          </li>
+        }
+    }
+}
+
+impl Component for ApiTokensModel {
+    type Message = Msg;
+    type Properties = ();
+
+    fn create(_props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut model = Self {
+            fetch_task: None,
+            link,
+            api_tokens: Vec::new(),
+            site_settings: SiteSettings::default(),
+            name: String::from(""),
+            name_error: None,
+            scopes: Vec::new(),
+            scopes_error: None,
+            created_token: None,
+            error: None,
+        };
+        model.load_tokens();
+        model
+    }
+
+    fn change(&mut self, _props: Self::Properties) -> bool {
+        false
+    }
+
+    fn update(&mut self, msg: Self::Message) -> bool {
+        match msg {
+            Msg::ReceiveTokens(response) => {
+                match response {
+                    Ok(graphql_response) => {
+                        self.error = graphql_response.other_errors();
+                        if let Some(data) = graphql_response.data {
+                            self.api_tokens = data.api_tokens;
+                            self.site_settings = data.site_settings;
+                        }
+                    }
+                    Err(error) => self.error = Some(error.to_string()),
+                }
+                self.fetch_task = None;
+            }
+            Msg::NameInputReceived(value) => {
+                self.error = None;
+                self.name_error = None;
+                self.name = value;
+            }
+            Msg::ToggleScope(scope) => {
+                self.scopes_error = None;
+                match self.scopes.iter().position(|selected| selected == scope) {
+                    Some(index) => {
+                        self.scopes.remove(index);
+                    }
+                    None => self.scopes.push(scope.to_string()),
+                }
+            }
+            Msg::Create => {
+                let operation = CreateApiTokenConnection::build(CreateApiTokenArguments {
+                    name: self.name.clone(),
+                    scopes: self.scopes.clone(),
+                });
+                let request = authorized_request(serde_json::to_string(&operation).unwrap());
+                let callback = self.link.callback(
+                    |response: Response<
+                        Json<Result<ApiResponse<CreateApiTokenConnection>, anyhow::Error>>,
+                    >| {
+                        let Json(data) = response.into_body();
+                        Msg::ReceiveCreated(data)
+                    },
+                );
+                let task = FetchService::fetch(request, callback).expect("failed to start request");
+                self.fetch_task = Some(task);
+            }
+            Msg::ReceiveCreated(response) => {
+                self.fetch_task = None;
+                match response {
+                    Ok(graphql_response) => {
+                        self.name_error = graphql_response.field_error("name");
+                        self.scopes_error = graphql_response.field_error("scopes");
+                        self.error = graphql_response.other_errors();
+                        if let Some(data) = graphql_response.data {
+                            self.created_token = Some(data.create_api_token.token);
+                            self.name = String::from("");
+                            self.scopes = Vec::new();
+                            self.load_tokens();
+                        }
+                    }
+                    Err(error) => self.error = Some(error.to_string()),
+                }
+            }
+            Msg::Revoke(api_token_id) => {
+                let operation =
+                    RevokeApiTokenConnection::build(RevokeApiTokenArguments { api_token_id });
+                let request = authorized_request(serde_json::to_string(&operation).unwrap());
+                let callback = self.link.callback(
+                    |response: Response<
+                        Json<Result<ApiResponse<RevokeApiTokenConnection>, anyhow::Error>>,
+                    >| {
+                        let Json(data) = response.into_body();
+                        Msg::ReceiveRevoked(data)
+                    },
+                );
+                let task = FetchService::fetch(request, callback).expect("failed to start request");
+                self.fetch_task = Some(task);
+            }
+            Msg::ReceiveRevoked(response) => {
+                self.fetch_task = None;
+                match response {
+                    Ok(graphql_response) => {
+                        self.error = graphql_response.other_errors();
+                        self.created_token = None;
+                        self.load_tokens();
+                    }
+                    Err(error) => self.error = Some(error.to_string()),
+                }
+            }
+        }
+        true
+    }
+
+    fn view(&self) -> Html {
+        html! {
+          <div>
+            <ToolbarModel />
+            <div class="max-w-xl mx-auto py-12 px-4 sm:px-6 lg:px-8">
+              <h2 class="text-3xl font-extrabold text-gray-900">
+                {"API tokens"}
+              </h2>
+              <p class="mt-2 text-sm text-gray-500">
+                {"Tokens let scripts and CI jobs use the API on your behalf. Send them in the token header or as Authorization: Bearer."}
+              </p>
+              <div class="mt-8 space-y-6">
+                <div>
+                  <label htmlFor="name" class="block text-sm font-medium text-gray-700">
+                    {"Name"}
+                  </label>
+                  <div class="mt-1">
+                    <input
+                      id="name"
+                      name="name"
+                      type="text"
+                      class="appearance-none block w-full px-3 py-2 border border-gray-300 rounded-md shadow-sm placeholder-gray-400 focus:outline-none focus:ring-indigo-500 focus:border-indigo-500 sm:text-sm"
+                      placeholder="Release notes from CI"
+                      oninput=self.link.callback(|input_data: InputData| Msg::NameInputReceived(input_data.value))
+                      value=&self.name
+                    />
+                  </div>
+                  { Self::view_field_error(&self.name_error) }
+                </div>
+                <div class="space-y-3">
+                  { for SCOPES.iter().map(|(scope, description)| self.view_scope(scope, description)) }
+                  { Self::view_field_error(&self.scopes_error) }
+                </div>
+                <button
+                  class="w-full flex justify-center py-3 px-4 border border-transparent rounded-md shadow-sm text-sm font-medium text-white bg-indigo-600 hover:bg-indigo-700 focus:outline-none focus:ring-2 focus:ring-offset-2 focus:ring-indigo-500"
+                  onclick=self.link.callback(|_| Msg::Create)
+                >
+                  {"Create token"}
+                </button>
+                { Self::view_field_error(&self.error) }
+              </div>
+              { self.view_created_token() }
+              <ul class="mt-8 divide-y divide-gray-200">
+                { for self.api_tokens.iter().map(|api_token| self.view_api_token(api_token)) }
+              </ul>
+            </div>
+          </div>
+        }
+    }
+}
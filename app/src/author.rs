@@ -0,0 +1,222 @@
+use crate::components::toolbar::ToolbarModel;
+use crate::post::Post;
+use crate::query_dsl;
+use crate::settings::SiteSettings;
+use cynic::GraphQLResponse;
+use cynic::QueryBuilder;
+use serde::Deserialize;
+use yew::format::Json;
+use yew::prelude::*;
+use yew::services::console::ConsoleService;
+use yew::services::fetch::{FetchTask, Request, Response};
+use yew::services::FetchService;
+
+#[derive(cynic::QueryFragment, Deserialize, Clone, PartialEq)]
+#[cynic(schema_path = "schema.graphql", query_module = "query_dsl")]
+#[serde(rename_all = "camelCase")]
+pub struct Author {
+    pub id: i32,
+    pub display_name: String,
+    pub bio: String,
+    pub avatar_url: Option<String>,
+    pub website_url: Option<String>,
+    pub social_links: Vec<String>,
+}
+
+impl Author {
+    pub fn href(&self) -> String {
+        format!("/author/{}", self.id)
+    }
+
+    // Stands in for the avatar of someone who deleted their account.
+    pub fn view_former_avatar(size: &str) -> Html {
+        html! {
+          <span class={format!("{} rounded-full bg-gray-200 inline-block", size)}></span>
+        }
+    }
+
+    pub fn view_avatar(&self, size: &str) -> Html {
+        match self.avatar_url {
+            Some(ref avatar_url) => html! {
+              <img class={format!("{} rounded-full", size)} src=avatar_url.clone() alt="" />
+            },
+            None => {
+                let initial: String = self.display_name.chars().take(1).collect();
+                html! {
+                  <span class={format!("{} rounded-full bg-indigo-100 text-indigo-600 font-medium inline-flex items-center justify-center", size)}>
+                    { initial.to_uppercase() }
+                  </span>
+                }
+            }
+        }
+    }
+}
+
+#[derive(cynic::FragmentArguments)]
+pub struct AuthorArguments {
+    pub author_id: i32,
+}
+
+#[derive(cynic::QueryFragment, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "QueryRoot",
+    argument_struct = "AuthorArguments"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthorConnection {
+    #[arguments(author_id = args.author_id)]
+    author: Option<Author>,
+    #[arguments(author_id = args.author_id)]
+    posts: Vec<Post>,
+    site_settings: SiteSettings,
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct AuthorProps {
+    pub id: i32,
+}
+
+pub enum Msg {
+    ReceiveResponse(Result<GraphQLResponse<AuthorConnection>, anyhow::Error>),
+}
+
+pub struct AuthorModel {
+    props: AuthorProps,
+    link: ComponentLink<Self>,
+    author: Option<Author>,
+    posts: Vec<Post>,
+    site_settings: SiteSettings,
+    fetch_target: Option<FetchTask>,
+}
+
+impl AuthorModel {
+    fn load(&mut self) {
+        let operation = AuthorConnection::build(AuthorArguments {
+            author_id: self.props.id,
+        });
+        let request = Request::post("/graphql")
+            .header("Content-Type", "application/json")
+            .body(Ok(serde_json::to_string(&operation).unwrap()))
+            .expect("Failed to build request.");
+        let callback = self.link.callback(
+            |response: Response<Json<Result<GraphQLResponse<AuthorConnection>, anyhow::Error>>>| {
+                let Json(data) = response.into_body();
+                Msg::ReceiveResponse(data)
+            },
+        );
+        let target = FetchService::fetch(request, callback).expect("failed to start request");
+        self.fetch_target = Some(target);
+    }
+
+    fn view_link(url: &str) -> Html {
+        html! {
+          <li>
+            <a href=url.to_string() rel="me noopener" class="text-sm text-indigo-600 hover:text-indigo-500">
+              { url }
+            </a>
+          </li>
+        }
+    }
+
+    fn view_author(&self, author: &Author) -> Html {
+        html! {
+          <div class="flex items-start space-x-5">
+            <div class="flex-shrink-0">
+              { author.view_avatar("h-20 w-20") }
+            </div>
+            <div>
+              <h1 class="text-2xl font-bold text-gray-900">{ &author.display_name }</h1>
+              <p class="mt-2 text-sm text-gray-700 whitespace-pre-line">{ &author.bio }</p>
+              <ul class="mt-3 space-y-1">
+                { for author.website_url.iter().map(|url| Self::view_link(url)) }
+                { for author.social_links.iter().map(|url| Self::view_link(url)) }
+              </ul>
+            </div>
+          </div>
+        }
+    }
+
+    fn view_post(&self, post: &Post) -> Html {
+        html! {
+          <li class="bg-white px-4 py-6 shadow sm:p-6 sm:rounded-lg mt-4">
+            <a href={format!("/post/{}", post.slug)}>
+              <h2 class="text-base font-medium text-gray-900">{ &post.title }</h2>
+              <p class="text-sm text-gray-500">{ self.site_settings.format_date(&post.created_at) }</p>
+              <p class="mt-2 text-sm text-gray-700">{ &post.summary }</p>
+            </a>
+          </li>
+        }
+    }
+}
+
+impl Component for AuthorModel {
+    type Message = Msg;
+    type Properties = AuthorProps;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut model = Self {
+            props,
+            link,
+            author: None,
+            posts: vec![],
+            site_settings: SiteSettings::default(),
+            fetch_target: None,
+        };
+        model.load();
+        model
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::ReceiveResponse(response) => {
+                match response {
+                    Ok(graphql_response) => {
+                        if let Some(data) = graphql_response.data {
+                            self.author = data.author;
+                            self.posts = data.posts;
+                            self.site_settings = data.site_settings;
+                        }
+                    }
+                    Err(error) => ConsoleService::info(&format!("Error: {}", error.to_string())),
+                };
+                self.fetch_target = None;
+            }
+        }
+        true
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if self.props != props {
+            self.props = props;
+            self.load();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn view(&self) -> Html {
+        let content = match self.author {
+            Some(ref author) => self.view_author(author),
+            None if self.fetch_target.is_none() => html! {
+              <p class="text-sm text-gray-500">{"This author doesn't exist."}</p>
+            },
+            None => html! {},
+        };
+        html! {
+          <div class="min-h-screen bg-gray-100">
+            <ToolbarModel />
+            <div class="py-10">
+              <div class="max-w-3xl mx-auto sm:px-6 lg:px-8">
+                { content }
+                <ul class="mt-8">
+                  { for self.posts.iter().map(|post| self.view_post(post)) }
+                </ul>
+              </div>
+            </div>
+          </div>
+        }
+    }
+}
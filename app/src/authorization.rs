@@ -1,5 +1,7 @@
 use crate::query_dsl;
 use serde::Deserialize;
+use yew::services::fetch::Request;
+use yew::services::storage::{Area, StorageService};
 
 #[derive(cynic::QueryFragment, Deserialize)]
 #[cynic(
@@ -11,3 +13,18 @@ use serde::Deserialize;
 pub struct Authorities {
     pub valid_token: bool,
 }
+
+pub fn authorized_request(query: String) -> Request<Result<String, anyhow::Error>> {
+    let storage = StorageService::new(Area::Local).unwrap();
+
+    let token: String = match storage.restore("auth_token") {
+        Ok(token) => token,
+        Err(_err) => String::from(""),
+    };
+
+    Request::post("/graphql")
+        .header("Content-Type", "application/json")
+        .header("token", token)
+        .body(Ok(query))
+        .expect("Failed to build request.")
+}
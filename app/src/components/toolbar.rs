@@ -1,10 +1,9 @@
 use crate::authorization::Authorities;
 use crate::query_dsl;
-use crate::Jsonobject;
+use crate::settings::SiteSettings;
 use cynic::GraphQLResponse;
-use cynic::QueryBuilder;
+use cynic::{MutationBuilder, QueryBuilder};
 use serde::Deserialize;
-use serde_json::{value::Value, Map};
 use yew::format::Json;
 use yew::prelude::*;
 use yew::services::console::ConsoleService;
@@ -20,14 +19,27 @@ use yew::services::FetchService;
 )]
 #[serde(rename_all = "camelCase")]
 pub struct ToolbarConnection {
-    settings: Jsonobject,
+    site_settings: SiteSettings,
     authorization: Option<Authorities>,
 }
 
+#[derive(cynic::QueryFragment, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "MutationRoot"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct SignOutConnection {
+    sign_out: String,
+}
+
 pub struct ToolbarModel {
-    settings: Map<String, Value>,
+    site_settings: SiteSettings,
     authorization: Option<Authorities>,
     fetch_target: Option<FetchTask>,
+    sign_out_task: Option<FetchTask>,
+    link: ComponentLink<Self>,
 }
 
 impl ToolbarModel {
@@ -35,12 +47,44 @@ impl ToolbarModel {
         match &self.authorization {
             Some(authorization) => match authorization.valid_token {
                 true => {
-                    html! {<a
+                    html! {<>
+                    <a
                         href="/posts/new"
                         class="ml-6 px-4 py-2 border border-transparent text-sm font-medium rounded-md shadow-sm text-white bg-indigo-600 hover:bg-indigo-700 focus:outline-none focus:ring-2 focus:ring-offset-2 focus:ring-indigo-500"
                     >
                         {"New Post"}
-                    </a>}
+                    </a>
+                    <a
+                        href="/posts/trash"
+                        class="ml-6 text-sm font-medium text-gray-700 hover:text-gray-900"
+                    >
+                        {"Trash"}
+                    </a>
+                    <a
+                        href="/account/profile"
+                        class="ml-6 text-sm font-medium text-gray-700 hover:text-gray-900"
+                    >
+                        {"Profile"}
+                    </a>
+                    <a
+                        href="/account/tokens"
+                        class="ml-6 text-sm font-medium text-gray-700 hover:text-gray-900"
+                    >
+                        {"API tokens"}
+                    </a>
+                    <a
+                        href="/account/security"
+                        class="ml-6 text-sm font-medium text-gray-700 hover:text-gray-900"
+                    >
+                        {"Security"}
+                    </a>
+                    <button
+                        onclick=self.link.callback(|_| Msg::SignOut)
+                        class="ml-6 px-4 py-2 border border-gray-300 text-sm font-medium rounded-md shadow-sm text-gray-700 bg-white hover:bg-gray-50 focus:outline-none focus:ring-2 focus:ring-offset-2 focus:ring-indigo-500"
+                    >
+                        {"Sign out"}
+                    </button>
+                    </>}
                 }
                 false => {
                     html! { <a
@@ -65,6 +109,8 @@ impl ToolbarModel {
 
 pub enum Msg {
     ReceiveResponse(Result<GraphQLResponse<ToolbarConnection>, anyhow::Error>),
+    SignOut,
+    SignedOut(Result<GraphQLResponse<SignOutConnection>, anyhow::Error>),
 }
 
 impl Component for ToolbarModel {
@@ -98,9 +144,11 @@ impl Component for ToolbarModel {
         let target = FetchService::fetch(request, callback).expect("failed to start request");
 
         Self {
-            settings: Map::new(),
+            site_settings: SiteSettings::default(),
             authorization: None,
             fetch_target: Some(target),
+            sign_out_task: None,
+            link,
         }
     }
 
@@ -110,10 +158,7 @@ impl Component for ToolbarModel {
                 match response {
                     Ok(graphql_response) => match graphql_response.data {
                         Some(data) => {
-                            self.settings = match data.settings.0.as_object() {
-                                Some(map) => map.clone(),
-                                None => Map::new(),
-                            };
+                            self.site_settings = data.site_settings;
                             self.authorization = data.authorization;
                         }
                         None => {}
@@ -122,6 +167,44 @@ impl Component for ToolbarModel {
                 };
                 self.fetch_target = None;
             }
+            Msg::SignOut => {
+                let storage = StorageService::new(Area::Local).unwrap();
+
+                let token: String = match storage.restore("auth_token") {
+                    Ok(token) => token,
+                    Err(_err) => String::from(""),
+                };
+
+                let operation = SignOutConnection::build(());
+
+                let query = serde_json::to_string(&operation).unwrap();
+
+                let request = Request::post("/graphql")
+                    .header("Content-Type", "application/json")
+                    .header("token", token)
+                    .body(Ok(query))
+                    .expect("Failed to build request.");
+                let callback = self.link.callback(
+                    |response: Response<
+                        Json<Result<GraphQLResponse<SignOutConnection>, anyhow::Error>>,
+                    >| {
+                        let Json(data) = response.into_body();
+                        Msg::SignedOut(data)
+                    },
+                );
+                let task = FetchService::fetch(request, callback).expect("failed to start request");
+                self.sign_out_task = Some(task);
+            }
+            Msg::SignedOut(response) => {
+                if let Err(error) = response {
+                    ConsoleService::info(&format!("Error: {}", error.to_string()));
+                }
+                // The token is forgotten locally even if the server could not revoke it.
+                let mut storage = StorageService::new(Area::Local).unwrap();
+                storage.remove("auth_token");
+                self.authorization = None;
+                self.sign_out_task = None;
+            }
         }
         true
     }
@@ -144,7 +227,7 @@ impl Component for ToolbarModel {
                 </div>
                 <div class="min-w-0 flex-1 md:px-8 lg:px-0 xl:col-span-6">
                   <div class="flex items-center px-6 py-4 md:max-w-3xl md:mx-auto lg:max-w-none lg:mx-0 xl:px-0">
-                    <p class="block w-full bg-white rounded-md py-2 pl-10 pr-3 text-lg placeholder-gray-500 focus:outline-none focus:text-gray-900 focus:placeholder-gray-400 focus:ring-1 focus:ring-indigo-500 focus:border-indigo-500 sm:text-sm" >{self.settings.get("title").and_then(|value| value.as_str()).unwrap_or("")}{self.settings.get("tagline").and_then(|value| Some(format!(" - {}", value))).unwrap_or(String::from(""))}</p>
+                    <p class="block w-full bg-white rounded-md py-2 pl-10 pr-3 text-lg placeholder-gray-500 focus:outline-none focus:text-gray-900 focus:placeholder-gray-400 focus:ring-1 focus:ring-indigo-500 focus:border-indigo-500 sm:text-sm" >{self.site_settings.heading()}</p>
                   </div>
                 </div>
                 <div class="flex items-center md:absolute md:right-0 md:inset-y-0 lg:hidden">
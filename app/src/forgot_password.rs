@@ -0,0 +1,204 @@
+use crate::query_dsl;
+use cynic::GraphQLResponse;
+use cynic::MutationBuilder;
+use serde::Deserialize;
+use yew::{
+    format::Json,
+    prelude::*,
+    services::fetch::{FetchService, FetchTask, Request, Response},
+};
+
+#[derive(cynic::FragmentArguments)]
+pub struct ForgotPasswordArguments {
+    email: String,
+}
+
+#[derive(cynic::QueryFragment, Debug, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "MutationRoot",
+    argument_struct = "ForgotPasswordArguments"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ForgotPasswordConnection {
+    #[arguments(email = args.email.clone())]
+    request_password_reset: String,
+}
+
+#[derive(Debug)]
+pub enum Msg {
+    RequestReset,
+    ReceiveResponse(Result<GraphQLResponse<ForgotPasswordConnection>, anyhow::Error>),
+    EmailInputReceived(String),
+}
+
+pub struct ForgotPasswordModel {
+    fetch_task: Option<FetchTask>,
+    link: ComponentLink<Self>,
+    email: String,
+    email_error: Option<String>,
+    error: Option<String>,
+    sent: bool,
+}
+
+impl ForgotPasswordModel {
+    fn view_email_error(&self) -> Html {
+        if let Some(ref message) = self.email_error {
+            html! {
+                <p class="mt-1 text-red-500 text-sm">{ message }</p>
+            }
+        } else {
+            html! {}
+        }
+    }
+
+    fn view_error(&self) -> Html {
+        if let Some(ref error) = self.error {
+            html! {
+                <p class="mt-1 text-red-500 text-sm">{ error }</p>
+            }
+        } else {
+            html! {}
+        }
+    }
+
+    fn view_form(&self) -> Html {
+        if self.sent {
+            return html! {
+                <p class="text-sm text-gray-700">
+                  {"If an account exists for this email address, we have sent it a link to reset the password. The link is valid for one hour."}
+                </p>
+            };
+        }
+
+        html! {
+          <div class="space-y-6">
+            <div>
+              <label htmlFor="email" class="block text-sm font-medium text-gray-700">
+                {"Email address"}
+              </label>
+              <div class="mt-1">
+                <input
+                  id="email"
+                  name="email"
+                  type="email"
+                  autoComplete="email"
+                  class="appearance-none block w-full px-3 py-2 border border-gray-300 rounded-md shadow-sm placeholder-gray-400 focus:outline-none focus:ring-indigo-500 focus:border-indigo-500 sm:text-sm"
+                  placeholder="henry@ford.com"
+                  oninput=self.link.callback(|input_data: InputData| Msg::EmailInputReceived(input_data.value))
+                  value=&self.email
+                />
+              </div>
+              { self.view_email_error() }
+            </div>
+            <div>
+              <button
+                class="w-full flex justify-center py-3 px-4 border border-transparent rounded-md shadow-sm text-sm font-medium text-white bg-indigo-600 hover:bg-indigo-700 focus:outline-none focus:ring-2 focus:ring-offset-2 focus:ring-indigo-500"
+                onclick=self.link.callback(|_| Msg::RequestReset)
+              >
+                { if self.fetch_task.is_some() { "Sending..." } else { "Send reset link" } }
+              </button>
+              { self.view_error() }
+            </div>
+          </div>
+        }
+    }
+}
+
+impl Component for ForgotPasswordModel {
+    type Message = Msg;
+    type Properties = ();
+
+    fn create(_props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Self {
+            fetch_task: None,
+            link,
+            email: String::from(""),
+            email_error: None,
+            error: None,
+            sent: false,
+        }
+    }
+
+    fn change(&mut self, _props: Self::Properties) -> bool {
+        false
+    }
+
+    fn update(&mut self, msg: Self::Message) -> bool {
+        match msg {
+            Msg::RequestReset => {
+                if self.email.is_empty() {
+                    self.email_error = Some("Your email is not valid".into());
+                    return true;
+                }
+
+                let operation = ForgotPasswordConnection::build(ForgotPasswordArguments {
+                    email: self.email.clone(),
+                });
+
+                let query = serde_json::to_string(&operation).unwrap();
+
+                let request = Request::post("/graphql")
+                    .header("Content-Type", "application/json")
+                    .body(Ok(query))
+                    .expect("Failed to build request.");
+                let callback = self.link.callback(
+                    |response: Response<
+                        Json<Result<GraphQLResponse<ForgotPasswordConnection>, anyhow::Error>>,
+                    >| {
+                        let Json(data) = response.into_body();
+                        Msg::ReceiveResponse(data)
+                    },
+                );
+                let task = FetchService::fetch(request, callback).expect("failed to start request");
+                self.fetch_task = Some(task);
+            }
+            Msg::ReceiveResponse(response) => {
+                match response {
+                    Ok(graphql_response) => {
+                        if let Some(errors) = graphql_response.errors {
+                            self.error =
+                                Some(errors.into_iter().map(|error| error.message).collect());
+                        }
+                        if graphql_response.data.is_some() {
+                            self.sent = true;
+                        }
+                    }
+                    Err(error) => self.error = Some(error.to_string()),
+                }
+                self.fetch_task = None;
+            }
+            Msg::EmailInputReceived(value) => {
+                self.error = None;
+                self.email_error = None;
+                self.email = value;
+            }
+        }
+        true
+    }
+
+    fn view(&self) -> Html {
+        html! {
+          <div class="min-h-screen bg-white flex">
+            <div class="flex-1 flex flex-col justify-center py-12 px-4 sm:px-6 lg:flex-none lg:px-20 xl:px-24">
+              <div class="mx-auto w-full max-w-sm lg:w-96">
+                <div>
+                  <h2 class="mt-6 text-3xl font-extrabold text-gray-900">
+                    {"Forgot your password?"}
+                  </h2>
+                </div>
+                <div class="mt-8">
+                  { self.view_form() }
+                </div>
+                <div class="mt-6 text-sm">
+                  <a href="/sign/in" class="font-medium text-indigo-600 hover:text-indigo-500">
+                    {"Back to sign in"}
+                  </a>
+                </div>
+              </div>
+            </div>
+          </div>
+        }
+    }
+}
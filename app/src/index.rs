@@ -1,11 +1,11 @@
+use crate::author::Author;
 use crate::components::toolbar::ToolbarModel;
 use crate::post::Post;
 use crate::query_dsl;
-use crate::Jsonobject;
+use crate::settings::SiteSettings;
 use cynic::GraphQLResponse;
 use cynic::QueryBuilder;
 use serde::Deserialize;
-use serde_json::{value::Value, Map};
 use yew::format::Json;
 use yew::prelude::*;
 use yew::services::console::ConsoleService;
@@ -21,19 +21,19 @@ use yew::services::FetchService;
 #[serde(rename_all = "camelCase")]
 pub struct PostsConnection {
     posts: Vec<Post>,
-    settings: Jsonobject,
+    site_settings: SiteSettings,
 }
 
 pub struct IndexModel {
     posts: Vec<Post>,
-    settings: Map<String, Value>,
+    site_settings: SiteSettings,
     fetch_target: Option<FetchTask>,
 }
 
 impl IndexModel {
     pub fn render_post(&self, post: &Post) -> Html {
         html! {
-          <a href={format!("/post/{}", post.id)}>
+          <a href={format!("/post/{}", post.slug)}>
           <li class="bg-white px-4 py-6 shadow sm:p-6 sm:rounded-lg mt-4">
               <article aria-labelledby="question-title-81614">
                 <div>
@@ -42,14 +42,21 @@ impl IndexModel {
                   </h2>
                   <div class="flex space-x-3">
                   <div class="flex-shrink-0">
-                    <img class="h-10 w-10 rounded-full" src="https://images.unsplash.com/photo-1506794778202-cad84cf45f1d?ixlib=rb-1.2.1&amp;ixid=eyJhcHBfaWQiOjEyMDd9&amp;auto=format&amp;fit=facearea&amp;facepad=2&amp;w=256&amp;h=256&amp;q=80" alt="" />
+                    { post.author.as_ref().map_or_else(|| Author::view_former_avatar("h-10 w-10"), |author| author.view_avatar("h-10 w-10")) }
                   </div>
                   <div class="min-w-0 flex-1">
                     <p class="text-sm font-medium text-gray-900">
-                      <a href="#" class="hover:underline">{"Dries Vincent"}</a>
+                      {
+                          match post.author {
+                              Some(ref author) => html! {
+                                <a href={author.href()} class="hover:underline">{&author.display_name}</a>
+                              },
+                              None => html! {{"Former author"}},
+                          }
+                      }
                     </p>
                     <p class="text-sm text-gray-500">
-                      <a href="#" class="hover:underline">{post.created_at.0.format("%Y-%m-%d")}</a>
+                      <a href="#" class="hover:underline">{self.site_settings.format_date(&post.created_at)}</a>
                     </p>
                   </div>
                 </div>
@@ -123,7 +130,7 @@ impl Component for IndexModel {
         let target = FetchService::fetch(request, callback).expect("failed to start request");
         Self {
             posts: vec![],
-            settings: Map::new(),
+            site_settings: SiteSettings::default(),
             fetch_target: Some(target),
         }
     }
@@ -135,10 +142,7 @@ impl Component for IndexModel {
                     Ok(graphql_response) => match graphql_response.data {
                         Some(data) => {
                             self.posts = data.posts;
-                            self.settings = match data.settings.0.as_object() {
-                                Some(map) => map.clone(),
-                                None => Map::new(),
-                            };
+                            self.site_settings = data.site_settings;
                         }
                         None => {}
                     },
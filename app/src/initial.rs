@@ -1,5 +1,5 @@
 use crate::query_dsl;
-use cynic::GraphQLResponse;
+use crate::response::ApiResponse;
 use cynic::MutationBuilder;
 use serde::Deserialize;
 use yew::{
@@ -13,7 +13,6 @@ pub struct InitialArguments {
     email: String,
     name: String,
     password: String,
-    key: String,
     title: String,
 }
 
@@ -25,17 +24,15 @@ pub struct InitialArguments {
     argument_struct = "InitialArguments"
 )]
 #[serde(rename_all = "camelCase")]
-pub struct SignUpConnection {
-    #[arguments(email = args.email.clone(), name = args.name.clone(), password = args.password.clone())]
-    sign_up: String,
-    #[arguments(key = args.key.clone(), value = args.title.clone())]
-    add_setting: String,
+pub struct InstallConnection {
+    #[arguments(email = args.email.clone(), name = args.name.clone(), password = args.password.clone(), title = args.title.clone())]
+    install: String,
 }
 
 #[derive(Debug)]
 pub enum Msg {
     SignUp,
-    ReceiveResponse(Result<GraphQLResponse<SignUpConnection>, anyhow::Error>),
+    ReceiveResponse(Result<ApiResponse<InstallConnection>, anyhow::Error>),
     BlogInputReceived(String),
     EmailInputReceived(String),
     NameInputReceived(String),
@@ -46,7 +43,7 @@ pub enum Msg {
 #[derive(Debug)]
 pub struct InitialModel {
     fetch_task: Option<FetchTask>,
-    iss: Option<SignUpConnection>,
+    iss: Option<InstallConnection>,
     link: ComponentLink<Self>,
     email: String,
     email_error: Option<String>,
@@ -68,7 +65,7 @@ impl InitialModel {
                 html! {
                     <>
                         <p>{ "The ISS is at:" }</p>
-                        <p>{ format!("Latitude: {}", space_station.sign_up) }</p>
+                        <p>{ format!("Latitude: {}", space_station.install) }</p>
                     </>
                 }
             }
@@ -260,8 +257,7 @@ impl Component for InitialModel {
                     return true;
                 }
 
-                let operation = SignUpConnection::build(InitialArguments {
-                    key: String::from("title"),
+                let operation = InstallConnection::build(InitialArguments {
                     title: self.blog.clone(),
                     email: self.email.clone(),
                     name: self.name.clone(),
@@ -277,7 +273,7 @@ impl Component for InitialModel {
                 // 2. construct a callback
                 let callback = self.link.callback(
                     |response: Response<
-                        Json<Result<GraphQLResponse<SignUpConnection>, anyhow::Error>>,
+                        Json<Result<ApiResponse<InstallConnection>, anyhow::Error>>,
                     >| {
                         let Json(data) = response.into_body();
                         Msg::ReceiveResponse(data)
@@ -294,16 +290,11 @@ impl Component for InitialModel {
             ReceiveResponse(response) => {
                 match response {
                     Ok(graphql_response) => {
-                        if graphql_response.errors.is_some() {
-                            self.error = Some(
-                                graphql_response
-                                    .errors
-                                    .unwrap()
-                                    .into_iter()
-                                    .map(|error| error.message)
-                                    .collect(),
-                            );
-                        }
+                        self.blog_error = graphql_response.field_error("title");
+                        self.email_error = graphql_response.field_error("email");
+                        self.name_error = graphql_response.field_error("name");
+                        self.password_error = graphql_response.field_error("password");
+                        self.error = graphql_response.other_errors();
                         if graphql_response.data.is_some() {
                             self.success = Some("OK".into());
                             self.email = String::from("");
@@ -1,14 +1,24 @@
 #![recursion_limit = "2048"]
+use accept_invitation::AcceptInvitationModel;
+use account_profile::AccountProfileModel;
+use account_security::AccountSecurityModel;
+use api_tokens::ApiTokensModel;
+use author::AuthorModel;
 use cynic::GraphQLResponse;
 use cynic::QueryBuilder;
+use forgot_password::ForgotPasswordModel;
 use index::IndexModel;
 use initial::InitialModel;
 use new_post::NewPostModel;
 use post::PostModel;
+use reset_password::ResetPasswordModel;
 use serde::Deserialize;
-use serde_json::{value::Value, Map};
+use settings::SiteSettings;
 use sign_in::SignInModel;
+use tag::TagModel;
+use trash::TrashModel;
 use update_post::UpdatePostModel;
+use verify_email::VerifyEmailModel;
 use wasm_bindgen::prelude::*;
 use yew::format::Json;
 use yew::prelude::*;
@@ -17,20 +27,32 @@ use yew::services::fetch::{FetchTask, Request, Response};
 use yew::services::FetchService;
 use yew_router::{prelude::*, Switch};
 
-#[derive(cynic::Scalar, Deserialize)]
+#[derive(cynic::Scalar, Clone, Deserialize)]
 pub struct DateTime(chrono::DateTime<chrono::Utc>);
 
 #[derive(cynic::Scalar, Deserialize)]
 pub struct Jsonobject(serde_json::value::Value);
 
+mod accept_invitation;
+mod account_profile;
+mod account_security;
+mod api_tokens;
+mod author;
 mod authorization;
 mod components;
+mod forgot_password;
 mod index;
 mod initial;
 mod new_post;
 mod post;
+mod reset_password;
+mod response;
+mod settings;
 mod sign_in;
+mod tag;
+mod trash;
 mod update_post;
+mod verify_email;
 
 pub mod query_dsl {
     use crate::{DateTime, Jsonobject};
@@ -46,11 +68,12 @@ pub mod query_dsl {
 )]
 #[serde(rename_all = "camelCase")]
 pub struct SettingsConnection {
-    settings: Jsonobject,
+    installed: bool,
+    site_settings: SiteSettings,
 }
 
 pub struct RootModel {
-    settings: Map<String, Value>,
+    installed: bool,
     fetch_target: Option<FetchTask>,
 }
 
@@ -81,7 +104,7 @@ impl Component for RootModel {
         let target = FetchService::fetch(request, callback).expect("failed to start request");
 
         Self {
-            settings: Map::new(),
+            installed: false,
             fetch_target: Some(target),
         }
     }
@@ -92,10 +115,12 @@ impl Component for RootModel {
                 match response {
                     Ok(graphql_response) => match graphql_response.data {
                         Some(data) => {
-                            self.settings = match data.settings.0.as_object() {
-                                Some(map) => map.clone(),
-                                None => Map::new(),
-                            };
+                            self.installed = data.installed;
+                            if let Some(document) =
+                                web_sys::window().and_then(|window| window.document())
+                            {
+                                document.set_title(&data.site_settings.title);
+                            }
                         }
                         None => {}
                     },
@@ -119,21 +144,31 @@ impl Component for RootModel {
             return html! {"Loading..."};
         }
 
-        match self.settings.get("title") {
-            Some(_title) => html! {
+        match self.installed {
+            true => html! {
             <Router<AppRoute, ()>
                 render = Router::render(|switch: AppRoute| {
                     match switch {
                         AppRoute::Index => html!{<IndexModel />},
-                        AppRoute::Post(id) => html!{<PostModel id={id} />},
+                        AppRoute::Post(slug) => html!{<PostModel slug={slug} />},
                         AppRoute::UpdatePost(id) => html!{<UpdatePostModel id={id} />},
                        AppRoute::SignIn => html!{<SignInModel />},
                         AppRoute::NewPost => html!{<NewPostModel />},
+                        AppRoute::Trash => html!{<TrashModel />},
+                        AppRoute::ForgotPassword => html!{<ForgotPasswordModel />},
+                        AppRoute::ResetPassword(token) => html!{<ResetPasswordModel token={token} />},
+                        AppRoute::VerifyEmail(token) => html!{<VerifyEmailModel token={token} />},
+                        AppRoute::Author(id) => html!{<AuthorModel id={id} />},
+                        AppRoute::Tag(slug) => html!{<TagModel slug={slug} />},
+                        AppRoute::AccountProfile => html!{<AccountProfileModel />},
+                        AppRoute::AccountSecurity => html!{<AccountSecurityModel />},
+                        AppRoute::ApiTokens => html!{<ApiTokensModel />},
+                        AppRoute::AcceptInvitation(token) => html!{<AcceptInvitationModel token={token} />},
                     }
                 })
               />
             },
-            None => html! {<InitialModel />},
+            false => html! {<InitialModel />},
         }
     }
 }
@@ -142,12 +177,32 @@ impl Component for RootModel {
 pub enum AppRoute {
     #[to = "/posts/new"]
     NewPost,
+    #[to = "/posts/trash"]
+    Trash,
     #[to = "/posts/update/{id}"]
     UpdatePost(i32),
-    #[to = "/post/{id}"]
-    Post(i32),
+    #[to = "/post/{slug}"]
+    Post(String),
     #[to = "/sign/in"]
     SignIn,
+    #[to = "/password/forgot"]
+    ForgotPassword,
+    #[to = "/password/reset/{token}"]
+    ResetPassword(String),
+    #[to = "/email/verify/{token}"]
+    VerifyEmail(String),
+    #[to = "/author/{id}"]
+    Author(i32),
+    #[to = "/tag/{slug}"]
+    Tag(String),
+    #[to = "/account/profile"]
+    AccountProfile,
+    #[to = "/account/security"]
+    AccountSecurity,
+    #[to = "/account/tokens"]
+    ApiTokens,
+    #[to = "/invite/{token}"]
+    AcceptInvitation(String),
     #[to = "/"]
     Index,
 }
@@ -1,5 +1,5 @@
 use crate::components::toolbar::ToolbarModel;
-use crate::post::Post;
+use crate::post::{Post, PostStatus};
 use crate::query_dsl;
 use crate::AppRoute;
 use cynic::GraphQLResponse;
@@ -21,6 +21,7 @@ pub struct NewPostArguments {
     text: String,
     slug: String,
     summary: String,
+    status: PostStatus,
 }
 
 #[derive(cynic::QueryFragment, Deserialize)]
@@ -32,7 +33,7 @@ pub struct NewPostArguments {
 )]
 #[serde(rename_all = "camelCase")]
 pub struct NewPostConnection {
-    #[arguments(title = args.title.clone(), text = args.text.clone(), slug = args.slug.clone(), summary = args.summary.clone())]
+    #[arguments(title = args.title.clone(), text = args.text.clone(), slug = args.slug.clone(), summary = args.summary.clone(), status = Some(args.status))]
     new_post: Post,
 }
 
@@ -146,7 +147,7 @@ impl NewPostModel {
 }
 
 pub enum Msg {
-    SubmitNewPost,
+    SubmitNewPost(PostStatus),
     ChangeTitle(String),
     ChangeSlug(String),
     ChangeSummary(String),
@@ -179,7 +180,7 @@ impl Component for NewPostModel {
 
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match msg {
-            Msg::SubmitNewPost => {
+            Msg::SubmitNewPost(status) => {
                 let storage = StorageService::new(Area::Local).unwrap();
 
                 let token: String = match storage.restore("auth_token") {
@@ -200,6 +201,7 @@ impl Component for NewPostModel {
                     text: self.text.clone(),
                     slug: self.slug.clone(),
                     summary: self.summary.clone(),
+                    status,
                 });
 
                 let query = serde_json::to_string(&operation).unwrap();
@@ -258,7 +260,7 @@ impl Component for NewPostModel {
                             let post = graphql_response.data.unwrap().new_post;
                             self.success = Some("OK".into());
                             self.router_agent
-                                .send(ChangeRoute(AppRoute::Post(post.id).into()));
+                                .send(ChangeRoute(AppRoute::Post(post.slug).into()));
                             self.success = Some("OK".into());
                             self.text = String::from("");
                             self.title = String::from("");
@@ -366,8 +368,14 @@ impl Component for NewPostModel {
                       <button type="button" class="bg-white py-2 px-4 border border-gray-300 rounded-md shadow-sm text-sm font-medium text-gray-700 hover:bg-gray-50 focus:outline-none focus:ring-2 focus:ring-offset-2 focus:ring-indigo-500">
                         {"Cancel"}
                       </button>
-                      <button onclick=self.link.callback(|_| Msg::SubmitNewPost) class="ml-3 inline-flex justify-center py-2 px-4 border border-transparent shadow-sm text-sm font-medium rounded-md text-white bg-indigo-600 hover:bg-indigo-700 focus:outline-none focus:ring-2 focus:ring-offset-2 focus:ring-indigo-500">
-                        {"Submit New Post"}
+                      <button onclick=self.link.callback(|_| Msg::SubmitNewPost(PostStatus::Draft)) class="ml-3 bg-white py-2 px-4 border border-gray-300 rounded-md shadow-sm text-sm font-medium text-gray-700 hover:bg-gray-50 focus:outline-none focus:ring-2 focus:ring-offset-2 focus:ring-indigo-500">
+                        {"Save draft"}
+                      </button>
+                      <button onclick=self.link.callback(|_| Msg::SubmitNewPost(PostStatus::Pending)) class="ml-3 bg-white py-2 px-4 border border-gray-300 rounded-md shadow-sm text-sm font-medium text-gray-700 hover:bg-gray-50 focus:outline-none focus:ring-2 focus:ring-offset-2 focus:ring-indigo-500">
+                        {"Submit for review"}
+                      </button>
+                      <button onclick=self.link.callback(|_| Msg::SubmitNewPost(PostStatus::Published)) class="ml-3 inline-flex justify-center py-2 px-4 border border-transparent shadow-sm text-sm font-medium rounded-md text-white bg-indigo-600 hover:bg-indigo-700 focus:outline-none focus:ring-2 focus:ring-offset-2 focus:ring-indigo-500">
+                        {"Publish"}
                       </button>
                     </div>
                   </div>
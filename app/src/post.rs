@@ -1,3 +1,4 @@
+use crate::author::Author;
 use crate::components::toolbar::ToolbarModel;
 use crate::query_dsl;
 use cynic::GraphQLResponse;
@@ -11,6 +12,21 @@ use yew::services::fetch::{FetchTask, Request, Response};
 use yew::services::FetchService;
 use yew::virtual_dom::VNode;
 
+#[derive(cynic::Enum, Clone, Copy, Debug, PartialEq)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "PostStatus",
+    rename_all = "SCREAMING_SNAKE_CASE"
+)]
+pub enum PostStatus {
+    Draft,
+    Pending,
+    Published,
+    Scheduled,
+    Archived,
+}
+
 #[derive(cynic::QueryFragment, Deserialize)]
 #[cynic(
     schema_path = "schema.graphql",
@@ -25,6 +41,15 @@ pub struct Post {
     pub text: String,
     pub summary: String,
     pub created_at: crate::DateTime,
+    pub status: PostStatus,
+    pub published_at: Option<crate::DateTime>,
+    pub version: i32,
+    pub cover_image_url: Option<String>,
+    pub tags: Vec<String>,
+    pub category_id: Option<i32>,
+    pub seo_title: Option<String>,
+    pub seo_description: Option<String>,
+    pub author: Option<Author>,
 }
 
 #[derive(cynic::FragmentArguments)]
@@ -45,17 +70,120 @@ pub struct PostConnection {
     pub post: Option<Post>,
 }
 
+#[derive(cynic::FragmentArguments)]
+pub struct PostBySlugArguments {
+    pub slug: String,
+}
+
+#[derive(cynic::QueryFragment, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "QueryRoot",
+    argument_struct = "PostBySlugArguments"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct PostBySlugConnection {
+    #[arguments(slug = args.slug.clone())]
+    pub post_by_slug: Option<Post>,
+}
+
+#[derive(cynic::QueryFragment, Deserialize)]
+#[cynic(schema_path = "schema.graphql", query_module = "query_dsl")]
+pub struct Tag {
+    pub slug: String,
+    pub name: String,
+}
+
+#[derive(cynic::QueryFragment, Deserialize)]
+#[cynic(schema_path = "schema.graphql", query_module = "query_dsl")]
+pub struct Category {
+    pub id: i32,
+    pub name: String,
+}
+
+#[derive(cynic::QueryFragment, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "QueryRoot",
+    argument_struct = "PostArguments"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct PostTaxonomyConnection {
+    #[arguments(post_id = args.post_id)]
+    pub post_tags: Vec<Tag>,
+    #[arguments(post_id = args.post_id)]
+    pub post_breadcrumbs: Vec<Category>,
+}
+
 #[derive(Clone, PartialEq, Properties)]
 pub struct PostModelProps {
-    pub id: i32,
+    pub slug: String,
 }
 
 pub struct PostModel {
     fetch_target: Option<FetchTask>,
+    link: ComponentLink<Self>,
     post: Option<Post>,
+    tags: Vec<Tag>,
+    breadcrumbs: Vec<Category>,
 }
 
 impl PostModel {
+    fn load_taxonomy(&mut self, post_id: i32) {
+        let operation = PostTaxonomyConnection::build(PostArguments { post_id });
+        let request = Request::post("/graphql")
+            .header("Content-Type", "application/json")
+            .body(Ok(serde_json::to_string(&operation).unwrap()))
+            .expect("Failed to build request.");
+        let callback = self.link.callback(
+            |response: Response<
+                Json<Result<GraphQLResponse<PostTaxonomyConnection>, anyhow::Error>>,
+            >| {
+                let Json(data) = response.into_body();
+                Msg::ReceiveTaxonomy(data)
+            },
+        );
+        let target = FetchService::fetch(request, callback).expect("failed to start request");
+        self.fetch_target = Some(target);
+    }
+
+    // The category of the post and the ones above it, starting from the home
+    // page.
+    pub fn breadcrumbs(&self) -> Html {
+        if self.breadcrumbs.is_empty() {
+            return html! {};
+        }
+        html! {
+          <nav class="flex justify-center text-sm text-gray-500" aria-label="Breadcrumb">
+            <ol class="flex items-center space-x-2">
+              <li><a href="/" class="hover:text-gray-700">{"Home"}</a></li>
+              { for self.breadcrumbs.iter().map(|category| html! {
+                <li class="flex items-center space-x-2">
+                  <span aria-hidden="true">{"/"}</span>
+                  <span>{ &category.name }</span>
+                </li>
+              }) }
+            </ol>
+          </nav>
+        }
+    }
+
+    pub fn tags(&self) -> Html {
+        html! {
+          <ul class="mt-4 flex flex-wrap justify-center">
+            { for self.tags.iter().map(|tag| html! {
+              <li class="m-1">
+                <a href={format!("/tag/{}", tag.slug)} class="inline-flex items-center px-3 py-0.5 rounded-full text-sm font-medium bg-indigo-100 text-indigo-800 hover:bg-indigo-200">
+                  { &tag.name }
+                </a>
+              </li>
+            }) }
+          </ul>
+        }
+    }
+
     pub fn title(&self) -> Html {
         match &self.post {
             Some(post) => {
@@ -65,6 +193,21 @@ impl PostModel {
         }
     }
 
+    pub fn byline(&self) -> Html {
+        match &self.post {
+            Some(Post {
+                author: Some(author),
+                ..
+            }) => html! {
+              <a href={author.href()} class="mt-4 flex items-center justify-center space-x-3 text-sm font-medium text-gray-900 hover:underline">
+                { author.view_avatar("h-8 w-8") }
+                <span>{ &author.display_name }</span>
+              </a>
+            },
+            _ => html! {},
+        }
+    }
+
     pub fn markdown_node(&self) -> Html {
         match &self.post {
             Some(post) => {
@@ -87,14 +230,15 @@ impl PostModel {
 }
 
 pub enum Msg {
-    ReceiveResponse(Result<GraphQLResponse<PostConnection>, anyhow::Error>),
+    ReceiveResponse(Result<GraphQLResponse<PostBySlugConnection>, anyhow::Error>),
+    ReceiveTaxonomy(Result<GraphQLResponse<PostTaxonomyConnection>, anyhow::Error>),
 }
 
 impl Component for PostModel {
     type Message = Msg;
     type Properties = PostModelProps;
     fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
-        let operation = PostConnection::build(PostArguments { post_id: props.id });
+        let operation = PostBySlugConnection::build(PostBySlugArguments { slug: props.slug });
 
         let query = serde_json::to_string(&operation).unwrap();
 
@@ -103,7 +247,9 @@ impl Component for PostModel {
             .body(Ok(query))
             .expect("Failed to build request.");
         let callback = link.callback(
-            |response: Response<Json<Result<GraphQLResponse<PostConnection>, anyhow::Error>>>| {
+            |response: Response<
+                Json<Result<GraphQLResponse<PostBySlugConnection>, anyhow::Error>>,
+            >| {
                 let Json(data) = response.into_body();
                 Msg::ReceiveResponse(data)
             },
@@ -111,16 +257,34 @@ impl Component for PostModel {
         let target = FetchService::fetch(request, callback).expect("failed to start request");
         Self {
             fetch_target: Some(target),
+            link,
             post: None,
+            tags: vec![],
+            breadcrumbs: vec![],
         }
     }
 
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match msg {
             Msg::ReceiveResponse(response) => {
+                self.fetch_target = None;
+                match response {
+                    Ok(graphql_response) => {
+                        self.post = graphql_response.data.and_then(|data| data.post_by_slug);
+                        if let Some(post_id) = self.post.as_ref().map(|post| post.id) {
+                            self.load_taxonomy(post_id);
+                        }
+                    }
+                    Err(error) => ConsoleService::info(&format!("Error: {}", error.to_string())),
+                };
+            }
+            Msg::ReceiveTaxonomy(response) => {
                 match response {
                     Ok(graphql_response) => {
-                        self.post = graphql_response.data.and_then(|data| data.post);
+                        if let Some(data) = graphql_response.data {
+                            self.tags = data.post_tags;
+                            self.breadcrumbs = data.post_breadcrumbs;
+                        }
                     }
                     Err(error) => ConsoleService::info(&format!("Error: {}", error.to_string())),
                 };
@@ -172,10 +336,13 @@ impl Component for PostModel {
             </div>
             <div class="relative px-4 sm:px-6 lg:px-8">
               <div class="text-lg max-w-prose mx-auto">
-                <h1>
+                {self.breadcrumbs()}
+                <h1 class="mt-4">
                   <span class="block text-base text-center text-indigo-600 font-semibold tracking-wide uppercase">{"Introducing"}</span>
                   <span class="mt-2 block text-3xl text-center leading-8 font-extrabold tracking-tight text-gray-900 sm:text-4xl">{self.title()}</span>
                 </h1>
+                {self.byline()}
+                {self.tags()}
               </div>
               <div class="text-lg max-w-prose mx-auto">
               <div class="mt-6 prose prose-indigo prose-lg text-gray-500 mx-auto">
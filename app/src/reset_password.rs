@@ -0,0 +1,227 @@
+use crate::query_dsl;
+use crate::response::ApiResponse;
+use crate::AppRoute;
+use cynic::MutationBuilder;
+use serde::Deserialize;
+use yew::{
+    format::Json,
+    prelude::*,
+    services::fetch::{FetchService, FetchTask, Request, Response},
+};
+use yew_router::{agent::RouteRequest::ChangeRoute, prelude::*};
+
+#[derive(cynic::FragmentArguments)]
+pub struct ResetPasswordArguments {
+    token: String,
+    new_password: String,
+}
+
+#[derive(cynic::QueryFragment, Debug, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "MutationRoot",
+    argument_struct = "ResetPasswordArguments"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ResetPasswordConnection {
+    #[arguments(token = args.token.clone(), new_password = args.new_password.clone())]
+    reset_password: String,
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct ResetPasswordProps {
+    pub token: String,
+}
+
+#[derive(Debug)]
+pub enum Msg {
+    ResetPassword,
+    ReceiveResponse(Result<ApiResponse<ResetPasswordConnection>, anyhow::Error>),
+    PasswordInputReceived(String),
+    ConfirmationInputReceived(String),
+    Ignore,
+}
+
+pub struct ResetPasswordModel {
+    props: ResetPasswordProps,
+    fetch_task: Option<FetchTask>,
+    link: ComponentLink<Self>,
+    password: String,
+    password_error: Option<String>,
+    confirmation: String,
+    error: Option<String>,
+    router_agent: Box<dyn Bridge<RouteAgent>>,
+}
+
+impl ResetPasswordModel {
+    fn view_password_error(&self) -> Html {
+        if let Some(ref message) = self.password_error {
+            html! {
+                <p class="mt-1 text-red-500 text-sm">{ message }</p>
+            }
+        } else {
+            html! {}
+        }
+    }
+
+    fn view_error(&self) -> Html {
+        if let Some(ref error) = self.error {
+            html! {
+                <p class="mt-1 text-red-500 text-sm">{ error }</p>
+            }
+        } else {
+            html! {}
+        }
+    }
+}
+
+impl Component for ResetPasswordModel {
+    type Message = Msg;
+    type Properties = ResetPasswordProps;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Self {
+            props,
+            fetch_task: None,
+            password: String::from(""),
+            password_error: None,
+            confirmation: String::from(""),
+            error: None,
+            router_agent: RouteAgent::bridge(link.callback(|_| Msg::Ignore)),
+            link,
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> bool {
+        if self.props != props {
+            self.props = props;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> bool {
+        match msg {
+            Msg::ResetPassword => {
+                if self.password.is_empty() {
+                    self.password_error = Some("Your password is not valid".into());
+                    return true;
+                }
+
+                if self.password != self.confirmation {
+                    self.password_error = Some("The passwords do not match".into());
+                    return true;
+                }
+
+                let operation = ResetPasswordConnection::build(ResetPasswordArguments {
+                    token: self.props.token.clone(),
+                    new_password: self.password.clone(),
+                });
+
+                let query = serde_json::to_string(&operation).unwrap();
+
+                let request = Request::post("/graphql")
+                    .header("Content-Type", "application/json")
+                    .body(Ok(query))
+                    .expect("Failed to build request.");
+                let callback = self.link.callback(
+                    |response: Response<
+                        Json<Result<ApiResponse<ResetPasswordConnection>, anyhow::Error>>,
+                    >| {
+                        let Json(data) = response.into_body();
+                        Msg::ReceiveResponse(data)
+                    },
+                );
+                let task = FetchService::fetch(request, callback).expect("failed to start request");
+                self.fetch_task = Some(task);
+            }
+            Msg::ReceiveResponse(response) => {
+                match response {
+                    Ok(graphql_response) => {
+                        self.password_error = graphql_response.field_error("newPassword");
+                        self.error = graphql_response.other_errors();
+                        if graphql_response.data.is_some() {
+                            self.router_agent.send(ChangeRoute(AppRoute::SignIn.into()));
+                        }
+                    }
+                    Err(error) => self.error = Some(error.to_string()),
+                }
+                self.fetch_task = None;
+            }
+            Msg::PasswordInputReceived(value) => {
+                self.error = None;
+                self.password_error = None;
+                self.password = value;
+            }
+            Msg::ConfirmationInputReceived(value) => {
+                self.error = None;
+                self.password_error = None;
+                self.confirmation = value;
+            }
+            Msg::Ignore => return false,
+        }
+        true
+    }
+
+    fn view(&self) -> Html {
+        html! {
+          <div class="min-h-screen bg-white flex">
+            <div class="flex-1 flex flex-col justify-center py-12 px-4 sm:px-6 lg:flex-none lg:px-20 xl:px-24">
+              <div class="mx-auto w-full max-w-sm lg:w-96">
+                <div>
+                  <h2 class="mt-6 text-3xl font-extrabold text-gray-900">
+                    {"Choose a new password"}
+                  </h2>
+                </div>
+                <div class="mt-8 space-y-6">
+                  <div>
+                    <label htmlFor="password" class="block text-sm font-medium text-gray-700">
+                      {"New password"}
+                    </label>
+                    <div class="mt-1">
+                      <input
+                        id="password"
+                        name="password"
+                        type="password"
+                        autoComplete="new-password"
+                        class="appearance-none block w-full px-3 py-2 border border-gray-300 rounded-md shadow-sm placeholder-gray-400 focus:outline-none focus:ring-indigo-500 focus:border-indigo-500 sm:text-sm"
+                        oninput=self.link.callback(|input_data: InputData| Msg::PasswordInputReceived(input_data.value))
+                        value=&self.password
+                      />
+                    </div>
+                  </div>
+                  <div>
+                    <label htmlFor="confirmation" class="block text-sm font-medium text-gray-700">
+                      {"Confirm new password"}
+                    </label>
+                    <div class="mt-1">
+                      <input
+                        id="confirmation"
+                        name="confirmation"
+                        type="password"
+                        autoComplete="new-password"
+                        class="appearance-none block w-full px-3 py-2 border border-gray-300 rounded-md shadow-sm placeholder-gray-400 focus:outline-none focus:ring-indigo-500 focus:border-indigo-500 sm:text-sm"
+                        oninput=self.link.callback(|input_data: InputData| Msg::ConfirmationInputReceived(input_data.value))
+                        value=&self.confirmation
+                      />
+                    </div>
+                    { self.view_password_error() }
+                  </div>
+                  <div>
+                    <button
+                      class="w-full flex justify-center py-3 px-4 border border-transparent rounded-md shadow-sm text-sm font-medium text-white bg-indigo-600 hover:bg-indigo-700 focus:outline-none focus:ring-2 focus:ring-offset-2 focus:ring-indigo-500"
+                      onclick=self.link.callback(|_| Msg::ResetPassword)
+                    >
+                      { if self.fetch_task.is_some() { "Saving..." } else { "Reset password" } }
+                    </button>
+                    { self.view_error() }
+                  </div>
+                </div>
+              </div>
+            </div>
+          </div>
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Deserialize, Debug)]
+pub struct ApiResponse<T> {
+    pub data: Option<T>,
+    pub errors: Option<Vec<ApiError>>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ApiError {
+    pub message: String,
+    #[serde(default)]
+    pub extensions: ErrorExtensions,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct ErrorExtensions {
+    pub code: Option<String>,
+    #[serde(default)]
+    pub fields: HashMap<String, String>,
+    #[serde(rename = "currentVersion")]
+    pub current_version: Option<i32>,
+}
+
+impl<T> ApiResponse<T> {
+    pub fn field_error(&self, field: &str) -> Option<String> {
+        self.errors
+            .iter()
+            .flatten()
+            .find_map(|error| error.extensions.fields.get(field).cloned())
+    }
+
+    // The version another editor saved, when the change was rejected because of it.
+    pub fn conflict_version(&self) -> Option<i32> {
+        self.errors
+            .iter()
+            .flatten()
+            .find(|error| error.extensions.code.as_deref() == Some("CONFLICT"))
+            .and_then(|error| error.extensions.current_version)
+    }
+
+    pub fn other_errors(&self) -> Option<String> {
+        let messages: Vec<String> = self
+            .errors
+            .iter()
+            .flatten()
+            .filter(|error| error.extensions.fields.is_empty())
+            .map(|error| error.message.clone())
+            .collect();
+        match messages.is_empty() {
+            true => None,
+            false => Some(messages.join(" ")),
+        }
+    }
+}
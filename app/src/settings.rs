@@ -0,0 +1,34 @@
+use crate::query_dsl;
+use crate::DateTime;
+use serde::Deserialize;
+
+#[derive(cynic::QueryFragment, Deserialize, Clone, Default)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "SiteSettings"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct SiteSettings {
+    pub title: String,
+    pub tagline: String,
+    pub date_format: String,
+}
+
+impl SiteSettings {
+    pub fn heading(&self) -> String {
+        match self.tagline.is_empty() {
+            true => self.title.clone(),
+            false => format!("{} - {}", self.title, self.tagline),
+        }
+    }
+
+    pub fn format_date(&self, date: &DateTime) -> String {
+        let format = match self.date_format.as_str() {
+            "short" => "%b %e, %Y",
+            "long" => "%A, %B %e, %Y",
+            _ => "%Y-%m-%d",
+        };
+        date.0.format(format).to_string()
+    }
+}
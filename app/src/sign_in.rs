@@ -1,7 +1,7 @@
 use crate::query_dsl;
+use crate::response::ApiResponse;
 use crate::AppRoute;
-use cynic::GraphQLResponse;
-use cynic::MutationBuilder;
+use cynic::{MutationBuilder, QueryBuilder};
 use serde::Deserialize;
 use yew::{
     format::Json,
@@ -27,7 +27,52 @@ pub struct SignInArguments {
 #[serde(rename_all = "camelCase")]
 pub struct SignInConnection {
     #[arguments(email = args.email.clone(), password = args.password.clone())]
-    sign_in: String,
+    sign_in: SignInResult,
+}
+
+#[derive(cynic::QueryFragment, Debug, Deserialize)]
+#[cynic(schema_path = "schema.graphql", query_module = "query_dsl")]
+pub struct SignInResult {
+    token: Option<String>,
+    challenge: Option<String>,
+}
+
+#[derive(cynic::FragmentArguments)]
+pub struct CompleteSignInArguments {
+    challenge: String,
+    code: String,
+}
+
+#[derive(cynic::QueryFragment, Debug, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "MutationRoot",
+    argument_struct = "CompleteSignInArguments"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct CompleteSignInConnection {
+    #[arguments(challenge = args.challenge.clone(), code = args.code.clone())]
+    complete_sign_in: String,
+}
+
+#[derive(cynic::QueryFragment, Debug, Deserialize)]
+#[cynic(schema_path = "schema.graphql", query_module = "query_dsl")]
+#[serde(rename_all = "camelCase")]
+pub struct SingleSignOn {
+    name: String,
+    login_url: String,
+}
+
+#[derive(cynic::QueryFragment, Debug, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "QueryRoot"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct SingleSignOnConnection {
+    single_sign_on: Option<SingleSignOn>,
 }
 
 #[derive(PartialEq, Properties, Clone)]
@@ -36,9 +81,13 @@ pub struct Props {}
 #[derive(Debug)]
 pub enum Msg {
     SignIn,
-    ReceiveResponse(Result<GraphQLResponse<SignInConnection>, anyhow::Error>),
+    ReceiveResponse(Result<ApiResponse<SignInConnection>, anyhow::Error>),
+    CompleteSignIn,
+    ReceiveCompleteResponse(Result<ApiResponse<CompleteSignInConnection>, anyhow::Error>),
+    ReceiveSingleSignOn(Result<ApiResponse<SingleSignOnConnection>, anyhow::Error>),
     EmailInputReceived(String),
     PasswordInputReceived(String),
+    CodeInputReceived(String),
     RememberMeInputReceived,
     ClearNotifications,
     Ignore,
@@ -46,24 +95,168 @@ pub enum Msg {
 
 pub struct SignInModel {
     fetch_task: Option<FetchTask>,
+    single_sign_on_task: Option<FetchTask>,
+    single_sign_on: Option<SingleSignOn>,
     sign_in_action: Option<SignInConnection>,
     link: ComponentLink<Self>,
     email: String,
     email_error: Option<String>,
     password: String,
     password_error: Option<String>,
+    challenge: Option<String>,
+    code: String,
+    code_error: Option<String>,
     router_agent: Box<dyn Bridge<RouteAgent>>,
     error: Option<String>,
     success: Option<String>,
 }
 
 impl SignInModel {
+    fn signed_in(&mut self, token: String) {
+        let mut storage = StorageService::new(Area::Local).unwrap();
+        storage.store("auth_token", Ok(token));
+        self.success = Some("OK".into());
+        self.router_agent.send(ChangeRoute(AppRoute::Index.into()));
+        self.email = String::from("");
+        self.password = String::from("");
+        self.challenge = None;
+        self.code = String::from("");
+    }
+
+    fn view_form(&self) -> Html {
+        if self.challenge.is_some() {
+            return self.view_challenge();
+        }
+        html! {
+              <div class="mt-8">
+                <div>
+                  <div class="space-y-6">
+                    <div>
+                      <label
+                        htmlFor="email"
+                        class="block text-sm font-medium text-gray-700"
+                      >
+                        {"Email address"}
+                      </label>
+                      <div class="mt-1">
+                        <input
+                          id="email"
+                          name="email"
+                          type="email"
+                          autoComplete="email"
+                          class="appearance-none block w-full px-3 py-2 border border-gray-300 rounded-md shadow-sm placeholder-gray-400 focus:outline-none focus:ring-indigo-500 focus:border-indigo-500 sm:text-sm"
+                          placeholder="henry@ford.com"
+                          oninput=self.link.callback(|input_data: InputData| Msg::EmailInputReceived(input_data.value))
+                          value=&self.email
+                        />
+                      </div>
+                      { self.view_email_error() }
+                    </div>
+
+                    <div class="space-y-1">
+                      <label
+                        htmlFor="password"
+                        class="block text-sm font-medium text-gray-700"
+                      >
+                        {"Password"}
+                      </label>
+                      <div class="mt-1">
+                        <input
+                          id="password"
+                          name="password"
+                          type="password"
+                          autoComplete="current-password"
+                          class="appearance-none block w-full px-3 py-2 border border-gray-300 rounded-md shadow-sm placeholder-gray-400 focus:outline-none focus:ring-indigo-500 focus:border-indigo-500 sm:text-sm"
+                          placeholder="*************"
+                          oninput=self.link.callback(|input_data: InputData| Msg::PasswordInputReceived(input_data.value))
+                          value=&self.password
+                        />
+                      </div>
+                      { self.view_password_error() }
+                    </div>
+
+                    <div class="flex items-center justify-between">
+                      <div class="flex items-center">
+                        <input
+                          id="remember_me"
+                          name="remember_me"
+                          type="checkbox"
+                          class="h-4 w-4 text-indigo-600 focus:ring-indigo-500 border-gray-300 rounded"
+                          oninput=self.link.callback(|_| Msg::RememberMeInputReceived)
+                        />
+                        <label
+                          htmlFor="remember_me"
+                          class="ml-2 block text-sm text-gray-900"
+                        >
+                          {"Remember me"}
+                        </label>
+                      </div>
+
+                      <div class="text-sm">
+                        <a
+                          href="/password/forgot"
+                          class="font-medium text-indigo-600 hover:text-indigo-500"
+                        >
+                          {"Forgot your password?"}
+                        </a>
+                      </div>
+                    </div>
+
+                    <div>
+                      { self.view_button() }
+                    </div>
+                    { self.view_single_sign_on() }
+                  </div>
+                </div>
+              </div>
+        }
+    }
+
+    fn view_challenge(&self) -> Html {
+        html! {
+              <div class="mt-8 space-y-6">
+                <div>
+                  <label
+                    htmlFor="code"
+                    class="block text-sm font-medium text-gray-700"
+                  >
+                    {"Authentication code"}
+                  </label>
+                  <div class="mt-1">
+                    <input
+                      id="code"
+                      name="code"
+                      type="text"
+                      autoComplete="one-time-code"
+                      class="appearance-none block w-full px-3 py-2 border border-gray-300 rounded-md shadow-sm placeholder-gray-400 focus:outline-none focus:ring-indigo-500 focus:border-indigo-500 sm:text-sm"
+                      placeholder="123456"
+                      oninput=self.link.callback(|input_data: InputData| Msg::CodeInputReceived(input_data.value))
+                      value=&self.code
+                    />
+                  </div>
+                  <p class="mt-1 text-sm text-gray-500">
+                    {"Enter the code from your authenticator app, or one of your recovery codes."}
+                  </p>
+                  { self.view_code_error() }
+                </div>
+                <div>
+                  <button
+                    class="w-full flex justify-center py-3 px-4 border border-transparent rounded-md shadow-sm text-sm font-medium text-white bg-indigo-600 hover:bg-indigo-700 focus:outline-none focus:ring-2 focus:ring-offset-2 focus:ring-indigo-500"
+                    onclick=self.link.callback(|_| Msg::CompleteSignIn)
+                  >
+                    { if self.fetch_task.is_some() { "Verifying..." } else { "Verify" } }
+                  </button>
+                </div>
+              </div>
+        }
+    }
+
     fn view_button(&self) -> Html {
         match self.sign_in_action {
             Some(ref response) => {
                 html! {
                     <>
-                        <p>{ &response.sign_in }</p>
+                        <p>{ response.sign_in.token.clone().unwrap_or_default() }</p>
                     </>
                 }
             }
@@ -80,6 +273,22 @@ impl SignInModel {
         }
     }
 
+    fn view_single_sign_on(&self) -> Html {
+        match self.single_sign_on {
+            Some(ref single_sign_on) => html! {
+              <div>
+                <a
+                  href=single_sign_on.login_url.clone()
+                  class="w-full flex justify-center py-3 px-4 border border-gray-300 rounded-md shadow-sm text-sm font-medium text-gray-700 bg-white hover:bg-gray-50"
+                >
+                  { format!("Sign in with {}", single_sign_on.name) }
+                </a>
+              </div>
+            },
+            None => html! {},
+        }
+    }
+
     fn view_fetching(&self) -> Html {
         if self.fetch_task.is_some() {
             html! { { "Signing in..." } }
@@ -98,6 +307,16 @@ impl SignInModel {
         }
     }
 
+    fn view_code_error(&self) -> Html {
+        if let Some(ref message) = self.code_error {
+            html! {
+                <p class="mt-1 text-red-500 text-sm">{ message }</p>
+            }
+        } else {
+            html! {}
+        }
+    }
+
     fn view_password_error(&self) -> Html {
         if let Some(ref message) = self.password_error {
             html! {
@@ -194,8 +413,26 @@ impl Component for SignInModel {
     type Properties = Props;
 
     fn create(_props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let operation = SingleSignOnConnection::build(());
+        let request = Request::post("/graphql")
+            .header("Content-Type", "application/json")
+            .body(Ok(serde_json::to_string(&operation).unwrap()))
+            .expect("Failed to build request.");
+        let callback = link.callback(
+            |response: Response<
+                Json<Result<ApiResponse<SingleSignOnConnection>, anyhow::Error>>,
+            >| {
+                let Json(data) = response.into_body();
+                Msg::ReceiveSingleSignOn(data)
+            },
+        );
+        let single_sign_on_task =
+            FetchService::fetch(request, callback).expect("failed to start request");
+
         Self {
             fetch_task: None,
+            single_sign_on_task: Some(single_sign_on_task),
+            single_sign_on: None,
             sign_in_action: None,
             error: None,
             success: None,
@@ -203,6 +440,9 @@ impl Component for SignInModel {
             email_error: None,
             password: String::from(""),
             password_error: None,
+            challenge: None,
+            code: String::from(""),
+            code_error: None,
             router_agent: RouteAgent::bridge(link.callback(|_| Msg::Ignore)),
             link,
         }
@@ -239,7 +479,7 @@ impl Component for SignInModel {
                 // 2. construct a callback
                 let callback = self.link.callback(
                     |response: Response<
-                        Json<Result<GraphQLResponse<SignInConnection>, anyhow::Error>>,
+                        Json<Result<ApiResponse<SignInConnection>, anyhow::Error>>,
                     >| {
                         let Json(data) = response.into_body();
                         Msg::ReceiveResponse(data)
@@ -256,24 +496,16 @@ impl Component for SignInModel {
             ReceiveResponse(response) => {
                 match response {
                     Ok(graphql_response) => {
-                        if graphql_response.errors.is_some() {
-                            self.error = Some(
-                                graphql_response
-                                    .errors
-                                    .unwrap()
-                                    .into_iter()
-                                    .map(|error| error.message)
-                                    .collect(),
-                            );
-                        }
-                        if graphql_response.data.is_some() {
-                            let token = graphql_response.data.unwrap().sign_in;
-                            let mut storage = StorageService::new(Area::Local).unwrap();
-                            storage.store("auth_token", Ok(token.clone()));
-                            self.success = Some("OK".into());
-                            self.router_agent.send(ChangeRoute(AppRoute::Index.into()));
-                            self.email = String::from("");
-                            self.password = String::from("");
+                        self.email_error = graphql_response.field_error("email");
+                        self.password_error = graphql_response.field_error("password");
+                        self.error = graphql_response.other_errors();
+                        if let Some(data) = graphql_response.data {
+                            match data.sign_in {
+                                SignInResult {
+                                    token: Some(token), ..
+                                } => self.signed_in(token),
+                                SignInResult { challenge, .. } => self.challenge = challenge,
+                            }
                         }
                     }
                     Err(error) => self.error = Some(error.to_string()),
@@ -283,6 +515,67 @@ impl Component for SignInModel {
                 // 'fetching...'
                 true
             }
+            CompleteSignIn => {
+                let challenge = match self.challenge {
+                    Some(ref challenge) => challenge.clone(),
+                    None => return false,
+                };
+                if self.code.is_empty() {
+                    self.code_error = Some("Enter the code from your authenticator app".into());
+                    return true;
+                }
+
+                let operation = CompleteSignInConnection::build(CompleteSignInArguments {
+                    challenge,
+                    code: self.code.clone(),
+                });
+
+                let query = serde_json::to_string(&operation).unwrap();
+
+                let request = Request::post("/graphql")
+                    .header("Content-Type", "application/json")
+                    .body(Ok(query))
+                    .expect("Failed to build request.");
+                let callback = self.link.callback(
+                    |response: Response<
+                        Json<Result<ApiResponse<CompleteSignInConnection>, anyhow::Error>>,
+                    >| {
+                        let Json(data) = response.into_body();
+                        Msg::ReceiveCompleteResponse(data)
+                    },
+                );
+                let task = FetchService::fetch(request, callback).expect("failed to start request");
+                self.fetch_task = Some(task);
+                true
+            }
+            ReceiveCompleteResponse(response) => {
+                match response {
+                    Ok(graphql_response) => {
+                        self.code_error = graphql_response.field_error("code");
+                        self.error = graphql_response.other_errors();
+                        if let Some(data) = graphql_response.data {
+                            self.signed_in(data.complete_sign_in);
+                        } else if self.code_error.is_none() {
+                            // The challenge expired or was used up, so start over.
+                            self.challenge = None;
+                            self.code = String::from("");
+                        }
+                    }
+                    Err(error) => self.error = Some(error.to_string()),
+                }
+                self.fetch_task = None;
+                true
+            }
+            ReceiveSingleSignOn(response) => {
+                if let Ok(ApiResponse {
+                    data: Some(data), ..
+                }) = response
+                {
+                    self.single_sign_on = data.single_sign_on;
+                }
+                self.single_sign_on_task = None;
+                true
+            }
             ClearNotifications => {
                 self.error = None;
                 self.success = None;
@@ -302,6 +595,13 @@ impl Component for SignInModel {
                 self.password = value;
                 true
             }
+            CodeInputReceived(value) => {
+                self.error = None;
+                self.success = None;
+                self.code_error = None;
+                self.code = value;
+                true
+            }
             RememberMeInputReceived => {
                 self.error = None;
                 self.success = None;
@@ -326,86 +626,7 @@ impl Component for SignInModel {
                 </h2>
              </div>
 
-              <div class="mt-8">
-                <div>
-                  <div class="space-y-6">
-                    <div>
-                      <label
-                        htmlFor="email"
-                        class="block text-sm font-medium text-gray-700"
-                      >
-                        {"Email address"}
-                      </label>
-                      <div class="mt-1">
-                        <input
-                          id="email"
-                          name="email"
-                          type="email"
-                          autoComplete="email"
-                          class="appearance-none block w-full px-3 py-2 border border-gray-300 rounded-md shadow-sm placeholder-gray-400 focus:outline-none focus:ring-indigo-500 focus:border-indigo-500 sm:text-sm"
-                          placeholder="henry@ford.com"
-                          oninput=self.link.callback(|input_data: InputData| Msg::EmailInputReceived(input_data.value))
-                          value=&self.email
-                        />
-                      </div>
-                      { self.view_email_error() }
-                    </div>
-
-                    <div class="space-y-1">
-                      <label
-                        htmlFor="password"
-                        class="block text-sm font-medium text-gray-700"
-                      >
-                        {"Password"}
-                      </label>
-                      <div class="mt-1">
-                        <input
-                          id="password"
-                          name="password"
-                          type="password"
-                          autoComplete="current-password"
-                          class="appearance-none block w-full px-3 py-2 border border-gray-300 rounded-md shadow-sm placeholder-gray-400 focus:outline-none focus:ring-indigo-500 focus:border-indigo-500 sm:text-sm"
-                          placeholder="*************"
-                          oninput=self.link.callback(|input_data: InputData| Msg::PasswordInputReceived(input_data.value))
-                          value=&self.password
-                        />
-                      </div>
-                    </div>
-
-                    <div class="flex items-center justify-between">
-                      <div class="flex items-center">
-                        <input
-                          id="remember_me"
-                          name="remember_me"
-                          type="checkbox"
-                          class="h-4 w-4 text-indigo-600 focus:ring-indigo-500 border-gray-300 rounded"
-                          oninput=self.link.callback(|_| Msg::RememberMeInputReceived)
-                        />
-                        <label
-                          htmlFor="remember_me"
-                          class="ml-2 block text-sm text-gray-900"
-                        >
-                          {"Remember me"}
-                        </label>
-                        { self.view_password_error() }
-                      </div>
-
-                      <div class="text-sm">
-                        <a
-                          href="/sign/up"
-                          class="font-medium text-indigo-600 hover:text-indigo-500"
-                        >
-                          {"Don't have an account?"}
-                        </a>
-                      </div>
-                    </div>
-
-                    <div>
-                      { self.view_button() }
-                    </div>
-                  </div>
-                </div>
-              </div>
+              { self.view_form() }
             </div>
           </div>
           <div class="hidden lg:block relative w-0 flex-1">
@@ -0,0 +1,174 @@
+use crate::components::toolbar::ToolbarModel;
+use crate::post::Post;
+use crate::query_dsl;
+use crate::settings::SiteSettings;
+use cynic::GraphQLResponse;
+use cynic::QueryBuilder;
+use serde::Deserialize;
+use yew::format::Json;
+use yew::prelude::*;
+use yew::services::console::ConsoleService;
+use yew::services::fetch::{FetchTask, Request, Response};
+use yew::services::FetchService;
+
+#[derive(cynic::QueryFragment, Deserialize)]
+#[cynic(schema_path = "schema.graphql", query_module = "query_dsl")]
+#[serde(rename_all = "camelCase")]
+pub struct Tag {
+    pub name: String,
+    pub post_count: i32,
+}
+
+#[derive(cynic::FragmentArguments)]
+pub struct TagArguments {
+    pub slug: String,
+}
+
+#[derive(cynic::QueryFragment, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "QueryRoot",
+    argument_struct = "TagArguments"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct TagConnection {
+    #[arguments(slug = args.slug.clone())]
+    tag: Option<Tag>,
+    #[arguments(tag = args.slug.clone())]
+    posts: Vec<Post>,
+    site_settings: SiteSettings,
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct TagProps {
+    pub slug: String,
+}
+
+pub enum Msg {
+    ReceiveResponse(Result<GraphQLResponse<TagConnection>, anyhow::Error>),
+}
+
+pub struct TagModel {
+    props: TagProps,
+    link: ComponentLink<Self>,
+    tag: Option<Tag>,
+    posts: Vec<Post>,
+    site_settings: SiteSettings,
+    fetch_target: Option<FetchTask>,
+}
+
+impl TagModel {
+    fn load(&mut self) {
+        let operation = TagConnection::build(TagArguments {
+            slug: self.props.slug.clone(),
+        });
+        let request = Request::post("/graphql")
+            .header("Content-Type", "application/json")
+            .body(Ok(serde_json::to_string(&operation).unwrap()))
+            .expect("Failed to build request.");
+        let callback = self.link.callback(
+            |response: Response<Json<Result<GraphQLResponse<TagConnection>, anyhow::Error>>>| {
+                let Json(data) = response.into_body();
+                Msg::ReceiveResponse(data)
+            },
+        );
+        let target = FetchService::fetch(request, callback).expect("failed to start request");
+        self.fetch_target = Some(target);
+    }
+
+    fn view_tag(&self, tag: &Tag) -> Html {
+        let count = match tag.post_count {
+            1 => String::from("1 post"),
+            count => format!("{} posts", count),
+        };
+        html! {
+          <div>
+            <h1 class="text-2xl font-bold text-gray-900">{ format!("#{}", tag.name) }</h1>
+            <p class="mt-2 text-sm text-gray-500">{ count }</p>
+          </div>
+        }
+    }
+
+    fn view_post(&self, post: &Post) -> Html {
+        html! {
+          <li class="bg-white px-4 py-6 shadow sm:p-6 sm:rounded-lg mt-4">
+            <a href={format!("/post/{}", post.slug)}>
+              <h2 class="text-base font-medium text-gray-900">{ &post.title }</h2>
+              <p class="text-sm text-gray-500">{ self.site_settings.format_date(&post.created_at) }</p>
+              <p class="mt-2 text-sm text-gray-700">{ &post.summary }</p>
+            </a>
+          </li>
+        }
+    }
+}
+
+impl Component for TagModel {
+    type Message = Msg;
+    type Properties = TagProps;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut model = Self {
+            props,
+            link,
+            tag: None,
+            posts: vec![],
+            site_settings: SiteSettings::default(),
+            fetch_target: None,
+        };
+        model.load();
+        model
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::ReceiveResponse(response) => {
+                match response {
+                    Ok(graphql_response) => {
+                        if let Some(data) = graphql_response.data {
+                            self.tag = data.tag;
+                            self.posts = data.posts;
+                            self.site_settings = data.site_settings;
+                        }
+                    }
+                    Err(error) => ConsoleService::info(&format!("Error: {}", error.to_string())),
+                };
+                self.fetch_target = None;
+            }
+        }
+        true
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if self.props != props {
+            self.props = props;
+            self.load();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn view(&self) -> Html {
+        let content = match self.tag {
+            Some(ref tag) => self.view_tag(tag),
+            None if self.fetch_target.is_none() => html! {
+              <p class="text-sm text-gray-500">{"This tag doesn't exist."}</p>
+            },
+            None => html! {},
+        };
+        html! {
+          <div class="min-h-screen bg-gray-100">
+            <ToolbarModel />
+            <div class="py-10">
+              <div class="max-w-3xl mx-auto sm:px-6 lg:px-8">
+                { content }
+                <ul class="mt-8">
+                  { for self.posts.iter().map(|post| self.view_post(post)) }
+                </ul>
+              </div>
+            </div>
+          </div>
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use crate::authorization::authorized_request;
+use crate::components::toolbar::ToolbarModel;
+use crate::query_dsl;
+use crate::response::ApiResponse;
+use crate::settings::SiteSettings;
+use crate::DateTime;
+use cynic::{MutationBuilder, QueryBuilder};
+use serde::Deserialize;
+use yew::{
+    format::Json,
+    prelude::*,
+    services::fetch::{FetchService, FetchTask, Response},
+};
+
+#[derive(cynic::QueryFragment, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "Post"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashedPost {
+    id: i32,
+    slug: String,
+    title: String,
+    deleted_at: Option<DateTime>,
+}
+
+#[derive(cynic::QueryFragment, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "QueryRoot"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashConnection {
+    trashed_posts: Vec<TrashedPost>,
+    site_settings: SiteSettings,
+}
+
+#[derive(cynic::FragmentArguments)]
+pub struct TrashArguments {
+    post_id: i32,
+}
+
+#[derive(cynic::QueryFragment, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "Post"
+)]
+pub struct RestoredPost {
+    id: i32,
+}
+
+#[derive(cynic::QueryFragment, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "MutationRoot",
+    argument_struct = "TrashArguments"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct RestorePostConnection {
+    #[arguments(post_id = args.post_id)]
+    restore_post: RestoredPost,
+}
+
+#[derive(cynic::QueryFragment, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "MutationRoot",
+    argument_struct = "TrashArguments"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct PurgePostConnection {
+    #[arguments(post_id = args.post_id)]
+    purge_post: String,
+}
+
+pub enum Msg {
+    ReceiveTrash(Result<ApiResponse<TrashConnection>, anyhow::Error>),
+    Restore(i32),
+    ReceiveRestored(Result<ApiResponse<RestorePostConnection>, anyhow::Error>),
+    Purge(i32),
+    ReceivePurged(Result<ApiResponse<PurgePostConnection>, anyhow::Error>),
+}
+
+pub struct TrashModel {
+    fetch_task: Option<FetchTask>,
+    link: ComponentLink<Self>,
+    posts: Vec<TrashedPost>,
+    site_settings: SiteSettings,
+    error: Option<String>,
+}
+
+impl TrashModel {
+    fn load_trash(&mut self) {
+        let operation = TrashConnection::build(());
+        let request = authorized_request(serde_json::to_string(&operation).unwrap());
+        let callback = self.link.callback(
+            |response: Response<Json<Result<ApiResponse<TrashConnection>, anyhow::Error>>>| {
+                let Json(data) = response.into_body();
+                Msg::ReceiveTrash(data)
+            },
+        );
+        let task = FetchService::fetch(request, callback).expect("failed to start request");
+        self.fetch_task = Some(task);
+    }
+
+    fn view_post(&self, post: &TrashedPost) -> Html {
+        let id = post.id;
+        let deleted = match post.deleted_at {
+            Some(ref deleted_at) => {
+                format!("Deleted {}", self.site_settings.format_date(deleted_at))
+            }
+            None => String::new(),
+        };
+        html! {
+          <li class="py-4 flex items-center justify-between">
+            <div>
+              <p class="text-sm font-medium text-gray-900">{ &post.title }</p>
+              <p class="text-sm font-mono text-gray-500">{ format!("/post/{}", post.slug) }</p>
+              <p class="text-sm text-gray-500">{ deleted }</p>
+            </div>
+            <div class="flex space-x-3">
+              <button
+                class="px-3 py-2 border border-gray-300 text-sm font-medium rounded-md text-gray-700 bg-white hover:bg-gray-50"
+                onclick=self.link.callback(move |_| Msg::Restore(id))
+              >
+                {"Restore"}
+              </button>
+              <button
+                class="px-3 py-2 border border-transparent text-sm font-medium rounded-md text-white bg-red-600 hover:bg-red-700"
+                onclick=self.link.callback(move |_| Msg::Purge(id))
+              >
+                {"Delete forever"}
+              </button>
+            </div>
+          </li>
+        }
+    }
+}
+
+impl Component for TrashModel {
+    type Message = Msg;
+    type Properties = ();
+
+    fn create(_props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut model = Self {
+            fetch_task: None,
+            link,
+            posts: Vec::new(),
+            site_settings: SiteSettings::default(),
+            error: None,
+        };
+        model.load_trash();
+        model
+    }
+
+    fn change(&mut self, _props: Self::Properties) -> bool {
+        false
+    }
+
+    fn update(&mut self, msg: Self::Message) -> bool {
+        match msg {
+            Msg::ReceiveTrash(response) => {
+                match response {
+                    Ok(graphql_response) => {
+                        self.error = graphql_response.other_errors();
+                        if let Some(data) = graphql_response.data {
+                            self.posts = data.trashed_posts;
+                            self.site_settings = data.site_settings;
+                        }
+                    }
+                    Err(error) => self.error = Some(error.to_string()),
+                }
+                self.fetch_task = None;
+            }
+            Msg::Restore(post_id) => {
+                let operation = RestorePostConnection::build(TrashArguments { post_id });
+                let request = authorized_request(serde_json::to_string(&operation).unwrap());
+                let callback = self.link.callback(
+                    |response: Response<
+                        Json<Result<ApiResponse<RestorePostConnection>, anyhow::Error>>,
+                    >| {
+                        let Json(data) = response.into_body();
+                        Msg::ReceiveRestored(data)
+                    },
+                );
+                let task = FetchService::fetch(request, callback).expect("failed to start request");
+                self.fetch_task = Some(task);
+            }
+            Msg::ReceiveRestored(response) => {
+                self.fetch_task = None;
+                match response {
+                    Ok(graphql_response) => {
+                        self.error = graphql_response.other_errors();
+                        self.load_trash();
+                    }
+                    Err(error) => self.error = Some(error.to_string()),
+                }
+            }
+            Msg::Purge(post_id) => {
+                let operation = PurgePostConnection::build(TrashArguments { post_id });
+                let request = authorized_request(serde_json::to_string(&operation).unwrap());
+                let callback = self.link.callback(
+                    |response: Response<
+                        Json<Result<ApiResponse<PurgePostConnection>, anyhow::Error>>,
+                    >| {
+                        let Json(data) = response.into_body();
+                        Msg::ReceivePurged(data)
+                    },
+                );
+                let task = FetchService::fetch(request, callback).expect("failed to start request");
+                self.fetch_task = Some(task);
+            }
+            Msg::ReceivePurged(response) => {
+                self.fetch_task = None;
+                match response {
+                    Ok(graphql_response) => {
+                        self.error = graphql_response.other_errors();
+                        self.load_trash();
+                    }
+                    Err(error) => self.error = Some(error.to_string()),
+                }
+            }
+        }
+        true
+    }
+
+    fn view(&self) -> Html {
+        let content = if self.posts.is_empty() && self.fetch_task.is_none() {
+            html! {
+              <p class="mt-8 text-sm text-gray-500">{"The trash is empty."}</p>
+            }
+        } else {
+            html! {
+              <ul class="mt-8 divide-y divide-gray-200">
+                { for self.posts.iter().map(|post| self.view_post(post)) }
+              </ul>
+            }
+        };
+        html! {
+          <div>
+            <ToolbarModel />
+            <div class="max-w-xl mx-auto py-12 px-4 sm:px-6 lg:px-8">
+              <h2 class="text-3xl font-extrabold text-gray-900">
+                {"Trash"}
+              </h2>
+              <p class="mt-2 text-sm text-gray-500">
+                {"Deleted posts stay here until they are restored or purged. Old posts are purged automatically."}
+              </p>
+              { content }
+              { for self.error.iter().map(|error| html! {
+                <p class="mt-4 text-sm text-red-600">{ error }</p>
+              }) }
+            </div>
+          </div>
+        }
+    }
+}
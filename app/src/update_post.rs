@@ -1,6 +1,10 @@
+use crate::author::Author;
+use crate::authorization::authorized_request;
 use crate::components::toolbar::ToolbarModel;
-use crate::post::{Post, PostArguments, PostConnection};
+use crate::post::{Post, PostArguments, PostConnection, PostStatus};
 use crate::query_dsl;
+use crate::response::ApiResponse;
+use crate::AppRoute;
 use cynic::GraphQLResponse;
 use cynic::{MutationBuilder, QueryBuilder};
 use serde::Deserialize;
@@ -12,12 +16,68 @@ use yew::{
     services::fetch::{FetchService, FetchTask, Request, Response},
     services::storage::{Area, StorageService},
 };
+use yew_router::{agent::RouteRequest::ChangeRoute, prelude::*};
+
+#[derive(cynic::QueryFragment, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "MutationRoot",
+    argument_struct = "PostArguments"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct DeletePostConnection {
+    #[arguments(post_id = args.post_id)]
+    delete_post: String,
+}
+
+// Only the fields that differ from the saved post are sent, except for the
+// category which is always sent as an empty one clears it.
+#[derive(cynic::InputObject, Clone, Default)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "UpdatePostInput",
+    rename_all = "camelCase"
+)]
+pub struct UpdatePostInput {
+    slug: Option<String>,
+    title: Option<String>,
+    text: Option<String>,
+    summary: Option<String>,
+    cover_image_url: Option<String>,
+    tags: Option<Vec<String>>,
+    status: Option<PostStatus>,
+    published_at: Option<crate::DateTime>,
+    seo_title: Option<String>,
+    seo_description: Option<String>,
+    category_id: Option<i32>,
+}
+
+#[derive(cynic::QueryFragment, Deserialize)]
+#[cynic(schema_path = "schema.graphql", query_module = "query_dsl")]
+#[serde(rename_all = "camelCase")]
+pub struct Category {
+    id: i32,
+    parent_id: Option<i32>,
+    name: String,
+}
+
+#[derive(cynic::QueryFragment, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "QueryRoot"
+)]
+pub struct CategoriesConnection {
+    categories: Vec<Category>,
+}
 
 #[derive(cynic::FragmentArguments)]
 pub struct UpdatePostArguments {
     post_id: i32,
-    title: Option<String>,
-    text: Option<String>,
+    expected_version: i32,
+    input: UpdatePostInput,
 }
 
 #[derive(cynic::QueryFragment, Deserialize)]
@@ -29,8 +89,178 @@ pub struct UpdatePostArguments {
 )]
 #[serde(rename_all = "camelCase")]
 pub struct UpdatePostConnection {
-    #[arguments(post_id = args.post_id, title = args.title.clone(), text = args.text.clone())]
-    update_post: String,
+    #[arguments(
+        post_id = args.post_id,
+        expected_version = args.expected_version,
+        input = args.input.clone()
+    )]
+    update_post: Post,
+}
+
+#[derive(cynic::QueryFragment, Deserialize)]
+#[cynic(schema_path = "schema.graphql", query_module = "query_dsl")]
+#[serde(rename_all = "camelCase")]
+pub struct PostRevision {
+    id: i32,
+    title: String,
+    created_at: crate::DateTime,
+    author: Option<Author>,
+}
+
+#[derive(cynic::QueryFragment, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "QueryRoot",
+    argument_struct = "PostArguments"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct PostRevisionsConnection {
+    #[arguments(post_id = args.post_id)]
+    post_revisions: Vec<PostRevision>,
+}
+
+#[derive(cynic::Enum, Clone, Copy, Debug, PartialEq)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "DiffTag",
+    rename_all = "SCREAMING_SNAKE_CASE"
+)]
+pub enum DiffTag {
+    Equal,
+    Delete,
+    Insert,
+}
+
+#[derive(cynic::QueryFragment, Deserialize)]
+#[cynic(schema_path = "schema.graphql", query_module = "query_dsl")]
+#[serde(rename_all = "camelCase")]
+pub struct DiffLine {
+    tag: DiffTag,
+    old_line: Option<i32>,
+    new_line: Option<i32>,
+    text: String,
+}
+
+#[derive(cynic::QueryFragment, Deserialize)]
+#[cynic(schema_path = "schema.graphql", query_module = "query_dsl")]
+pub struct RevisionDiff {
+    title: Vec<DiffLine>,
+    summary: Vec<DiffLine>,
+    text: Vec<DiffLine>,
+}
+
+#[derive(cynic::FragmentArguments)]
+pub struct RevisionDiffArguments {
+    from: i32,
+    to: i32,
+}
+
+#[derive(cynic::QueryFragment, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "QueryRoot",
+    argument_struct = "RevisionDiffArguments"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct RevisionDiffConnection {
+    #[arguments(from = args.from, to = args.to)]
+    revision_diff: RevisionDiff,
+}
+
+#[derive(cynic::FragmentArguments)]
+pub struct RestoreRevisionArguments {
+    revision_id: i32,
+}
+
+#[derive(cynic::QueryFragment, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "MutationRoot",
+    argument_struct = "RestoreRevisionArguments"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreRevisionConnection {
+    #[arguments(revision_id = args.revision_id)]
+    restore_revision: String,
+}
+
+// Publish dates are edited in UTC, to the minute.
+const PUBLISHED_AT_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+fn format_published_at(published_at: &Option<crate::DateTime>) -> String {
+    published_at
+        .as_ref()
+        .map(|published_at| published_at.0.format(PUBLISHED_AT_FORMAT).to_string())
+        .unwrap_or_default()
+}
+
+fn parse_published_at(value: &str) -> Option<crate::DateTime> {
+    chrono::NaiveDateTime::parse_from_str(value.trim(), PUBLISHED_AT_FORMAT)
+        .ok()
+        .map(|naive| crate::DateTime(chrono::DateTime::from_utc(naive, chrono::Utc)))
+}
+
+fn changed(value: &str, saved: &str) -> Option<String> {
+    if value == saved {
+        None
+    } else {
+        Some(value.to_string())
+    }
+}
+
+// Orders the categories so that each one comes right after its parent, along
+// with how deep it is nested.
+fn category_tree(categories: &[Category]) -> Vec<(usize, &Category)> {
+    fn children<'a>(
+        categories: &'a [Category],
+        parent_id: Option<i32>,
+        depth: usize,
+        tree: &mut Vec<(usize, &'a Category)>,
+    ) {
+        for category in categories.iter().filter(|c| c.parent_id == parent_id) {
+            tree.push((depth, category));
+            children(categories, Some(category.id), depth + 1, tree);
+        }
+    }
+    let mut tree = Vec::with_capacity(categories.len());
+    children(categories, None, 0, &mut tree);
+    tree
+}
+
+fn parse_tags(tags: &str) -> Vec<String> {
+    tags.split(',')
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
+#[derive(Clone, Copy)]
+pub enum Field {
+    Title,
+    Slug,
+    Summary,
+    CoverImageUrl,
+    Tags,
+    PublishedAt,
+    SeoTitle,
+    SeoDescription,
+}
+
+#[derive(Default)]
+struct Errors {
+    title: Option<String>,
+    slug: Option<String>,
+    summary: Option<String>,
+    cover_image_url: Option<String>,
+    tags: Option<String>,
+    published_at: Option<String>,
+    seo_title: Option<String>,
+    seo_description: Option<String>,
+    category_id: Option<String>,
 }
 
 #[derive(Clone, PartialEq, Properties)]
@@ -45,11 +275,356 @@ pub struct UpdatePostModel {
     link: ComponentLink<Self>,
     text: String,
     text_error: Option<String>,
+    title: String,
+    slug: String,
+    summary: String,
+    cover_image_url: String,
+    tags: String,
+    published_at: String,
+    seo_title: String,
+    seo_description: String,
+    category_id: Option<i32>,
+    categories: Vec<Category>,
+    categories_task: Option<FetchTask>,
+    errors: Errors,
     error: Option<String>,
     success: Option<String>,
+    history_task: Option<FetchTask>,
+    revisions: Vec<PostRevision>,
+    diff: Option<(i32, RevisionDiff)>,
+    version: i32,
+    conflict: Option<Post>,
+    router_agent: Box<dyn Bridge<RouteAgent>>,
 }
 
 impl UpdatePostModel {
+    fn fill(&mut self, post: &Post) {
+        self.text = post.text.clone();
+        self.title = post.title.clone();
+        self.slug = post.slug.clone();
+        self.summary = post.summary.clone();
+        self.cover_image_url = post.cover_image_url.clone().unwrap_or_default();
+        self.tags = post.tags.join(", ");
+        self.published_at = format_published_at(&post.published_at);
+        self.seo_title = post.seo_title.clone().unwrap_or_default();
+        self.seo_description = post.seo_description.clone().unwrap_or_default();
+        self.category_id = post.category_id;
+        self.version = post.version;
+    }
+
+    fn changes(&self, status: Option<PostStatus>) -> UpdatePostInput {
+        let post = match self.post {
+            Some(ref post) => post,
+            None => return UpdatePostInput::default(),
+        };
+        let tags = parse_tags(&self.tags);
+        UpdatePostInput {
+            slug: changed(&self.slug, &post.slug),
+            title: changed(&self.title, &post.title),
+            text: changed(&self.text, &post.text),
+            summary: changed(&self.summary, &post.summary),
+            cover_image_url: changed(
+                &self.cover_image_url,
+                post.cover_image_url.as_deref().unwrap_or(""),
+            ),
+            tags: if tags == post.tags { None } else { Some(tags) },
+            status: status.filter(|status| *status != post.status),
+            // Sent on its own, so the date can change without the status.
+            published_at: if self.published_at == format_published_at(&post.published_at) {
+                None
+            } else {
+                parse_published_at(&self.published_at)
+            },
+            seo_title: changed(&self.seo_title, post.seo_title.as_deref().unwrap_or("")),
+            seo_description: changed(
+                &self.seo_description,
+                post.seo_description.as_deref().unwrap_or(""),
+            ),
+            category_id: self.category_id,
+        }
+    }
+
+    fn view_field_error(error: &Option<String>) -> Html {
+        match error {
+            Some(error) => html! {
+              <p class="mt-2 text-sm text-red-600">{ error }</p>
+            },
+            None => html! {},
+        }
+    }
+
+    fn view_input(&self, field: Field, label: &str, value: &str, error: &Option<String>) -> Html {
+        html! {
+          <div>
+            <label class="block text-sm font-medium text-gray-700">
+              { label }
+            </label>
+            <div class="mt-1">
+              <input
+                type="text"
+                class="shadow-sm focus:ring-indigo-500 focus:border-indigo-500 block w-full sm:text-sm border-gray-300 rounded-md"
+                oninput=self.link.callback(move |input_data: InputData| Msg::InputReceived(field, input_data.value))
+                value=value.to_string()
+              />
+            </div>
+            { Self::view_field_error(error) }
+          </div>
+        }
+    }
+
+    fn view_textarea(
+        &self,
+        field: Field,
+        label: &str,
+        value: &str,
+        error: &Option<String>,
+    ) -> Html {
+        html! {
+          <div>
+            <label class="block text-sm font-medium text-gray-700">
+              { label }
+            </label>
+            <div class="mt-1">
+              <textarea
+                rows="3"
+                class="shadow-sm focus:ring-indigo-500 focus:border-indigo-500 block w-full sm:text-sm border-gray-300 rounded-md"
+                oninput=self.link.callback(move |input_data: InputData| Msg::InputReceived(field, input_data.value))
+                value=value.to_string()
+              />
+            </div>
+            { Self::view_field_error(error) }
+          </div>
+        }
+    }
+
+    fn view_category_option(&self, category_id: Option<i32>, depth: usize, name: &str) -> Html {
+        html! {
+          <label class="flex items-center text-sm text-gray-700" style={format!("padding-left: {}rem", depth)}>
+            <input
+              type="radio"
+              name="category"
+              class="focus:ring-indigo-500 h-4 w-4 text-indigo-600 border-gray-300"
+              checked=self.category_id == category_id
+              onclick=self.link.callback(move |_| Msg::SelectCategory(category_id))
+            />
+            <span class="ml-2">{ name }</span>
+          </label>
+        }
+    }
+
+    fn view_categories(&self) -> Html {
+        html! {
+          <div>
+            <span class="block text-sm font-medium text-gray-700">{"Category"}</span>
+            <div class="mt-1 space-y-1">
+              { self.view_category_option(None, 0, "No category") }
+              { for category_tree(&self.categories).into_iter().map(|(depth, category)| {
+                self.view_category_option(Some(category.id), depth, &category.name)
+              }) }
+            </div>
+            { Self::view_field_error(&self.errors.category_id) }
+          </div>
+        }
+    }
+
+    fn view_details(&self) -> Html {
+        html! {
+          <div class="max-w-3xl mx-auto mt-6 grid grid-cols-1 gap-y-6">
+            { self.view_input(Field::Title, "Title", &self.title, &self.errors.title) }
+            { self.view_input(Field::Slug, "Slug", &self.slug, &self.errors.slug) }
+            { self.view_textarea(Field::Summary, "Summary", &self.summary, &self.errors.summary) }
+            { self.view_input(Field::CoverImageUrl, "Cover image URL", &self.cover_image_url, &self.errors.cover_image_url) }
+            { self.view_input(Field::Tags, "Tags, separated by commas", &self.tags, &self.errors.tags) }
+            { self.view_categories() }
+            { self.view_input(Field::PublishedAt, "Publish date, in UTC (YYYY-MM-DD HH:MM)", &self.published_at, &self.errors.published_at) }
+            { self.view_input(Field::SeoTitle, "SEO title", &self.seo_title, &self.errors.seo_title) }
+            { self.view_textarea(Field::SeoDescription, "SEO description", &self.seo_description, &self.errors.seo_description) }
+          </div>
+        }
+    }
+
+    fn load_post(&mut self) {
+        let operation = PostConnection::build(PostArguments {
+            post_id: self.props.id,
+        });
+        let request = authorized_request(serde_json::to_string(&operation).unwrap());
+        let callback = self.link.callback(
+            |response: Response<Json<Result<GraphQLResponse<PostConnection>, anyhow::Error>>>| {
+                let Json(data) = response.into_body();
+                Msg::ReceiveResponse(data)
+            },
+        );
+        let task = FetchService::fetch(request, callback).expect("failed to start request");
+        self.fetch_task = Some(task);
+    }
+
+    fn load_categories(&mut self) {
+        let operation = CategoriesConnection::build(());
+        let request = authorized_request(serde_json::to_string(&operation).unwrap());
+        let callback = self.link.callback(
+            |response: Response<Json<Result<ApiResponse<CategoriesConnection>, anyhow::Error>>>| {
+                let Json(data) = response.into_body();
+                Msg::ReceiveCategories(data)
+            },
+        );
+        let task = FetchService::fetch(request, callback).expect("failed to start request");
+        self.categories_task = Some(task);
+    }
+
+    fn load_latest(&mut self) {
+        let operation = PostConnection::build(PostArguments {
+            post_id: self.props.id,
+        });
+        let request = authorized_request(serde_json::to_string(&operation).unwrap());
+        let callback = self.link.callback(
+            |response: Response<Json<Result<GraphQLResponse<PostConnection>, anyhow::Error>>>| {
+                let Json(data) = response.into_body();
+                Msg::ReceiveLatest(data)
+            },
+        );
+        let task = FetchService::fetch(request, callback).expect("failed to start request");
+        self.fetch_task = Some(task);
+    }
+
+    // Someone else saved while this editor was open. Their text is shown next
+    // to ours and the editor decides which one to continue with.
+    fn view_conflict(&self) -> Html {
+        match self.conflict {
+            Some(ref latest) => html! {
+              <div class="max-w-3xl mx-auto mt-6 px-4 py-4 border border-yellow-300 bg-yellow-50 rounded-md space-y-3">
+                <p class="text-sm font-medium text-yellow-800">
+                  { format!("Someone else saved this post while you were editing it. Their version is {}.", latest.version) }
+                </p>
+                <pre class="text-xs text-gray-700 whitespace-pre-wrap max-h-64 overflow-y-auto">{ &latest.text }</pre>
+                <div class="flex space-x-4">
+                  <button
+                    class="px-4 py-2 border border-gray-300 rounded-md text-sm font-medium text-gray-700 bg-white hover:bg-gray-50"
+                    onclick=self.link.callback(|_| Msg::TakeTheirs)
+                  >
+                    {"Use their version"}
+                  </button>
+                  <button
+                    class="px-4 py-2 border border-transparent rounded-md text-sm font-medium text-white bg-indigo-600 hover:bg-indigo-700"
+                    onclick=self.link.callback(|_| Msg::KeepMine)
+                  >
+                    {"Save mine over it"}
+                  </button>
+                </div>
+              </div>
+            },
+            None => html! {},
+        }
+    }
+
+    fn load_revisions(&mut self) {
+        let operation = PostRevisionsConnection::build(PostArguments {
+            post_id: self.props.id,
+        });
+        let request = authorized_request(serde_json::to_string(&operation).unwrap());
+        let callback = self.link.callback(
+            |response: Response<
+                Json<Result<ApiResponse<PostRevisionsConnection>, anyhow::Error>>,
+            >| {
+                let Json(data) = response.into_body();
+                Msg::ReceiveRevisions(data)
+            },
+        );
+        let task = FetchService::fetch(request, callback).expect("failed to start request");
+        self.history_task = Some(task);
+    }
+
+    fn view_diff_lines(lines: &[DiffLine]) -> Html {
+        html! {
+          <div class="font-mono text-xs border border-gray-200 rounded-md overflow-x-auto">
+            { for lines.iter().map(|line| {
+                let (class, sign) = match line.tag {
+                    DiffTag::Equal => ("text-gray-700", " "),
+                    DiffTag::Delete => ("bg-red-50 text-red-800", "-"),
+                    DiffTag::Insert => ("bg-green-50 text-green-800", "+"),
+                };
+                let number = |number: Option<i32>| number.map(|number| number.to_string()).unwrap_or_default();
+                html! {
+                  <div class={format!("flex whitespace-pre {}", class)}>
+                    <span class="w-10 px-1 text-right text-gray-400 select-none">{ number(line.old_line) }</span>
+                    <span class="w-10 px-1 text-right text-gray-400 select-none">{ number(line.new_line) }</span>
+                    <span class="px-1 select-none">{ sign }</span>
+                    <span>{ &line.text }</span>
+                  </div>
+                }
+            }) }
+          </div>
+        }
+    }
+
+    fn view_diff(&self) -> Html {
+        match self.diff {
+            Some((revision_id, ref diff)) => html! {
+              <div class="mt-4 space-y-3">
+                <h3 class="text-sm font-medium text-gray-900">
+                  { format!("Changes since revision #{}", revision_id) }
+                </h3>
+                <p class="text-xs font-medium text-gray-500">{"Title"}</p>
+                { Self::view_diff_lines(&diff.title) }
+                <p class="text-xs font-medium text-gray-500">{"Summary"}</p>
+                { Self::view_diff_lines(&diff.summary) }
+                <p class="text-xs font-medium text-gray-500">{"Text"}</p>
+                { Self::view_diff_lines(&diff.text) }
+              </div>
+            },
+            None => html! {},
+        }
+    }
+
+    fn view_revision(&self, revision: &PostRevision, latest: bool) -> Html {
+        let revision_id = revision.id;
+        let author = revision
+            .author
+            .as_ref()
+            .map(|author| author.display_name.clone())
+            .unwrap_or_else(|| String::from("Former author"));
+        let actions = if latest {
+            html! { <span class="text-xs text-gray-500">{"Current"}</span> }
+        } else {
+            html! {<>
+              <button
+                class="text-sm text-indigo-600 hover:text-indigo-500"
+                onclick=self.link.callback(move |_| Msg::Compare(revision_id))
+              >
+                {"Compare"}
+              </button>
+              <button
+                class="ml-4 text-sm text-indigo-600 hover:text-indigo-500"
+                onclick=self.link.callback(move |_| Msg::Restore(revision_id))
+              >
+                {"Restore"}
+              </button>
+            </>}
+        };
+        html! {
+          <li class="py-3 flex items-center justify-between">
+            <div>
+              <p class="text-sm font-medium text-gray-900">{ format!("#{} {}", revision.id, revision.title) }</p>
+              <p class="text-sm text-gray-500">
+                { format!("{} by {}", revision.created_at.0.format("%Y-%m-%d %H:%M"), author) }
+              </p>
+            </div>
+            <div>{ actions }</div>
+          </li>
+        }
+    }
+
+    fn view_history(&self) -> Html {
+        html! {
+          <div class="max-w-3xl mx-auto py-8 px-4">
+            <h2 class="text-lg font-medium text-gray-900">{"History"}</h2>
+            <ul class="divide-y divide-gray-200">
+              { for self.revisions.iter().enumerate().map(|(index, revision)| self.view_revision(revision, index == 0)) }
+            </ul>
+            { self.view_diff() }
+          </div>
+        }
+    }
+
     pub fn markdown_node(&self) -> Html {
         let div = web_sys::window()
             .unwrap()
@@ -81,7 +656,7 @@ impl UpdatePostModel {
                         <p class="text-sm font-medium text-gray-900">
                         {"Post updated!"}
                         </p>
-                        <a href={format!("/post/{}", self.props.id)} class="mt-1 text-sm text-gray-500">
+                        <a href={self.post.as_ref().map(|post| format!("/post/{}", post.slug)).unwrap_or_default()} class="mt-1 text-sm text-gray-500">
                         {"Want to view the post?"}
                         </a>
                       </div>
@@ -144,47 +719,71 @@ impl UpdatePostModel {
 }
 
 pub enum Msg {
-    SubmitNewPost,
+    SubmitNewPost(Option<PostStatus>),
     Change(String),
     ReceiveResponse(Result<GraphQLResponse<PostConnection>, anyhow::Error>),
-    UpdateReceiveResponse(Result<GraphQLResponse<UpdatePostConnection>, anyhow::Error>),
+    InputReceived(Field, String),
+    SelectCategory(Option<i32>),
+    ReceiveCategories(Result<ApiResponse<CategoriesConnection>, anyhow::Error>),
+    UpdateReceiveResponse(Result<ApiResponse<UpdatePostConnection>, anyhow::Error>),
+    ReceiveLatest(Result<GraphQLResponse<PostConnection>, anyhow::Error>),
+    TakeTheirs,
+    KeepMine,
     ClearNotifications,
+    ReceiveRevisions(Result<ApiResponse<PostRevisionsConnection>, anyhow::Error>),
+    Compare(i32),
+    ReceiveDiff(
+        i32,
+        Result<ApiResponse<RevisionDiffConnection>, anyhow::Error>,
+    ),
+    Restore(i32),
+    ReceiveRestore(Result<ApiResponse<RestoreRevisionConnection>, anyhow::Error>),
+    Delete,
+    ReceiveDeleted(Result<ApiResponse<DeletePostConnection>, anyhow::Error>),
+    Ignore,
 }
 
 impl Component for UpdatePostModel {
     type Message = Msg;
     type Properties = UpdatePostModelProps;
     fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
-        let operation = PostConnection::build(PostArguments { post_id: props.id });
-
-        let query = serde_json::to_string(&operation).unwrap();
-
-        let request = Request::post("/graphql")
-            .header("Content-Type", "application/json")
-            .body(Ok(query))
-            .expect("Failed to build request.");
-        let callback = link.callback(
-            |response: Response<Json<Result<GraphQLResponse<PostConnection>, anyhow::Error>>>| {
-                let Json(data) = response.into_body();
-                Msg::ReceiveResponse(data)
-            },
-        );
-        let target = FetchService::fetch(request, callback).expect("failed to start request");
-        Self {
+        let mut model = Self {
             props,
-            fetch_task: Some(target),
+            fetch_task: None,
             post: None,
             text: String::from(""),
             text_error: None,
+            title: String::new(),
+            slug: String::new(),
+            summary: String::new(),
+            cover_image_url: String::new(),
+            tags: String::new(),
+            published_at: String::new(),
+            seo_title: String::new(),
+            seo_description: String::new(),
+            category_id: None,
+            categories: vec![],
+            categories_task: None,
+            errors: Errors::default(),
             error: None,
             success: None,
+            router_agent: RouteAgent::bridge(link.callback(|_| Msg::Ignore)),
             link,
-        }
+            history_task: None,
+            revisions: vec![],
+            diff: None,
+            version: 0,
+            conflict: None,
+        };
+        model.load_post();
+        model.load_categories();
+        model.load_revisions();
+        model
     }
 
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match msg {
-            Msg::SubmitNewPost => {
+            Msg::SubmitNewPost(status) => {
                 let storage = StorageService::new(Area::Local).unwrap();
 
                 let token: String = match storage.restore("auth_token") {
@@ -199,11 +798,18 @@ impl Component for UpdatePostModel {
                     self.text_error = Some("Your text is not valid".into());
                     return true;
                 }
+                if !self.published_at.trim().is_empty()
+                    && parse_published_at(&self.published_at).is_none()
+                {
+                    self.errors.published_at =
+                        Some("The publish date must look like 2021-03-01 09:30.".into());
+                    return true;
+                }
 
                 let operation = UpdatePostConnection::build(UpdatePostArguments {
                     post_id: self.props.id,
-                    title: None,
-                    text: Some(self.text.clone()),
+                    expected_version: self.version,
+                    input: self.changes(status),
                 });
 
                 let query = serde_json::to_string(&operation).unwrap();
@@ -216,7 +822,7 @@ impl Component for UpdatePostModel {
                 // 2. construct a callback
                 let callback = self.link.callback(
                     |response: Response<
-                        Json<Result<GraphQLResponse<UpdatePostConnection>, anyhow::Error>>,
+                        Json<Result<ApiResponse<UpdatePostConnection>, anyhow::Error>>,
                     >| {
                         let Json(data) = response.into_body();
                         Msg::UpdateReceiveResponse(data)
@@ -232,6 +838,28 @@ impl Component for UpdatePostModel {
             Msg::Change(text) => {
                 self.text = text;
             }
+            Msg::InputReceived(field, value) => match field {
+                Field::Title => self.title = value,
+                Field::Slug => self.slug = value,
+                Field::Summary => self.summary = value,
+                Field::CoverImageUrl => self.cover_image_url = value,
+                Field::Tags => self.tags = value,
+                Field::PublishedAt => self.published_at = value,
+                Field::SeoTitle => self.seo_title = value,
+                Field::SeoDescription => self.seo_description = value,
+            },
+            Msg::SelectCategory(category_id) => self.category_id = category_id,
+            Msg::ReceiveCategories(response) => {
+                match response {
+                    Ok(graphql_response) => {
+                        if let Some(data) = graphql_response.data {
+                            self.categories = data.categories;
+                        }
+                    }
+                    Err(error) => self.error = Some(error.to_string()),
+                }
+                self.categories_task = None;
+            }
             Msg::ReceiveResponse(response) => {
                 match response {
                     Ok(graphql_response) => {
@@ -246,10 +874,11 @@ impl Component for UpdatePostModel {
                             );
                         }
                         if graphql_response.data.is_some() {
-                            self.post = graphql_response.data.unwrap().post;
-                            if let Some(post) = &self.post {
-                                self.text = post.text.clone();
+                            let post = graphql_response.data.unwrap().post;
+                            if let Some(ref post) = post {
+                                self.fill(post);
                             }
+                            self.post = post;
                         }
                     }
                     Err(error) => self.error = Some(error.to_string()),
@@ -261,27 +890,169 @@ impl Component for UpdatePostModel {
                 self.success = None;
             }
             Msg::UpdateReceiveResponse(response) => {
+                self.fetch_task = None;
                 match response {
                     Ok(graphql_response) => {
-                        if graphql_response.errors.is_some() {
-                            self.error = Some(
-                                graphql_response
-                                    .errors
-                                    .unwrap()
-                                    .into_iter()
-                                    .map(|error| error.message)
-                                    .collect(),
-                            );
+                        if graphql_response.conflict_version().is_some() {
+                            self.load_latest();
+                            return true;
+                        }
+                        self.errors = Errors {
+                            title: graphql_response.field_error("title"),
+                            slug: graphql_response.field_error("slug"),
+                            summary: graphql_response.field_error("summary"),
+                            cover_image_url: graphql_response.field_error("coverImageUrl"),
+                            tags: graphql_response.field_error("tags"),
+                            published_at: graphql_response.field_error("publishedAt"),
+                            seo_title: graphql_response.field_error("seoTitle"),
+                            seo_description: graphql_response.field_error("seoDescription"),
+                            category_id: graphql_response.field_error("categoryId"),
+                        };
+                        self.error = graphql_response.other_errors();
+                        if let Some(data) = graphql_response.data {
+                            self.fill(&data.update_post);
+                            self.post = Some(data.update_post);
+                            self.conflict = None;
+                            self.success = Some(String::from("Updated!"));
+                            self.load_revisions();
+                        }
+                    }
+                    Err(error) => self.error = Some(error.to_string()),
+                }
+            }
+            Msg::ReceiveLatest(response) => {
+                match response {
+                    Ok(graphql_response) => {
+                        self.conflict = graphql_response.data.and_then(|data| data.post);
+                    }
+                    Err(error) => self.error = Some(error.to_string()),
+                }
+                self.fetch_task = None;
+            }
+            Msg::TakeTheirs => {
+                if let Some(latest) = self.conflict.take() {
+                    self.fill(&latest);
+                    self.post = Some(latest);
+                }
+            }
+            Msg::KeepMine => {
+                // Every field that differs from their post is sent, so ours
+                // replace theirs.
+                if let Some(latest) = self.conflict.take() {
+                    self.version = latest.version;
+                    self.post = Some(latest);
+                    self.link.send_message(Msg::SubmitNewPost(None));
+                }
+            }
+            Msg::ReceiveRevisions(response) => {
+                match response {
+                    Ok(graphql_response) => {
+                        if let Some(error) = graphql_response.other_errors() {
+                            self.error = Some(error);
+                        }
+                        if let Some(data) = graphql_response.data {
+                            self.revisions = data.post_revisions;
+                        }
+                    }
+                    Err(error) => self.error = Some(error.to_string()),
+                }
+                self.history_task = None;
+            }
+            Msg::Compare(revision_id) => {
+                let latest_id = match self.revisions.first() {
+                    Some(latest) => latest.id,
+                    None => return false,
+                };
+                let operation = RevisionDiffConnection::build(RevisionDiffArguments {
+                    from: revision_id,
+                    to: latest_id,
+                });
+                let request = authorized_request(serde_json::to_string(&operation).unwrap());
+                let callback = self.link.callback(
+                    move |response: Response<
+                        Json<Result<ApiResponse<RevisionDiffConnection>, anyhow::Error>>,
+                    >| {
+                        let Json(data) = response.into_body();
+                        Msg::ReceiveDiff(revision_id, data)
+                    },
+                );
+                let task = FetchService::fetch(request, callback).expect("failed to start request");
+                self.history_task = Some(task);
+            }
+            Msg::ReceiveDiff(revision_id, response) => {
+                match response {
+                    Ok(graphql_response) => {
+                        if let Some(error) = graphql_response.other_errors() {
+                            self.error = Some(error);
+                        }
+                        self.diff = graphql_response
+                            .data
+                            .map(|data| (revision_id, data.revision_diff));
+                    }
+                    Err(error) => self.error = Some(error.to_string()),
+                }
+                self.history_task = None;
+            }
+            Msg::Restore(revision_id) => {
+                let operation =
+                    RestoreRevisionConnection::build(RestoreRevisionArguments { revision_id });
+                let request = authorized_request(serde_json::to_string(&operation).unwrap());
+                let callback = self.link.callback(
+                    |response: Response<
+                        Json<Result<ApiResponse<RestoreRevisionConnection>, anyhow::Error>>,
+                    >| {
+                        let Json(data) = response.into_body();
+                        Msg::ReceiveRestore(data)
+                    },
+                );
+                let task = FetchService::fetch(request, callback).expect("failed to start request");
+                self.history_task = Some(task);
+            }
+            Msg::ReceiveRestore(response) => {
+                self.history_task = None;
+                match response {
+                    Ok(graphql_response) => {
+                        if let Some(error) = graphql_response.other_errors() {
+                            self.error = Some(error);
                         }
                         if graphql_response.data.is_some() {
-                            let post = graphql_response.data.unwrap().update_post;
-                            self.success = Some(post.into());
+                            self.diff = None;
+                            self.load_post();
+                            self.load_revisions();
                         }
                     }
                     Err(error) => self.error = Some(error.to_string()),
                 }
+            }
+            Msg::Delete => {
+                let operation = DeletePostConnection::build(PostArguments {
+                    post_id: self.props.id,
+                });
+                let request = authorized_request(serde_json::to_string(&operation).unwrap());
+                let callback = self.link.callback(
+                    |response: Response<
+                        Json<Result<ApiResponse<DeletePostConnection>, anyhow::Error>>,
+                    >| {
+                        let Json(data) = response.into_body();
+                        Msg::ReceiveDeleted(data)
+                    },
+                );
+                let task = FetchService::fetch(request, callback).expect("failed to start request");
+                self.fetch_task = Some(task);
+            }
+            Msg::ReceiveDeleted(response) => {
                 self.fetch_task = None;
+                match response {
+                    Ok(graphql_response) => {
+                        self.error = graphql_response.other_errors();
+                        if graphql_response.data.is_some() {
+                            self.router_agent.send(ChangeRoute(AppRoute::Trash.into()));
+                        }
+                    }
+                    Err(error) => self.error = Some(error.to_string()),
+                }
             }
+            Msg::Ignore => return false,
         }
         true
     }
@@ -311,11 +1082,35 @@ impl Component for UpdatePostModel {
                     <div class={"prose"}>{self.markdown_node()}</div>
                 </div>
             </div>
-            <button
-              onclick=self.link.callback(|_| Msg::SubmitNewPost)
-            >
-              {"Submit New Post"}
-            </button>
+            { self.view_details() }
+            <div class="max-w-3xl mx-auto mt-6 flex justify-end space-x-3">
+              <button
+                class="mr-auto py-2 px-4 border border-gray-300 rounded-md shadow-sm text-sm font-medium text-red-600 bg-white hover:bg-gray-50"
+                onclick=self.link.callback(|_| Msg::Delete)
+              >
+                {"Move to trash"}
+              </button>
+              <button
+                class="bg-white py-2 px-4 border border-gray-300 rounded-md shadow-sm text-sm font-medium text-gray-700 hover:bg-gray-50"
+                onclick=self.link.callback(|_| Msg::SubmitNewPost(Some(PostStatus::Draft)))
+              >
+                {"Save as draft"}
+              </button>
+              <button
+                class="bg-white py-2 px-4 border border-gray-300 rounded-md shadow-sm text-sm font-medium text-gray-700 hover:bg-gray-50"
+                onclick=self.link.callback(|_| Msg::SubmitNewPost(Some(PostStatus::Published)))
+              >
+                {"Publish"}
+              </button>
+              <button
+                class="py-2 px-4 border border-transparent rounded-md shadow-sm text-sm font-medium text-white bg-indigo-600 hover:bg-indigo-700"
+                onclick=self.link.callback(|_| Msg::SubmitNewPost(None))
+              >
+                {"Save"}
+              </button>
+            </div>
+            { self.view_conflict() }
+            { self.view_history() }
             { self.view_success() }
             { self.view_error() }
           </div>
@@ -0,0 +1,124 @@
+use crate::query_dsl;
+use cynic::GraphQLResponse;
+use cynic::MutationBuilder;
+use serde::Deserialize;
+use yew::{
+    format::Json,
+    prelude::*,
+    services::fetch::{FetchService, FetchTask, Request, Response},
+};
+
+#[derive(cynic::FragmentArguments)]
+pub struct VerifyEmailArguments {
+    token: String,
+}
+
+#[derive(cynic::QueryFragment, Debug, Deserialize)]
+#[cynic(
+    schema_path = "schema.graphql",
+    query_module = "query_dsl",
+    graphql_type = "MutationRoot",
+    argument_struct = "VerifyEmailArguments"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyEmailConnection {
+    #[arguments(token = args.token.clone())]
+    verify_email: String,
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct VerifyEmailProps {
+    pub token: String,
+}
+
+pub enum Msg {
+    ReceiveResponse(Result<GraphQLResponse<VerifyEmailConnection>, anyhow::Error>),
+}
+
+pub struct VerifyEmailModel {
+    fetch_task: Option<FetchTask>,
+    verified: bool,
+    error: Option<String>,
+}
+
+impl Component for VerifyEmailModel {
+    type Message = Msg;
+    type Properties = VerifyEmailProps;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let operation = VerifyEmailConnection::build(VerifyEmailArguments { token: props.token });
+
+        let query = serde_json::to_string(&operation).unwrap();
+
+        let request = Request::post("/graphql")
+            .header("Content-Type", "application/json")
+            .body(Ok(query))
+            .expect("Failed to build request.");
+        let callback = link.callback(
+            |response: Response<
+                Json<Result<GraphQLResponse<VerifyEmailConnection>, anyhow::Error>>,
+            >| {
+                let Json(data) = response.into_body();
+                Msg::ReceiveResponse(data)
+            },
+        );
+        let task = FetchService::fetch(request, callback).expect("failed to start request");
+
+        Self {
+            fetch_task: Some(task),
+            verified: false,
+            error: None,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::ReceiveResponse(response) => {
+                match response {
+                    Ok(graphql_response) => {
+                        if let Some(errors) = graphql_response.errors {
+                            self.error =
+                                Some(errors.into_iter().map(|error| error.message).collect());
+                        }
+                        self.verified = graphql_response.data.is_some();
+                    }
+                    Err(error) => self.error = Some(error.to_string()),
+                }
+                self.fetch_task = None;
+            }
+        }
+        true
+    }
+
+    fn change(&mut self, _props: Self::Properties) -> ShouldRender {
+        false
+    }
+
+    fn view(&self) -> Html {
+        let message = if self.fetch_task.is_some() {
+            String::from("Verifying your email address...")
+        } else if self.verified {
+            String::from("Your email address is verified. Thank you!")
+        } else {
+            self.error.clone().unwrap_or_default()
+        };
+
+        html! {
+          <div class="min-h-screen bg-white flex">
+            <div class="flex-1 flex flex-col justify-center py-12 px-4 sm:px-6 lg:flex-none lg:px-20 xl:px-24">
+              <div class="mx-auto w-full max-w-sm lg:w-96">
+                <h2 class="mt-6 text-3xl font-extrabold text-gray-900">
+                  {"Email verification"}
+                </h2>
+                <p class="mt-8 text-sm text-gray-700">{message}</p>
+                <div class="mt-6 text-sm">
+                  <a href="/" class="font-medium text-indigo-600 hover:text-indigo-500">
+                    {"Go to the blog"}
+                  </a>
+                </div>
+              </div>
+            </div>
+          </div>
+        }
+    }
+}
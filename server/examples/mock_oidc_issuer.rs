@@ -0,0 +1,195 @@
+// A stand-in identity provider for trying the OIDC sign in locally. It approves
+// every authorization request without asking anything.
+//
+//   cargo run --example mock_oidc_issuer
+//
+// and start the server with OIDC_ISSUER=http://localhost:3031,
+// OIDC_CLIENT_ID=blog and OIDC_REDIRECT_URL=http://localhost:3030/auth/oidc/callback.
+// MOCK_OIDC_SUBJECT, MOCK_OIDC_EMAIL and MOCK_OIDC_NAME choose who signs in.
+use chrono::{Duration, Utc};
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+use rand::{distributions::Alphanumeric, thread_rng, Rng};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::env;
+use std::sync::{Arc, Mutex};
+use url::Url;
+use warp::{
+    http::{StatusCode, Uri},
+    Filter, Reply,
+};
+
+const ISSUER: &str = "http://localhost:3031";
+const KEY_ID: &str = "mock";
+const PRIVATE_KEY: &str = include_str!("mock_oidc_issuer.pem");
+// The public half of PRIVATE_KEY, as the base64url modulus and exponent.
+const MODULUS: &str = "voyhZi4V-yKEW22JcZDSdYkU34LbGTdnjEMHXLxHwuNVoyhhI5fFhXA9xJgSXtuQ2BxGpsNqdmdhh3r7s1KEKlpSCkJ3vbNEtvrZe_YhuLNPzBfLmElt_PZmodMhAfhXrMx9uEBxHk41rwwt4Gef8sJlBiOdUyDBisOKZ-Ltfw84cXQ76oGpJR6FVucxzFLVwTlXOyOImZjarLmSg6T4uJYvlRLELeprOeZziKwDSBnVQX8zQJHGfVbOj4hbgsD7ZTUOK19uNY4d1AgB3ZwA5niMVRMzFEZqOjIlEVbdbqezFAbp9leQOxHfpfSD03Ck86U0kQe_CD2EWQ3J1PwuHQ";
+const EXPONENT: &str = "AQAB";
+
+struct Grant {
+    client_id: String,
+    redirect_uri: String,
+    nonce: Option<String>,
+    code_challenge: String,
+}
+
+type Grants = Arc<Mutex<HashMap<String, Grant>>>;
+
+#[derive(Deserialize)]
+struct AuthorizeQuery {
+    client_id: String,
+    redirect_uri: String,
+    state: Option<String>,
+    nonce: Option<String>,
+    code_challenge: String,
+    code_challenge_method: String,
+}
+
+#[derive(Deserialize)]
+struct TokenForm {
+    code: String,
+    redirect_uri: String,
+    code_verifier: String,
+}
+
+#[derive(Serialize)]
+struct Claims {
+    iss: &'static str,
+    sub: String,
+    aud: String,
+    exp: i64,
+    iat: i64,
+    nonce: Option<String>,
+    email: String,
+    email_verified: bool,
+    name: String,
+}
+
+fn error(status: StatusCode, error: &str) -> warp::reply::Response {
+    warp::reply::with_status(warp::reply::json(&json!({ "error": error })), status).into_response()
+}
+
+fn authorize(query: AuthorizeQuery, grants: Grants) -> warp::reply::Response {
+    if query.code_challenge_method != "S256" {
+        return error(StatusCode::BAD_REQUEST, "invalid_request");
+    }
+    let mut redirect = match Url::parse(&query.redirect_uri) {
+        Ok(redirect) => redirect,
+        Err(_) => return error(StatusCode::BAD_REQUEST, "invalid_request"),
+    };
+    let code: String = thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect();
+    redirect.query_pairs_mut().append_pair("code", &code);
+    if let Some(ref state) = query.state {
+        redirect.query_pairs_mut().append_pair("state", state);
+    }
+    grants.lock().unwrap().insert(
+        code,
+        Grant {
+            client_id: query.client_id,
+            redirect_uri: query.redirect_uri,
+            nonce: query.nonce,
+            code_challenge: query.code_challenge,
+        },
+    );
+    match redirect.as_str().parse::<Uri>() {
+        Ok(uri) => warp::redirect::temporary(uri).into_response(),
+        Err(_) => error(StatusCode::BAD_REQUEST, "invalid_request"),
+    }
+}
+
+fn token(form: TokenForm, grants: Grants) -> warp::reply::Response {
+    let grant = match grants.lock().unwrap().remove(&form.code) {
+        Some(grant) => grant,
+        None => return error(StatusCode::BAD_REQUEST, "invalid_grant"),
+    };
+    let challenge = base64::encode_config(
+        Sha256::digest(form.code_verifier.as_bytes()),
+        base64::URL_SAFE_NO_PAD,
+    );
+    if challenge != grant.code_challenge || form.redirect_uri != grant.redirect_uri {
+        return error(StatusCode::BAD_REQUEST, "invalid_grant");
+    }
+
+    let now = Utc::now();
+    let email = env::var("MOCK_OIDC_EMAIL").unwrap_or_else(|_| "jane@example.com".to_string());
+    let claims = Claims {
+        iss: ISSUER,
+        sub: env::var("MOCK_OIDC_SUBJECT").unwrap_or_else(|_| "mock-user".to_string()),
+        aud: grant.client_id,
+        exp: (now + Duration::minutes(5)).timestamp(),
+        iat: now.timestamp(),
+        nonce: grant.nonce,
+        name: env::var("MOCK_OIDC_NAME").unwrap_or_else(|_| email.clone()),
+        email,
+        email_verified: true,
+    };
+    let mut header = Header::new(Algorithm::RS256);
+    header.kid = Some(KEY_ID.to_string());
+    let key = EncodingKey::from_rsa_pem(PRIVATE_KEY.as_bytes()).expect("Invalid mock key");
+    match encode(&header, &claims, &key) {
+        Ok(id_token) => warp::reply::json(&json!({
+            "access_token": "mock",
+            "token_type": "Bearer",
+            "expires_in": 300,
+            "id_token": id_token,
+        }))
+        .into_response(),
+        Err(_) => error(StatusCode::INTERNAL_SERVER_ERROR, "server_error"),
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let grants: Grants = Arc::default();
+    let with_grants = warp::any().map(move || grants.clone());
+
+    let discovery = warp::path!(".well-known" / "openid-configuration")
+        .and(warp::get())
+        .map(|| {
+            warp::reply::json(&json!({
+                "issuer": ISSUER,
+                "authorization_endpoint": format!("{}/authorize", ISSUER),
+                "token_endpoint": format!("{}/token", ISSUER),
+                "jwks_uri": format!("{}/jwks", ISSUER),
+                "response_types_supported": ["code"],
+                "subject_types_supported": ["public"],
+                "id_token_signing_alg_values_supported": ["RS256"],
+                "code_challenge_methods_supported": ["S256"],
+            }))
+        });
+
+    let authorization = warp::path("authorize")
+        .and(warp::get())
+        .and(warp::query::<AuthorizeQuery>())
+        .and(with_grants.clone())
+        .map(authorize);
+
+    let token_exchange = warp::path("token")
+        .and(warp::post())
+        .and(warp::body::form::<TokenForm>())
+        .and(with_grants)
+        .map(token);
+
+    let jwks = warp::path("jwks").and(warp::get()).map(|| {
+        warp::reply::json(&json!({
+            "keys": [{
+                "kty": "RSA",
+                "use": "sig",
+                "alg": "RS256",
+                "kid": KEY_ID,
+                "n": MODULUS,
+                "e": EXPONENT,
+            }]
+        }))
+    });
+
+    let routes = discovery.or(authorization).or(token_exchange).or(jwks);
+    println!("Mock OIDC issuer listening on {}", ISSUER);
+    warp::serve(routes).run(([127, 0, 0, 1], 3031)).await;
+}
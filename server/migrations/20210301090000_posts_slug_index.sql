@@ -0,0 +1,3 @@
+UPDATE posts SET slug = slug || '-' || id
+WHERE id NOT IN (SELECT MIN(id) FROM posts GROUP BY slug);
+CREATE UNIQUE INDEX posts_slug_idx ON posts (slug);
\ No newline at end of file
@@ -0,0 +1,7 @@
+CREATE TABLE post_slug_history (
+    id serial PRIMARY KEY,
+    post_id integer NOT NULL REFERENCES posts (id),
+    slug varchar(255) NOT NULL,
+    created_at timestamp with time zone NOT NULL DEFAULT now()
+);
+CREATE UNIQUE INDEX post_slug_history_idx ON post_slug_history (slug);
\ No newline at end of file
@@ -0,0 +1,5 @@
+CREATE TYPE post_status AS ENUM ('draft', 'published', 'scheduled', 'archived');
+ALTER TABLE posts ADD COLUMN status post_status NOT NULL DEFAULT 'draft';
+ALTER TABLE posts ADD COLUMN published_at timestamp with time zone;
+UPDATE posts SET status = 'published', published_at = created_at;
+CREATE INDEX posts_status_idx ON posts (status, published_at);
\ No newline at end of file
@@ -0,0 +1,4 @@
+CREATE TYPE user_role AS ENUM ('admin', 'editor', 'author', 'contributor');
+ALTER TABLE users ADD COLUMN role user_role NOT NULL DEFAULT 'contributor';
+UPDATE users SET role = 'author';
+UPDATE users SET role = 'admin' WHERE id = (SELECT MIN(id) FROM users);
\ No newline at end of file
@@ -0,0 +1,8 @@
+CREATE TABLE installation (
+    id boolean PRIMARY KEY DEFAULT true CHECK (id),
+    completed_at timestamp with time zone NOT NULL DEFAULT now()
+);
+INSERT INTO installation (completed_at)
+SELECT now()
+WHERE EXISTS (SELECT 1 FROM settings WHERE key = 'title')
+OR EXISTS (SELECT 1 FROM users);
\ No newline at end of file
@@ -0,0 +1,3 @@
+DELETE FROM settings WHERE id NOT IN (SELECT MAX(id) FROM settings GROUP BY key);
+CREATE UNIQUE INDEX settings_key_idx ON settings (key);
+ALTER TABLE settings ADD COLUMN updated_at timestamp with time zone NOT NULL DEFAULT now();
\ No newline at end of file
@@ -0,0 +1,8 @@
+ALTER TABLE user_sessions
+    ADD COLUMN expires_at timestamp with time zone,
+    ADD COLUMN last_seen_at timestamp with time zone NOT NULL DEFAULT now(),
+    ADD COLUMN user_agent text,
+    ADD COLUMN ip_address text;
+UPDATE user_sessions SET expires_at = date + interval '30 days', last_seen_at = date;
+ALTER TABLE user_sessions ALTER COLUMN expires_at SET NOT NULL;
+CREATE INDEX user_sessions_user_id_idx ON user_sessions (user_id);
\ No newline at end of file
@@ -0,0 +1,3 @@
+DELETE FROM user_sessions;
+ALTER TABLE user_sessions RENAME COLUMN token TO token_hash;
+ALTER TABLE user_sessions ALTER COLUMN token_hash TYPE varchar(64);
\ No newline at end of file
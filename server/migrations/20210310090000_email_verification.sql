@@ -0,0 +1,10 @@
+ALTER TABLE users ADD COLUMN email_verified_at timestamp with time zone;
+UPDATE users SET email_verified_at = date;
+CREATE TABLE email_verifications (
+    id serial PRIMARY KEY,
+    user_id integer NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    token_hash varchar(64) NOT NULL UNIQUE,
+    expires_at timestamp with time zone NOT NULL,
+    used_at timestamp with time zone,
+    created_at timestamp with time zone NOT NULL DEFAULT now()
+);
\ No newline at end of file
@@ -0,0 +1,6 @@
+CREATE TABLE sign_in_throttles (
+    key text PRIMARY KEY,
+    failures integer NOT NULL DEFAULT 0,
+    last_failure_at timestamp with time zone NOT NULL DEFAULT now(),
+    locked_until timestamp with time zone
+);
\ No newline at end of file
@@ -0,0 +1,21 @@
+ALTER TABLE users
+    ADD COLUMN totp_secret varchar(32),
+    ADD COLUMN totp_enabled_at timestamp with time zone,
+    ADD COLUMN totp_last_step bigint;
+
+CREATE TABLE recovery_codes (
+    id serial PRIMARY KEY,
+    user_id integer NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    code_hash varchar(64) NOT NULL UNIQUE,
+    used_at timestamp with time zone,
+    created_at timestamp with time zone NOT NULL DEFAULT now()
+);
+
+CREATE TABLE sign_in_challenges (
+    id serial PRIMARY KEY,
+    user_id integer NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    token_hash varchar(64) NOT NULL UNIQUE,
+    failures integer NOT NULL DEFAULT 0,
+    expires_at timestamp with time zone NOT NULL,
+    created_at timestamp with time zone NOT NULL DEFAULT now()
+);
\ No newline at end of file
@@ -0,0 +1,10 @@
+CREATE TABLE invitations (
+    id serial PRIMARY KEY,
+    email varchar(255) NOT NULL,
+    role user_role NOT NULL,
+    token_hash varchar(64) NOT NULL UNIQUE,
+    invited_by integer REFERENCES users (id) ON DELETE SET NULL,
+    expires_at timestamp with time zone NOT NULL,
+    accepted_at timestamp with time zone,
+    created_at timestamp with time zone NOT NULL DEFAULT now()
+);
\ No newline at end of file
@@ -0,0 +1,22 @@
+ALTER TABLE users ADD COLUMN bio text NOT NULL DEFAULT '';
+ALTER TABLE users ADD COLUMN avatar_url text;
+ALTER TABLE users ADD COLUMN website_url text;
+ALTER TABLE users ADD COLUMN social_links text[] NOT NULL DEFAULT '{}';
+
+CREATE TYPE author AS (
+    id integer,
+    display_name varchar(255),
+    bio text,
+    avatar_url text,
+    website_url text,
+    social_links text[]
+);
+
+CREATE FUNCTION author_of(user_id integer) RETURNS author AS $$
+    SELECT
+        ROW(id, name, bio, avatar_url, website_url, social_links)::author
+    FROM
+        users
+    WHERE
+        id = user_id
+$$ LANGUAGE sql STABLE;
\ No newline at end of file
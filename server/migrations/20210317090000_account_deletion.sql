@@ -0,0 +1,8 @@
+ALTER TABLE posts ALTER COLUMN created_by DROP NOT NULL;
+ALTER TABLE posts DROP CONSTRAINT posts_created_by_fkey;
+ALTER TABLE posts ADD CONSTRAINT posts_created_by_fkey
+    FOREIGN KEY (created_by) REFERENCES users (id) ON DELETE SET NULL;
+
+ALTER TABLE user_sessions DROP CONSTRAINT user_sessions_user_id_fkey;
+ALTER TABLE user_sessions ADD CONSTRAINT user_sessions_user_id_fkey
+    FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE;
\ No newline at end of file
@@ -0,0 +1,17 @@
+CREATE TABLE post_revisions (
+    id serial PRIMARY KEY,
+    post_id integer NOT NULL REFERENCES posts (id) ON DELETE CASCADE,
+    title varchar(255) NOT NULL,
+    text text NOT NULL,
+    summary text NOT NULL,
+    created_by integer REFERENCES users (id) ON DELETE SET NULL,
+    created_at timestamp with time zone NOT NULL DEFAULT now()
+);
+CREATE INDEX post_revisions_post_idx ON post_revisions (post_id, id);
+
+INSERT INTO post_revisions
+    (post_id, title, text, summary, created_by, created_at)
+SELECT
+    id, title, text, summary, created_by, created_at
+FROM
+    posts;
\ No newline at end of file
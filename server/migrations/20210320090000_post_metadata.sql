@@ -0,0 +1,4 @@
+ALTER TABLE posts ADD COLUMN cover_image_url text;
+ALTER TABLE posts ADD COLUMN tags text[] NOT NULL DEFAULT '{}';
+ALTER TABLE posts ADD COLUMN seo_title varchar(255);
+ALTER TABLE posts ADD COLUMN seo_description text;
\ No newline at end of file
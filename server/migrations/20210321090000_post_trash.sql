@@ -0,0 +1,6 @@
+ALTER TABLE posts ADD COLUMN deleted_at timestamp with time zone;
+CREATE INDEX posts_deleted_at_idx ON posts (deleted_at) WHERE deleted_at IS NOT NULL;
+
+ALTER TABLE post_slug_history DROP CONSTRAINT post_slug_history_post_id_fkey;
+ALTER TABLE post_slug_history ADD CONSTRAINT post_slug_history_post_id_fkey
+    FOREIGN KEY (post_id) REFERENCES posts (id) ON DELETE CASCADE;
\ No newline at end of file
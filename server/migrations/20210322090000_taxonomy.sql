@@ -0,0 +1,106 @@
+CREATE TABLE tags (
+    id serial PRIMARY KEY,
+    slug varchar(255) NOT NULL UNIQUE,
+    name varchar(255) NOT NULL,
+    created_at timestamp with time zone NOT NULL DEFAULT now()
+);
+
+CREATE TABLE post_tags (
+    post_id integer NOT NULL REFERENCES posts (id) ON DELETE CASCADE,
+    tag_id integer NOT NULL REFERENCES tags (id) ON DELETE CASCADE,
+    PRIMARY KEY (post_id, tag_id)
+);
+CREATE INDEX post_tags_tag_idx ON post_tags (tag_id);
+
+INSERT INTO tags
+    (slug, name)
+SELECT DISTINCT ON (slug)
+    slug, name
+FROM (
+    SELECT
+        trim(both '-' from regexp_replace(lower(tag), '[^a-z0-9]+', '-', 'g')) AS slug,
+        tag AS name
+    FROM
+        posts
+    CROSS JOIN
+        unnest(posts.tags) AS tag
+) AS existing
+WHERE
+    slug <> ''
+ORDER BY
+    slug, name;
+
+INSERT INTO post_tags
+    (post_id, tag_id)
+SELECT DISTINCT
+    posts.id, tags.id
+FROM
+    posts
+CROSS JOIN
+    unnest(posts.tags) AS tag
+INNER JOIN
+    tags
+ON
+    tags.slug = trim(both '-' from regexp_replace(lower(tag), '[^a-z0-9]+', '-', 'g'));
+
+ALTER TABLE posts DROP COLUMN tags;
+
+CREATE FUNCTION tags_of(post_id integer) RETURNS text[] AS $$
+    SELECT
+        ARRAY(
+            SELECT
+                tags.name
+            FROM
+                post_tags
+            INNER JOIN
+                tags
+            ON
+                tags.id = post_tags.tag_id
+            WHERE
+                post_tags.post_id = tags_of.post_id
+            ORDER BY
+                tags.name
+        )
+$$ LANGUAGE sql STABLE;
+
+CREATE TABLE categories (
+    id serial PRIMARY KEY,
+    parent_id integer REFERENCES categories (id),
+    slug varchar(255) NOT NULL UNIQUE,
+    name varchar(255) NOT NULL,
+    created_at timestamp with time zone NOT NULL DEFAULT now()
+);
+CREATE INDEX categories_parent_idx ON categories (parent_id);
+
+ALTER TABLE posts ADD COLUMN category_id integer REFERENCES categories (id) ON DELETE SET NULL;
+
+-- Only posts readers can see are counted.
+CREATE FUNCTION tag_post_count(tag_id integer) RETURNS bigint AS $$
+    SELECT
+        count(*)
+    FROM
+        post_tags
+    INNER JOIN
+        posts
+    ON
+        posts.id = post_tags.post_id
+    WHERE
+        post_tags.tag_id = tag_post_count.tag_id
+    AND
+        posts.status = 'published'
+    AND
+        posts.deleted_at IS NULL
+$$ LANGUAGE sql STABLE;
+
+CREATE FUNCTION category_post_count(category_id integer) RETURNS bigint AS $$
+    SELECT
+        count(*)
+    FROM
+        posts
+    WHERE
+        posts.category_id = category_post_count.category_id
+    AND
+        posts.status = 'published'
+    AND
+        posts.deleted_at IS NULL
+$$ LANGUAGE sql STABLE;
\ No newline at end of file
@@ -0,0 +1,3 @@
+-- A login started by a signed in user links the identity to that user
+-- instead of signing in.
+ALTER TABLE oidc_logins ADD COLUMN user_id integer REFERENCES users (id) ON DELETE CASCADE;
\ No newline at end of file
@@ -0,0 +1,28 @@
+-- Emails are compared without case, and two accounts can't share one.
+-- Accounts created before this may share an address, which can't be merged
+-- automatically, so the migration stops and names the addresses to fix.
+DO $$
+DECLARE
+    duplicates text;
+BEGIN
+    SELECT
+        string_agg(email, ', ')
+    INTO
+        duplicates
+    FROM (
+        SELECT
+            lower(email) AS email
+        FROM
+            users
+        GROUP BY
+            lower(email)
+        HAVING
+            count(*) > 1
+    ) AS duplicated;
+    IF duplicates IS NOT NULL THEN
+        RAISE EXCEPTION 'Several accounts share the email addresses %. Keep one account per address by changing the email of the others or deleting them, then start the server again.', duplicates;
+    END IF;
+END
+$$;
+
+CREATE UNIQUE INDEX users_email_idx ON users (lower(email));
\ No newline at end of file
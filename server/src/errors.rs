@@ -0,0 +1,95 @@
+use async_graphql::{Error, ErrorExtensions, Name, Result, Value};
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+
+pub enum ApiError {
+    Unauthorized,
+    Forbidden(&'static str),
+    MissingScope(&'static str),
+    InvalidInput(FieldErrors),
+    TooManyAttempts(DateTime<Utc>),
+    Conflict(&'static str, i32),
+}
+
+impl ErrorExtensions for ApiError {
+    fn extend(&self) -> Error {
+        match self {
+            ApiError::Unauthorized => Error::new("No valid session token found.")
+                .extend_with(|_, extensions| extensions.set("code", "UNAUTHORIZED")),
+            ApiError::Forbidden(message) => Error::new(*message)
+                .extend_with(|_, extensions| extensions.set("code", "FORBIDDEN")),
+            ApiError::MissingScope(scope) => Error::new(format!(
+                "This API token is missing the \"{}\" scope.",
+                scope
+            ))
+            .extend_with(|_, extensions| {
+                extensions.set("code", "FORBIDDEN");
+                extensions.set("scope", *scope);
+            }),
+            ApiError::InvalidInput(errors) => {
+                Error::new(errors.message()).extend_with(|_, extensions| {
+                    extensions.set("code", "INVALID_INPUT");
+                    extensions.set("fields", errors.to_value());
+                })
+            }
+            ApiError::Conflict(message, current_version) => {
+                Error::new(*message).extend_with(|_, extensions| {
+                    extensions.set("code", "CONFLICT");
+                    extensions.set("currentVersion", *current_version);
+                })
+            }
+            ApiError::TooManyAttempts(locked_until) => {
+                let seconds = (*locked_until - Utc::now()).num_seconds().max(1);
+                Error::new(format!(
+                    "Too many failed sign in attempts. Try again in {} seconds.",
+                    seconds
+                ))
+                .extend_with(|_, extensions| {
+                    extensions.set("code", "TOO_MANY_ATTEMPTS");
+                    extensions.set("retryAfter", seconds);
+                })
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct FieldErrors(Vec<(&'static str, String)>);
+
+impl FieldErrors {
+    pub fn add(&mut self, field: &'static str, message: impl Into<String>) {
+        self.0.push((field, message.into()));
+    }
+
+    pub fn into_result(self) -> Result<()> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(ApiError::InvalidInput(self).extend())
+        }
+    }
+
+    fn message(&self) -> String {
+        self.0
+            .iter()
+            .map(|(_field, message)| message.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn to_value(&self) -> Value {
+        let mut fields = BTreeMap::new();
+        for (field, message) in &self.0 {
+            fields
+                .entry(Name::new(field))
+                .or_insert_with(|| Value::from(message.as_str()));
+        }
+        Value::Object(fields)
+    }
+}
+
+pub fn field_error(field: &'static str, message: impl Into<String>) -> Error {
+    let mut errors = FieldErrors::default();
+    errors.add(field, message);
+    ApiError::InvalidInput(errors).extend()
+}
@@ -0,0 +1,154 @@
+use async_graphql::{Error, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use lettre::{message::Mailbox, AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use std::env;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+const DEFAULT_FROM: &str = "Blog <blog@localhost>";
+const DEFAULT_DIRECTORY: &str = "./mail";
+
+#[derive(Clone, Debug)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, email: Email) -> Result<()>;
+}
+
+pub fn from_env() -> Arc<dyn Mailer> {
+    let from = env::var("MAIL_FROM").unwrap_or_else(|_| DEFAULT_FROM.to_string());
+    match env::var("MAILER").as_deref() {
+        Ok("smtp") => {
+            let url = env::var("SMTP_URL").expect("SMTP_URL must be set when MAILER=smtp");
+            Arc::new(SmtpMailer::new(&url, &from).expect("Invalid SMTP configuration"))
+        }
+        Ok("memory") => Arc::new(MemoryMailer::default()),
+        _ => {
+            let directory =
+                env::var("MAIL_DIRECTORY").unwrap_or_else(|_| DEFAULT_DIRECTORY.to_string());
+            Arc::new(FileMailer::new(directory.into(), &from))
+        }
+    }
+}
+
+fn build_message(from: &Mailbox, email: &Email) -> Result<Message> {
+    let to: Mailbox = match email.to.parse() {
+        Ok(to) => to,
+        Err(error) => {
+            println!("{}", error);
+            return Err(Error::from("The recipient address is not valid."));
+        }
+    };
+
+    match Message::builder()
+        .from(from.clone())
+        .to(to)
+        .subject(email.subject.clone())
+        .body(email.body.clone())
+    {
+        Ok(message) => Ok(message),
+        Err(error) => {
+            println!("{}", error);
+            Err(Error::from("Unable to build the email."))
+        }
+    }
+}
+
+fn parse_from(from: &str) -> Mailbox {
+    from.parse().expect("MAIL_FROM must be a valid mailbox")
+}
+
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    pub fn new(url: &str, from: &str) -> Result<Self> {
+        match AsyncSmtpTransport::<Tokio1Executor>::from_url(url) {
+            Ok(builder) => Ok(Self {
+                transport: builder.build(),
+                from: parse_from(from),
+            }),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from("The SMTP url is not valid."))
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: Email) -> Result<()> {
+        let message = build_message(&self.from, &email)?;
+        match self.transport.send(message).await {
+            Ok(_response) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from("Unable to send the email."))
+            }
+        }
+    }
+}
+
+pub struct FileMailer {
+    directory: PathBuf,
+    from: Mailbox,
+}
+
+impl FileMailer {
+    pub fn new(directory: PathBuf, from: &str) -> Self {
+        Self {
+            directory,
+            from: parse_from(from),
+        }
+    }
+}
+
+#[async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, email: Email) -> Result<()> {
+        let message = build_message(&self.from, &email)?;
+        let path = self
+            .directory
+            .join(format!("{}.eml", Utc::now().format("%Y%m%d%H%M%S%f")));
+        let written = match tokio::fs::create_dir_all(&self.directory).await {
+            Ok(()) => tokio::fs::write(&path, message.formatted()).await,
+            Err(error) => Err(error),
+        };
+        match written {
+            Ok(()) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from("Unable to write the email to disk."))
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct MemoryMailer {
+    sent: Mutex<Vec<Email>>,
+}
+
+impl MemoryMailer {
+    #[cfg(test)]
+    pub fn sent(&self) -> Vec<Email> {
+        self.sent.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl Mailer for MemoryMailer {
+    async fn send(&self, email: Email) -> Result<()> {
+        self.sent.lock().unwrap().push(email);
+        Ok(())
+    }
+}
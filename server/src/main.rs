@@ -1,17 +1,36 @@
 use async_graphql::http::{playground_source, GraphQLPlaygroundConfig};
 use async_graphql::{EmptySubscription, Schema};
 use async_graphql_warp::Response;
+use records::posts::{slug_history::PostSlugHistory, Viewer};
+use scheduler::{Scheduler, SchedulerEvent};
 use sqlx::postgres::PgPool;
 use std::convert::Infallible;
 use std::env;
-use warp::{http::Response as HttpResponse, Filter};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use warp::{
+    http::{Response as HttpResponse, Uri},
+    Filter, Rejection, Reply,
+};
 
+mod errors;
+mod mailer;
+mod oidc;
 mod records;
+mod scheduler;
 mod schema;
+#[cfg(test)]
+mod test_support;
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
 
 pub struct AuthToken(String);
+
+pub struct ClientInfo {
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+}
+
 struct QueryRoot;
 struct MutationRoot;
 
@@ -20,6 +39,56 @@ pub async fn db_connection() -> Result<PgPool> {
     Ok(PgPool::connect(&database_url).await?)
 }
 
+async fn redirect_previous_slug(
+    slug: String,
+    pg_pool: PgPool,
+) -> std::result::Result<impl Reply, Rejection> {
+    match PostSlugHistory::post(&pg_pool, &slug, Viewer::anonymous()).await {
+        Ok(Some(post)) => match format!("/post/{}", post.slug).parse::<Uri>() {
+            Ok(uri) => Ok(warp::redirect(uri)),
+            Err(_error) => Err(warp::reject::not_found()),
+        },
+        _ => Err(warp::reject::not_found()),
+    }
+}
+
+// Scripts usually send `Authorization: Bearer`, the app sends the `token` header.
+fn auth_token() -> impl Filter<Extract = (Option<AuthToken>,), Error = Rejection> + Clone {
+    warp::header::optional::<String>("token")
+        .and(warp::header::optional::<String>("authorization"))
+        .map(|token: Option<String>, authorization: Option<String>| {
+            let bearer = authorization.and_then(|authorization| {
+                authorization
+                    .strip_prefix("Bearer ")
+                    .map(|token| token.trim().to_string())
+            });
+            token.or(bearer).map(AuthToken)
+        })
+}
+
+fn client_info(
+    trust_proxy: bool,
+) -> impl Filter<Extract = (ClientInfo,), Error = Rejection> + Clone {
+    warp::header::optional::<String>("user-agent")
+        .and(warp::header::optional::<String>("x-forwarded-for"))
+        .and(warp::addr::remote())
+        .map(
+            move |user_agent, forwarded_for: Option<String>, remote: Option<SocketAddr>| {
+                // Only a proxy we sit behind may tell us who the client is.
+                let forwarded = forwarded_for
+                    .filter(|_| trust_proxy)
+                    .and_then(|forwarded| {
+                        forwarded.split(',').next().map(|ip| ip.trim().to_string())
+                    })
+                    .filter(|ip| !ip.is_empty());
+                ClientInfo {
+                    user_agent,
+                    ip_address: forwarded.or_else(|| remote.map(|remote| remote.ip().to_string())),
+                }
+            },
+        )
+}
+
 #[tokio::main]
 async fn main() {
     let pg_pool: PgPool = db_connection().await.expect("Database connection failed.");
@@ -28,23 +97,52 @@ async fn main() {
         .await
         .expect("Database migrations failed");
 
+    let scheduler = Scheduler::from_env(pg_pool.clone());
+    let mut scheduler_events = scheduler.subscribe();
+    tokio::spawn(async move {
+        while let Ok(event) = scheduler_events.recv().await {
+            match event {
+                SchedulerEvent::PostPublished(post) => {
+                    println!("Published scheduled post {}", post.slug)
+                }
+                SchedulerEvent::PostPurged(post) => {
+                    println!("Purged post {} from the trash", post.slug)
+                }
+            }
+        }
+    });
+    scheduler.spawn();
+
+    let oidc_client = oidc::OidcClient::from_env().map(Arc::new);
+    let oidc_client_route = oidc_client.clone();
+    let with_oidc_client = warp::any().and_then(move || {
+        let oidc_client = oidc_client_route.clone();
+        async move { oidc_client.ok_or_else(warp::reject::not_found) }
+    });
+
     let schema = Schema::build(QueryRoot, MutationRoot, EmptySubscription)
-        .data(pg_pool)
+        .data(pg_pool.clone())
+        .data(mailer::from_env())
+        .data(oidc_client.clone())
         .finish();
 
+    let trust_proxy = env::var("TRUST_PROXY").as_deref() == Ok("true");
     let graphql_post = warp::path("graphql")
         .and(warp::post())
-        .and(warp::header::optional("token"))
+        .and(auth_token())
+        .and(client_info(trust_proxy))
         .and(async_graphql_warp::graphql(schema.clone()))
         .and_then(
             |token,
+             client_info,
              (schema, mut request): (
                 Schema<QueryRoot, MutationRoot, EmptySubscription>,
                 async_graphql::Request,
             )| async move {
                 if let Some(token) = token {
-                    request = request.data(AuthToken(token));
+                    request = request.data(token);
                 }
+                request = request.data(client_info);
                 let resp = schema.execute(request).await;
                 Ok::<_, Infallible>(Response::from(resp))
             },
@@ -58,6 +156,28 @@ async fn main() {
 
     let static_files = warp::path("static").and(warp::fs::dir("./static"));
 
+    let oidc_pg_pool = pg_pool.clone();
+    let with_pg_pool = warp::any().map(move || oidc_pg_pool.clone());
+    let oidc_login = warp::path!("auth" / "oidc" / "login")
+        .and(warp::get())
+        .and(with_oidc_client.clone())
+        .and(with_pg_pool.clone())
+        .and_then(oidc::login);
+
+    let oidc_callback = warp::path!("auth" / "oidc" / "callback")
+        .and(warp::get())
+        .and(warp::query::<oidc::CallbackQuery>())
+        .and(warp::cookie::optional::<String>(oidc::STATE_COOKIE))
+        .and(with_oidc_client)
+        .and(with_pg_pool)
+        .and(client_info(trust_proxy))
+        .and_then(oidc::callback);
+
+    let post_redirect = warp::path!("post" / String)
+        .and(warp::get())
+        .and(warp::any().map(move || pg_pool.clone()))
+        .and_then(redirect_previous_slug);
+
     let catch_all = warp::any().map(move || {
         let body = include_str!("index.html");
         warp::reply::html(body)
@@ -66,6 +186,9 @@ async fn main() {
     let routes = static_files
         .or(graphql_post)
         .or(graphql_playground)
+        .or(oidc_login)
+        .or(oidc_callback)
+        .or(post_redirect)
         .or(catch_all);
 
     warp::serve(routes).run(([0, 0, 0, 0], 3030)).await;
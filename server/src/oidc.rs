@@ -0,0 +1,732 @@
+use crate::records::oidc_login::{NewOidcLogin, OidcLogin, LIFETIME_MINUTES};
+use crate::records::users::{
+    identity::UserIdentity, role::UserRole, session::NewSession, NewUser, SimpleUser,
+};
+use crate::ClientInfo;
+use async_graphql::{Error, Result, SimpleObject};
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::env;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use url::Url;
+use warp::{
+    http::{StatusCode, Uri},
+    Rejection, Reply,
+};
+
+const DEFAULT_PROVIDER_NAME: &str = "SSO";
+pub const LOGIN_PATH: &str = "/auth/oidc/login";
+pub const STATE_COOKIE: &str = "oidc_state";
+const METADATA_LIFETIME: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Clone, Debug)]
+pub struct OidcConfig {
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    pub redirect_url: String,
+    pub provider_name: String,
+    pub create_users: bool,
+}
+
+impl OidcConfig {
+    pub fn from_env() -> Option<Self> {
+        let issuer = env::var("OIDC_ISSUER").ok()?;
+        Some(Self {
+            issuer,
+            client_id: env::var("OIDC_CLIENT_ID")
+                .expect("OIDC_CLIENT_ID must be set when OIDC_ISSUER is"),
+            client_secret: env::var("OIDC_CLIENT_SECRET").ok(),
+            redirect_url: env::var("OIDC_REDIRECT_URL")
+                .expect("OIDC_REDIRECT_URL must be set when OIDC_ISSUER is"),
+            provider_name: env::var("OIDC_PROVIDER_NAME")
+                .unwrap_or_else(|_| DEFAULT_PROVIDER_NAME.to_string()),
+            create_users: env::var("OIDC_CREATE_USERS").as_deref() == Ok("true"),
+        })
+    }
+}
+
+#[derive(SimpleObject, Debug, Deserialize, Serialize, Clone)]
+pub struct SingleSignOn {
+    pub name: String,
+    pub login_url: String,
+}
+
+#[derive(Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+#[derive(Deserialize)]
+struct JsonWebKeySet {
+    keys: Vec<JsonWebKey>,
+}
+
+#[derive(Deserialize)]
+struct JsonWebKey {
+    kty: String,
+    kid: Option<String>,
+    n: Option<String>,
+    e: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct IdTokenClaims {
+    pub sub: String,
+    // Only the validation reads it, but requiring it rejects tokens that never expire.
+    #[serde(rename = "exp")]
+    _expires_at: u64,
+    pub nonce: Option<String>,
+    pub azp: Option<String>,
+    pub email: Option<String>,
+    #[serde(default, deserialize_with = "flexible_bool")]
+    pub email_verified: bool,
+    pub name: Option<String>,
+}
+
+// Some providers send email_verified as the string "true".
+fn flexible_bool<'de, D>(deserializer: D) -> std::result::Result<bool, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Flexible {
+        Bool(bool),
+        String(String),
+    }
+    Ok(match Flexible::deserialize(deserializer)? {
+        Flexible::Bool(value) => value,
+        Flexible::String(value) => value == "true",
+    })
+}
+
+fn code_challenge(code_verifier: &str) -> String {
+    base64::encode_config(
+        Sha256::digest(code_verifier.as_bytes()),
+        base64::URL_SAFE_NO_PAD,
+    )
+}
+
+fn invalid_response(error: impl std::fmt::Display) -> Error {
+    println!("{}", error);
+    Error::from("The identity provider sent an invalid response.")
+}
+
+async fn fetch_json<T: DeserializeOwned>(request: reqwest::RequestBuilder) -> Result<T> {
+    let response = match request
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+    {
+        Ok(response) => response,
+        Err(error) => {
+            println!("{}", error);
+            return Err(Error::from("The identity provider could not be reached."));
+        }
+    };
+    response.json::<T>().await.map_err(invalid_response)
+}
+
+pub struct OidcClient {
+    config: OidcConfig,
+    http: reqwest::Client,
+    metadata: Mutex<Option<(Instant, Arc<ProviderMetadata>)>>,
+}
+
+impl OidcClient {
+    pub fn new(config: OidcConfig) -> Self {
+        Self {
+            config,
+            http: reqwest::Client::new(),
+            metadata: Mutex::new(None),
+        }
+    }
+
+    pub fn from_env() -> Option<Self> {
+        OidcConfig::from_env().map(Self::new)
+    }
+
+    pub fn single_sign_on(&self) -> SingleSignOn {
+        SingleSignOn {
+            name: self.config.provider_name.clone(),
+            login_url: LOGIN_PATH.to_string(),
+        }
+    }
+
+    // Ties the state to the browser that started the login, so a callback
+    // carrying someone else's state is refused.
+    pub fn state_cookie(&self, state: &str, max_age: i64) -> String {
+        let secure = if self.config.redirect_url.starts_with("https://") {
+            "; Secure"
+        } else {
+            ""
+        };
+        format!(
+            "{}={}; Path=/auth/oidc; Max-Age={}; HttpOnly; SameSite=Lax{}",
+            STATE_COOKIE, state, max_age, secure
+        )
+    }
+
+    async fn discover(&self) -> Result<Arc<ProviderMetadata>> {
+        let cached = self
+            .metadata
+            .lock()
+            .unwrap()
+            .as_ref()
+            .filter(|(fetched_at, _metadata)| fetched_at.elapsed() < METADATA_LIFETIME)
+            .map(|(_fetched_at, metadata)| metadata.clone());
+        if let Some(metadata) = cached {
+            return Ok(metadata);
+        }
+
+        let url = format!(
+            "{}/.well-known/openid-configuration",
+            self.config.issuer.trim_end_matches('/')
+        );
+        let metadata: ProviderMetadata = fetch_json(self.http.get(&url)).await?;
+        if metadata.issuer != self.config.issuer {
+            return Err(invalid_response(format!(
+                "Discovered issuer {} doesn't match {}",
+                metadata.issuer, self.config.issuer
+            )));
+        }
+        let metadata = Arc::new(metadata);
+        *self.metadata.lock().unwrap() = Some((Instant::now(), metadata.clone()));
+        Ok(metadata)
+    }
+
+    pub async fn authorization_url(&self, login: &NewOidcLogin) -> Result<String> {
+        let metadata = self.discover().await?;
+        let mut url = Url::parse(&metadata.authorization_endpoint).map_err(invalid_response)?;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.config.client_id)
+            .append_pair("redirect_uri", &self.config.redirect_url)
+            .append_pair("scope", "openid email profile")
+            .append_pair("state", &login.state)
+            .append_pair("nonce", &login.nonce)
+            .append_pair("code_challenge", &code_challenge(&login.code_verifier))
+            .append_pair("code_challenge_method", "S256");
+        Ok(url.to_string())
+    }
+
+    pub async fn exchange_code(&self, code: &str, login: &OidcLogin) -> Result<IdTokenClaims> {
+        let metadata = self.discover().await?;
+        let mut request = self.http.post(&metadata.token_endpoint).form(&[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", &self.config.redirect_url),
+            ("client_id", &self.config.client_id),
+            ("code_verifier", &login.code_verifier),
+        ]);
+        if let Some(ref client_secret) = self.config.client_secret {
+            request = request.basic_auth(&self.config.client_id, Some(client_secret));
+        }
+        let tokens: TokenResponse = fetch_json(request).await?;
+
+        let header = decode_header(&tokens.id_token).map_err(invalid_response)?;
+        let key_set: JsonWebKeySet = fetch_json(self.http.get(&metadata.jwks_uri)).await?;
+        let key = key_set
+            .keys
+            .iter()
+            .filter(|key| key.kty == "RSA")
+            .find(|key| header.kid.is_none() || key.kid == header.kid);
+        let (modulus, exponent) = match key {
+            Some(JsonWebKey {
+                n: Some(modulus),
+                e: Some(exponent),
+                ..
+            }) => (modulus, exponent),
+            _ => return Err(invalid_response("No signing key matches the ID token")),
+        };
+
+        let mut validation = Validation::new(Algorithm::RS256);
+        validation.iss = Some(metadata.issuer.clone());
+        validation.set_audience(&[&self.config.client_id]);
+        let claims = decode::<IdTokenClaims>(
+            &tokens.id_token,
+            &DecodingKey::from_rsa_components(modulus, exponent),
+            &validation,
+        )
+        .map_err(invalid_response)?
+        .claims;
+
+        if claims.nonce.as_deref() != Some(login.nonce.as_str()) {
+            return Err(invalid_response("The ID token nonce doesn't match"));
+        }
+        if matches!(claims.azp, Some(ref azp) if azp != &self.config.client_id) {
+            return Err(invalid_response(
+                "The ID token was issued to another client",
+            ));
+        }
+        Ok(claims)
+    }
+
+    // Signs in with an identity that was linked before, or creates an account
+    // for it. Existing accounts are never linked here: their owner has to sign
+    // in and link the identity from the account security page.
+    async fn find_or_create_user(&self, pg_pool: &PgPool, claims: &IdTokenClaims) -> Result<i32> {
+        let issuer = &self.config.issuer;
+        if let Some(user_id) = UserIdentity::user_id(pg_pool, issuer, &claims.sub).await? {
+            return Ok(user_id);
+        }
+
+        let email = match claims.email {
+            Some(ref email) if claims.email_verified => email,
+            _ => {
+                return Err(Error::from(
+                    "The identity provider didn't share a verified email address.",
+                ))
+            }
+        };
+        if SimpleUser::find_by_email(pg_pool, email).await?.is_some() {
+            return Err(Error::from(format!(
+                "There is already an account for {}. Sign in with your password and link {} from the account security page.",
+                email, self.config.provider_name
+            )));
+        }
+        if !self.config.create_users {
+            return Err(Error::from(format!(
+                "There is no account for {}. Ask an administrator to add you.",
+                email
+            )));
+        }
+
+        let mut transaction = match pg_pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => {
+                println!("{}", error);
+                return Err(Error::from("Unable to create the account."));
+            }
+        };
+        let name = claims.name.as_deref().unwrap_or(email);
+        let new_user = NewUser::external(email, name, UserRole::Contributor)?;
+        let user_id = new_user.insert(&mut transaction).await?.id;
+        SimpleUser::mark_email_verified(&mut transaction, user_id).await?;
+        UserIdentity::link(&mut transaction, user_id, issuer, &claims.sub, Some(email)).await?;
+        match transaction.commit().await {
+            Ok(_) => Ok(user_id),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from("Unable to create the account."))
+            }
+        }
+    }
+
+    async fn link_user(
+        &self,
+        pg_pool: &PgPool,
+        user_id: i32,
+        claims: &IdTokenClaims,
+    ) -> Result<()> {
+        let issuer = &self.config.issuer;
+        match UserIdentity::user_id(pg_pool, issuer, &claims.sub).await? {
+            Some(linked_user_id) if linked_user_id == user_id => Ok(()),
+            Some(_linked_user_id) => Err(Error::from(format!(
+                "This {} account is already linked to another account.",
+                self.config.provider_name
+            ))),
+            None => {
+                UserIdentity::link(
+                    pg_pool,
+                    user_id,
+                    issuer,
+                    &claims.sub,
+                    claims.email.as_deref(),
+                )
+                .await
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct CallbackQuery {
+    code: Option<String>,
+    state: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+fn page(status: StatusCode, title: &str, body: &str) -> warp::reply::Response {
+    let html = format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>{}</title></head><body>{}</body></html>",
+        title, body
+    );
+    let reply = warp::reply::with_header(warp::reply::html(html), "cache-control", "no-store");
+    warp::reply::with_status(reply, status).into_response()
+}
+
+fn error_page(error: Error) -> warp::reply::Response {
+    let message = error
+        .message
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+    page(
+        StatusCode::BAD_REQUEST,
+        "Sign in failed",
+        &format!(
+            "<p>{}</p><p><a href=\"/sign/in\">Back to sign in</a></p>",
+            message
+        ),
+    )
+}
+
+pub async fn login(
+    client: Arc<OidcClient>,
+    pg_pool: PgPool,
+) -> std::result::Result<impl Reply, Rejection> {
+    let login = NewOidcLogin::make();
+    let location = match login.insert(&pg_pool).await {
+        Ok(()) => client.authorization_url(&login).await,
+        Err(error) => Err(error),
+    };
+    match location.map(|location| location.parse::<Uri>()) {
+        Ok(Ok(uri)) => Ok(warp::reply::with_header(
+            warp::redirect::temporary(uri),
+            "set-cookie",
+            client.state_cookie(&login.state, LIFETIME_MINUTES * 60),
+        )
+        .into_response()),
+        Ok(Err(error)) => Ok(error_page(invalid_response(error))),
+        Err(error) => Ok(error_page(error)),
+    }
+}
+
+enum Completion {
+    SignedIn(String),
+    Linked,
+}
+
+// Identities only belong to accounts created through the provider or linked
+// by their owner after a full sign in, so the provider is trusted with the
+// second factor and local two-factor authentication is not asked for here.
+async fn complete_login(
+    client: &OidcClient,
+    pg_pool: &PgPool,
+    client_info: &ClientInfo,
+    query: CallbackQuery,
+    state_cookie: Option<String>,
+) -> Result<Completion> {
+    if let Some(error) = query.error {
+        return Err(Error::from(format!(
+            "The identity provider refused the sign in: {}",
+            query.error_description.unwrap_or(error)
+        )));
+    }
+    let (code, state) = match (query.code, query.state) {
+        (Some(code), Some(state)) => (code, state),
+        _ => {
+            return Err(invalid_response(
+                "The callback is missing the code or state",
+            ))
+        }
+    };
+    if state_cookie.as_deref() != Some(state.as_str()) {
+        return Err(Error::from(
+            "The sign in was started in another browser. Try again.",
+        ));
+    }
+    let login = OidcLogin::consume(pg_pool, &state).await?;
+    let claims = client.exchange_code(&code, &login).await?;
+    if let Some(user_id) = login.user_id {
+        client.link_user(pg_pool, user_id, &claims).await?;
+        return Ok(Completion::Linked);
+    }
+    let user_id = client.find_or_create_user(pg_pool, &claims).await?;
+
+    let user_session = NewSession::make();
+    user_session
+        .insert(
+            pg_pool,
+            user_id,
+            client_info.user_agent.as_deref(),
+            client_info.ip_address.as_deref(),
+        )
+        .await?;
+    Ok(Completion::SignedIn(user_session.get_token()))
+}
+
+pub async fn callback(
+    query: CallbackQuery,
+    state_cookie: Option<String>,
+    client: Arc<OidcClient>,
+    pg_pool: PgPool,
+    client_info: ClientInfo,
+) -> std::result::Result<impl Reply, Rejection> {
+    let response = match complete_login(&client, &pg_pool, &client_info, query, state_cookie).await
+    {
+        // The app keeps its token in local storage, so hand it over from a page on
+        // the same origin instead of putting it in a URL.
+        Ok(Completion::SignedIn(token)) => page(
+            StatusCode::OK,
+            "Signing in",
+            &format!(
+                "<script>localStorage.setItem(\"auth_token\", {});location.replace(\"/\");</script>",
+                serde_json::to_string(&token).unwrap_or_default()
+            ),
+        ),
+        Ok(Completion::Linked) => {
+            warp::redirect::temporary(Uri::from_static("/account/security")).into_response()
+        }
+        Err(error) => error_page(error),
+    };
+    Ok(warp::reply::with_header(
+        response,
+        "set-cookie",
+        client.state_cookie("", 0),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestDatabase;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use serde_json::json;
+    use warp::Filter;
+
+    const PRIVATE_KEY: &str = include_str!("../examples/mock_oidc_issuer.pem");
+    const MODULUS: &str = "voyhZi4V-yKEW22JcZDSdYkU34LbGTdnjEMHXLxHwuNVoyhhI5fFhXA9xJgSXtuQ2BxGpsNqdmdhh3r7s1KEKlpSCkJ3vbNEtvrZe_YhuLNPzBfLmElt_PZmodMhAfhXrMx9uEBxHk41rwwt4Gef8sJlBiOdUyDBisOKZ-Ltfw84cXQ76oGpJR6FVucxzFLVwTlXOyOImZjarLmSg6T4uJYvlRLELeprOeZziKwDSBnVQX8zQJHGfVbOj4hbgsD7ZTUOK19uNY4d1AgB3ZwA5niMVRMzFEZqOjIlEVbdbqezFAbp9leQOxHfpfSD03Ck86U0kQe_CD2EWQ3J1PwuHQ";
+
+    // What the stub issuer puts in the next ID token.
+    #[derive(Default)]
+    struct Issued {
+        issuer: String,
+        nonce: String,
+    }
+
+    // Serves discovery, token and JWKS endpoints on a free port. The token
+    // endpoint signs whatever nonce the test left in `Issued`.
+    async fn stub_issuer() -> (OidcClient, Arc<Mutex<Issued>>) {
+        let issued: Arc<Mutex<Issued>> = Arc::default();
+        let with_issued = {
+            let issued = issued.clone();
+            warp::any().map(move || issued.clone())
+        };
+        let discovery = warp::path!(".well-known" / "openid-configuration")
+            .and(with_issued.clone())
+            .map(|issued: Arc<Mutex<Issued>>| {
+                let issuer = issued.lock().unwrap().issuer.clone();
+                warp::reply::json(&json!({
+                    "issuer": issuer,
+                    "authorization_endpoint": format!("{}/authorize", issuer),
+                    "token_endpoint": format!("{}/token", issuer),
+                    "jwks_uri": format!("{}/jwks", issuer),
+                }))
+            });
+        let token = warp::path!("token").and(warp::post()).and(with_issued).map(
+            |issued: Arc<Mutex<Issued>>| {
+                let issued = issued.lock().unwrap();
+                let now = chrono::Utc::now().timestamp();
+                let claims = json!({
+                    "iss": issued.issuer,
+                    "sub": "stub-user",
+                    "aud": "blog",
+                    "exp": now + 300,
+                    "iat": now,
+                    "nonce": issued.nonce,
+                    "email": "jane@example.com",
+                    "email_verified": true,
+                    "name": "Jane",
+                });
+                let mut header = Header::new(Algorithm::RS256);
+                header.kid = Some(String::from("stub"));
+                let key = EncodingKey::from_rsa_pem(PRIVATE_KEY.as_bytes()).unwrap();
+                let id_token = encode(&header, &claims, &key).unwrap();
+                warp::reply::json(&json!({ "id_token": id_token }))
+            },
+        );
+        let jwks = warp::path!("jwks").map(|| {
+            warp::reply::json(&json!({
+                "keys": [{ "kty": "RSA", "kid": "stub", "n": MODULUS, "e": "AQAB" }]
+            }))
+        });
+        let (address, server) =
+            warp::serve(discovery.or(token).or(jwks)).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+
+        let issuer = format!("http://{}", address);
+        issued.lock().unwrap().issuer = issuer.clone();
+        let client = OidcClient::new(OidcConfig {
+            issuer,
+            client_id: String::from("blog"),
+            client_secret: None,
+            redirect_url: String::from("http://localhost:3030/auth/oidc/callback"),
+            provider_name: String::from("Stub"),
+            create_users: true,
+        });
+        (client, issued)
+    }
+
+    fn header(response: &warp::reply::Response, name: &str) -> String {
+        response.headers()[name].to_str().unwrap().to_string()
+    }
+
+    // Runs the login route and returns the state and nonce sent to the issuer.
+    async fn start_login(client: &Arc<OidcClient>, pg_pool: &PgPool) -> (String, String) {
+        let response = login(client.clone(), pg_pool.clone())
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(response.status(), StatusCode::TEMPORARY_REDIRECT);
+        let location = Url::parse(&header(&response, "location")).unwrap();
+        let parameter = |name: &str| {
+            location
+                .query_pairs()
+                .find(|(key, _value)| key == name)
+                .map(|(_key, value)| value.to_string())
+                .unwrap()
+        };
+        let (state, nonce) = (parameter("state"), parameter("nonce"));
+        let cookie = header(&response, "set-cookie");
+        assert!(cookie.starts_with(&format!("{}={};", STATE_COOKIE, state)));
+        assert!(cookie.contains("HttpOnly") && cookie.contains("SameSite=Lax"));
+        (state, nonce)
+    }
+
+    async fn finish_login(
+        client: &Arc<OidcClient>,
+        pg_pool: &PgPool,
+        state: &str,
+        state_cookie: Option<&str>,
+    ) -> (StatusCode, String) {
+        let query = CallbackQuery {
+            code: Some(String::from("code")),
+            state: Some(state.to_string()),
+            error: None,
+            error_description: None,
+        };
+        let client_info = ClientInfo {
+            user_agent: Some(String::from("test")),
+            ip_address: Some(String::from("127.0.0.1")),
+        };
+        let response = callback(
+            query,
+            state_cookie.map(String::from),
+            client.clone(),
+            pg_pool.clone(),
+            client_info,
+        )
+        .await
+        .unwrap()
+        .into_response();
+        let status = response.status();
+        let body = warp::hyper::body::to_bytes(response.into_body())
+            .await
+            .unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    async fn identities(pg_pool: &PgPool) -> Vec<i32> {
+        let rows: Vec<(i32,)> = sqlx::query_as("SELECT user_id FROM user_identities")
+            .fetch_all(pg_pool)
+            .await
+            .unwrap();
+        rows.into_iter().map(|(user_id,)| user_id).collect()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL, run with --ignored"]
+    async fn signs_in_once_per_login() {
+        let database = TestDatabase::new().await;
+        let (client, issued) = stub_issuer().await;
+        let client = Arc::new(client);
+
+        let (state, nonce) = start_login(&client, &database.pg_pool).await;
+        issued.lock().unwrap().nonce = nonce;
+        let (status, body) = finish_login(&client, &database.pg_pool, &state, Some(&state)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains("auth_token"));
+        assert_eq!(identities(&database.pg_pool).await.len(), 1);
+
+        let (status, body) = finish_login(&client, &database.pg_pool, &state, Some(&state)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.contains("The sign in has expired."));
+
+        database.drop().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL, run with --ignored"]
+    async fn rejects_a_state_from_another_browser() {
+        let database = TestDatabase::new().await;
+        let (client, issued) = stub_issuer().await;
+        let client = Arc::new(client);
+
+        let (state, nonce) = start_login(&client, &database.pg_pool).await;
+        issued.lock().unwrap().nonce = nonce;
+        let (other_state, _other_nonce) = start_login(&client, &database.pg_pool).await;
+        for state_cookie in [None, Some(other_state.as_str())].iter() {
+            let (status, body) =
+                finish_login(&client, &database.pg_pool, &state, *state_cookie).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert!(body.contains("started in another browser"));
+        }
+        assert!(identities(&database.pg_pool).await.is_empty());
+
+        database.drop().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL, run with --ignored"]
+    async fn rejects_an_id_token_with_another_nonce() {
+        let database = TestDatabase::new().await;
+        let (client, issued) = stub_issuer().await;
+        let client = Arc::new(client);
+
+        let (state, _nonce) = start_login(&client, &database.pg_pool).await;
+        issued.lock().unwrap().nonce = String::from("replayed");
+        let (status, body) = finish_login(&client, &database.pg_pool, &state, Some(&state)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.contains("invalid response"));
+        assert!(identities(&database.pg_pool).await.is_empty());
+
+        database.drop().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL, run with --ignored"]
+    async fn links_an_existing_account_only_for_its_owner() {
+        let database = TestDatabase::new().await;
+        let user_id = database.insert_user("jane@example.com", "Jane").await;
+        let (client, issued) = stub_issuer().await;
+        let client = Arc::new(client);
+
+        let (state, nonce) = start_login(&client, &database.pg_pool).await;
+        issued.lock().unwrap().nonce = nonce;
+        let (status, body) = finish_login(&client, &database.pg_pool, &state, Some(&state)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.contains("There is already an account"));
+        assert!(identities(&database.pg_pool).await.is_empty());
+
+        let link = NewOidcLogin::for_user(user_id);
+        link.insert(&database.pg_pool).await.unwrap();
+        issued.lock().unwrap().nonce = link.nonce.clone();
+        let (status, _body) =
+            finish_login(&client, &database.pg_pool, &link.state, Some(&link.state)).await;
+        assert_eq!(status, StatusCode::TEMPORARY_REDIRECT);
+        assert_eq!(identities(&database.pg_pool).await, vec![user_id]);
+
+        let (state, nonce) = start_login(&client, &database.pg_pool).await;
+        issued.lock().unwrap().nonce = nonce;
+        let (status, body) = finish_login(&client, &database.pg_pool, &state, Some(&state)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.contains("auth_token"));
+
+        database.drop().await;
+    }
+}
@@ -0,0 +1,51 @@
+use async_graphql::{Error, Result};
+use sqlx::{PgPool, Postgres, Transaction};
+
+pub struct Installation;
+
+impl Installation {
+    pub async fn is_completed(pg_pool: &PgPool) -> Result<bool> {
+        match sqlx::query!(
+            r#"
+            SELECT EXISTS (SELECT 1 FROM installation) AS "completed!"
+            "#
+        )
+        .fetch_one(pg_pool)
+        .await
+        {
+            Ok(row) => Ok(row.completed),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while retrieving the installation state from the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn complete(transaction: &mut Transaction<'_, Postgres>) -> Result<()> {
+        match sqlx::query!(
+            r#"
+            INSERT INTO installation
+                (id)
+            VALUES
+                (true)
+            ON CONFLICT DO NOTHING
+            "#
+        )
+        .execute(&mut *transaction)
+        .await
+        {
+            Ok(done) if done.rows_affected() == 0 => {
+                Err(Error::from("The blog has already been installed."))
+            }
+            Ok(_done) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while completing the installation in the database.",
+                ))
+            }
+        }
+    }
+}
@@ -1,4 +1,7 @@
 pub mod authorities;
+pub mod installation;
+pub mod oidc_login;
 pub mod posts;
 pub mod settings;
+pub mod sign_in_throttle;
 pub mod users;
@@ -0,0 +1,115 @@
+use crate::records::users::session::{generate_token, hash_token};
+use async_graphql::{Error, Result};
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+
+pub const LIFETIME_MINUTES: i64 = 10;
+
+// What we need to remember between sending the browser to the issuer and it
+// coming back to the callback.
+pub struct NewOidcLogin {
+    pub state: String,
+    pub nonce: String,
+    pub code_verifier: String,
+    pub expires_at: DateTime<Utc>,
+    // Set when a signed in user links an identity to their account.
+    pub user_id: Option<i32>,
+}
+
+impl NewOidcLogin {
+    pub fn make() -> Self {
+        Self {
+            state: generate_token(),
+            nonce: generate_token(),
+            code_verifier: generate_token(),
+            expires_at: Utc::now() + Duration::minutes(LIFETIME_MINUTES),
+            user_id: None,
+        }
+    }
+
+    pub fn for_user(user_id: i32) -> Self {
+        Self {
+            user_id: Some(user_id),
+            ..Self::make()
+        }
+    }
+
+    pub async fn insert(&self, pg_pool: &PgPool) -> Result<()> {
+        // Logins that were never completed are cleaned up by the next one.
+        if let Err(error) = sqlx::query!(
+            r#"
+            DELETE FROM
+                oidc_logins
+            WHERE
+                expires_at <= now()
+            "#
+        )
+        .execute(pg_pool)
+        .await
+        {
+            println!("{}", error);
+            return Err(Error::from("Unable to delete the expired logins."));
+        }
+        match sqlx::query!(
+            r#"
+            INSERT INTO oidc_logins
+                (state_hash, nonce, code_verifier, expires_at, user_id)
+            VALUES
+                ($1, $2, $3, $4, $5)
+            "#,
+            hash_token(&self.state),
+            self.nonce,
+            self.code_verifier,
+            self.expires_at,
+            self.user_id
+        )
+        .execute(pg_pool)
+        .await
+        {
+            Ok(_done) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from("Unable to insert the login in database."))
+            }
+        }
+    }
+}
+
+pub struct OidcLogin {
+    pub nonce: String,
+    pub code_verifier: String,
+    pub user_id: Option<i32>,
+}
+
+impl OidcLogin {
+    pub async fn consume(pg_pool: &PgPool, state: &str) -> Result<Self> {
+        match sqlx::query_as!(
+            Self,
+            r#"
+            DELETE FROM
+                oidc_logins
+            WHERE
+                state_hash = $1
+            AND
+                expires_at > now()
+            RETURNING
+                nonce,
+                code_verifier,
+                user_id
+            "#,
+            hash_token(state)
+        )
+        .fetch_optional(pg_pool)
+        .await
+        {
+            Ok(Some(login)) => Ok(login),
+            Ok(None) => Err(Error::from("The sign in has expired. Try again.")),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while retrieving the login from the database.",
+                ))
+            }
+        }
+    }
+}
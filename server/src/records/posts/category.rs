@@ -0,0 +1,502 @@
+use super::tag::Label;
+use crate::errors::field_error;
+use async_graphql::{Error, Result, SimpleObject};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+
+#[derive(sqlx::FromRow, SimpleObject, Debug, Deserialize, Serialize, Clone)]
+pub struct Category {
+    pub id: i32,
+    // Empty for top level categories.
+    pub parent_id: Option<i32>,
+    pub slug: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    // Published posts filed directly under this category.
+    pub post_count: i64,
+}
+
+impl Category {
+    pub async fn all(pg_pool: &PgPool) -> Result<Vec<Self>> {
+        match sqlx::query_as!(
+            Self,
+            r#"
+            SELECT
+                id,
+                parent_id,
+                slug,
+                name,
+                created_at,
+                category_post_count(id) AS "post_count!"
+            FROM
+                categories
+            ORDER BY
+                name
+            "#
+        )
+        .fetch_all(pg_pool)
+        .await
+        {
+            Ok(categories) => Ok(categories),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while retrieving the categories from the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn get(pg_pool: &PgPool, category_id: i32) -> Result<Option<Self>> {
+        match sqlx::query_as!(
+            Self,
+            r#"
+            SELECT
+                id,
+                parent_id,
+                slug,
+                name,
+                created_at,
+                category_post_count(id) AS "post_count!"
+            FROM
+                categories
+            WHERE
+                id = $1
+            "#,
+            category_id
+        )
+        .fetch_optional(pg_pool)
+        .await
+        {
+            Ok(category) => Ok(category),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while retrieving the category from the database.",
+                ))
+            }
+        }
+    }
+
+    // The category of a post and everything above it, starting at the top.
+    pub async fn breadcrumbs(pg_pool: &PgPool, post_id: i32) -> Result<Vec<Self>> {
+        match sqlx::query_as!(
+            Self,
+            r#"
+            WITH RECURSIVE path AS (
+                SELECT
+                    categories.*,
+                    0 AS depth,
+                    ARRAY[categories.id] AS visited
+                FROM
+                    categories
+                INNER JOIN
+                    posts
+                ON
+                    posts.category_id = categories.id
+                WHERE
+                    posts.id = $1
+                UNION ALL
+                SELECT
+                    categories.*,
+                    path.depth + 1,
+                    path.visited || categories.id
+                FROM
+                    categories
+                INNER JOIN
+                    path
+                ON
+                    categories.id = path.parent_id
+                -- Stops at a category seen before, should the tree ever loop.
+                WHERE
+                    categories.id <> ALL(path.visited)
+            )
+            SELECT
+                id AS "id!",
+                parent_id,
+                slug AS "slug!",
+                name AS "name!",
+                created_at AS "created_at!",
+                category_post_count(id) AS "post_count!"
+            FROM
+                path
+            ORDER BY
+                depth DESC
+            "#,
+            post_id
+        )
+        .fetch_all(pg_pool)
+        .await
+        {
+            Ok(categories) => Ok(categories),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while retrieving the post categories from the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn insert(pg_pool: &PgPool, label: &Label, parent_id: Option<i32>) -> Result<Self> {
+        match sqlx::query_as!(
+            Self,
+            r#"
+            INSERT INTO categories
+                (parent_id, slug, name)
+            VALUES
+                ($1, $2, $3)
+            RETURNING
+                id,
+                parent_id,
+                slug,
+                name,
+                created_at,
+                category_post_count(id) AS "post_count!"
+            "#,
+            parent_id,
+            label.slug,
+            label.name
+        )
+        .fetch_one(pg_pool)
+        .await
+        {
+            Ok(category) => Ok(category),
+            Err(sqlx::Error::Database(error)) if error.code().as_deref() == Some("23505") => Err(
+                field_error("slug", "A category with this slug already exists."),
+            ),
+            Err(sqlx::Error::Database(error)) if error.code().as_deref() == Some("23503") => Err(
+                field_error("parentId", "The parent category doesn't exist."),
+            ),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from("Unable to insert category in database."))
+            }
+        }
+    }
+
+    // A category can't be moved under itself or one of its subcategories, as
+    // that would cut the branch off from the top. Callers check that the
+    // category exists first, so no row here means the move was refused.
+    pub async fn update(
+        pg_pool: &PgPool,
+        category_id: i32,
+        label: &Label,
+        parent_id: Option<i32>,
+    ) -> Result<Self> {
+        let mut transaction = match pg_pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => {
+                println!("{}", error);
+                return Err(Error::from(
+                    "An error occured while updating the category in the database.",
+                ));
+            }
+        };
+        if let Some(parent_id) = parent_id {
+            Self::lock_ancestors(&mut transaction, category_id, parent_id).await?;
+        }
+
+        let category = match sqlx::query_as!(
+            Self,
+            r#"
+            UPDATE
+                categories
+            SET
+                parent_id = $2,
+                slug = $3,
+                name = $4
+            WHERE
+                id = $1
+            RETURNING
+                id,
+                parent_id,
+                slug,
+                name,
+                created_at,
+                category_post_count(id) AS "post_count!"
+            "#,
+            category_id,
+            parent_id,
+            label.slug,
+            label.name
+        )
+        .fetch_optional(&mut transaction)
+        .await
+        {
+            Ok(Some(category)) => category,
+            Ok(None) => return Err(Error::from("The category doesn't exist.")),
+            Err(sqlx::Error::Database(error)) if error.code().as_deref() == Some("23505") => {
+                return Err(field_error(
+                    "slug",
+                    "A category with this slug already exists.",
+                ))
+            }
+            Err(sqlx::Error::Database(error)) if error.code().as_deref() == Some("23503") => {
+                return Err(field_error(
+                    "parentId",
+                    "The parent category doesn't exist.",
+                ))
+            }
+            Err(error) => {
+                println!("{}", error);
+                return Err(Error::from(
+                    "An error occured while updating the category in the database.",
+                ));
+            }
+        };
+
+        match transaction.commit().await {
+            Ok(()) => Ok(category),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while updating the category in the database.",
+                ))
+            }
+        }
+    }
+
+    // Locks the category and every ancestor of its new parent until the move
+    // is saved, so that two moves at once can't close a loop between them.
+    async fn lock_ancestors(
+        transaction: &mut Transaction<'_, Postgres>,
+        category_id: i32,
+        parent_id: i32,
+    ) -> Result<()> {
+        if Self::lock(transaction, category_id).await?.is_none() {
+            return Err(Error::from("The category doesn't exist."));
+        }
+        let mut visited = vec![];
+        let mut ancestor = Some(parent_id);
+        while let Some(id) = ancestor {
+            if id == category_id {
+                return Err(field_error(
+                    "parentId",
+                    "A category can't be moved under itself or one of its subcategories.",
+                ));
+            }
+            // Stops at a category seen before, should the tree already loop.
+            if visited.contains(&id) {
+                break;
+            }
+            visited.push(id);
+            ancestor = match Self::lock(transaction, id).await? {
+                Some(parent_id) => parent_id,
+                None => {
+                    return Err(field_error(
+                        "parentId",
+                        "The parent category doesn't exist.",
+                    ))
+                }
+            };
+        }
+        Ok(())
+    }
+
+    // Returns the parent of a category that exists, locked until the end of
+    // the transaction.
+    async fn lock(
+        transaction: &mut Transaction<'_, Postgres>,
+        category_id: i32,
+    ) -> Result<Option<Option<i32>>> {
+        match sqlx::query!(
+            r#"
+            SELECT
+                parent_id
+            FROM
+                categories
+            WHERE
+                id = $1
+            FOR UPDATE
+            "#,
+            category_id
+        )
+        .fetch_optional(transaction)
+        .await
+        {
+            Ok(category) => Ok(category.map(|category| category.parent_id)),
+            Err(sqlx::Error::Database(error)) if error.code().as_deref() == Some("40P01") => Err(
+                Error::from("Another change to the categories got in the way. Try again."),
+            ),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while retrieving the category from the database.",
+                ))
+            }
+        }
+    }
+
+    // Subcategories move up to the parent of the deleted category and its
+    // posts are left without one.
+    pub async fn delete(pg_pool: &PgPool, category_id: i32) -> Result<()> {
+        let mut transaction = match pg_pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => {
+                println!("{}", error);
+                return Err(Error::from(
+                    "An error occured while deleting the category from the database.",
+                ));
+            }
+        };
+
+        match sqlx::query!(
+            r#"
+            UPDATE
+                categories
+            SET
+                parent_id = deleted.parent_id
+            FROM
+                categories AS deleted
+            WHERE
+                deleted.id = $1
+            AND
+                categories.parent_id = deleted.id
+            "#,
+            category_id
+        )
+        .execute(&mut transaction)
+        .await
+        {
+            Ok(_done) => {}
+            Err(error) => {
+                println!("{}", error);
+                return Err(Error::from(
+                    "An error occured while deleting the category from the database.",
+                ));
+            }
+        }
+
+        match sqlx::query!(
+            r#"
+            DELETE FROM
+                categories
+            WHERE
+                id = $1
+            "#,
+            category_id
+        )
+        .execute(&mut transaction)
+        .await
+        {
+            Ok(done) if done.rows_affected() == 0 => {
+                return Err(Error::from("The category doesn't exist."))
+            }
+            Ok(_done) => {}
+            Err(error) => {
+                println!("{}", error);
+                return Err(Error::from(
+                    "An error occured while deleting the category from the database.",
+                ));
+            }
+        }
+
+        match transaction.commit().await {
+            Ok(()) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while deleting the category from the database.",
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Category;
+    use crate::records::posts::tag::Label;
+    use crate::test_support::TestDatabase;
+    use sqlx::PgPool;
+
+    async fn insert(pg_pool: &PgPool, name: &str, parent_id: Option<i32>) -> i32 {
+        let label = Label::new(name, None).unwrap();
+        Category::insert(pg_pool, &label, parent_id)
+            .await
+            .unwrap()
+            .id
+    }
+
+    async fn parent_id(pg_pool: &PgPool, category_id: i32) -> Option<i32> {
+        Category::get(pg_pool, category_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .parent_id
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL, run with --ignored"]
+    async fn refuses_to_move_a_category_under_itself() {
+        let database = TestDatabase::new().await;
+        let pg_pool = &database.pg_pool;
+        let news = insert(pg_pool, "News", None).await;
+        let local = insert(pg_pool, "Local", Some(news)).await;
+        let label = Label::new("News", None).unwrap();
+
+        for parent_id in [news, local].iter() {
+            let error = Category::update(pg_pool, news, &label, Some(*parent_id))
+                .await
+                .unwrap_err();
+            assert!(error.message.contains("can't be moved under itself"));
+        }
+        assert_eq!(parent_id(pg_pool, news).await, None);
+
+        database.drop().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL, run with --ignored"]
+    async fn lets_only_one_of_two_crossing_moves_through() {
+        let database = TestDatabase::new().await;
+        let pg_pool = &database.pg_pool;
+        let news = insert(pg_pool, "News", None).await;
+        let sport = insert(pg_pool, "Sport", None).await;
+        let news_label = Label::new("News", None).unwrap();
+        let sport_label = Label::new("Sport", None).unwrap();
+
+        let (news_moved, sport_moved) = tokio::join!(
+            Category::update(pg_pool, news, &news_label, Some(sport)),
+            Category::update(pg_pool, sport, &sport_label, Some(news)),
+        );
+        assert!(news_moved.is_ok() != sport_moved.is_ok());
+        let parents = (
+            parent_id(pg_pool, news).await,
+            parent_id(pg_pool, sport).await,
+        );
+        assert!(parents == (Some(sport), None) || parents == (None, Some(news)));
+
+        database.drop().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL, run with --ignored"]
+    async fn stops_the_breadcrumbs_at_a_loop() {
+        let database = TestDatabase::new().await;
+        let pg_pool = &database.pg_pool;
+        let news = insert(pg_pool, "News", None).await;
+        let sport = insert(pg_pool, "Sport", Some(news)).await;
+        sqlx::query("UPDATE categories SET parent_id = $1 WHERE id = $2")
+            .bind(sport)
+            .bind(news)
+            .execute(pg_pool)
+            .await
+            .unwrap();
+        let (post_id,): (i32,) = sqlx::query_as(
+            "INSERT INTO posts (slug, title, text, summary, category_id) VALUES ('hello', 'Hello', 'Hi', 'Hi', $1) RETURNING id",
+        )
+        .bind(sport)
+        .fetch_one(pg_pool)
+        .await
+        .unwrap();
+
+        let breadcrumbs = Category::breadcrumbs(pg_pool, post_id).await.unwrap();
+        let ids: Vec<i32> = breadcrumbs.iter().map(|category| category.id).collect();
+        assert_eq!(ids, vec![news, sport]);
+
+        database.drop().await;
+    }
+}
@@ -0,0 +1,1065 @@
+use crate::errors::{field_error, ApiError, FieldErrors};
+use crate::records::users::{
+    author::{is_web_url, Author},
+    SimpleUser,
+};
+use async_graphql::{
+    Enum, Error, ErrorExtensions, InputObject, MaybeUndefined, Result, SimpleObject,
+};
+use chrono::{DateTime, Utc};
+use revision::PostRevision;
+use serde::{Deserialize, Serialize};
+use slug_history::PostSlugHistory;
+use sqlx::{Executor, PgPool, Postgres};
+use tag::{slugify, Tag};
+
+pub mod category;
+pub mod revision;
+pub mod slug_history;
+pub mod tag;
+
+#[derive(sqlx::Type, Enum, Copy, Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[sqlx(type_name = "post_status", rename_all = "lowercase")]
+pub enum PostStatus {
+    Draft,
+    Pending,
+    Published,
+    Scheduled,
+    Archived,
+}
+
+impl PostStatus {
+    pub fn published_at(&self, requested: Option<DateTime<Utc>>) -> Result<Option<DateTime<Utc>>> {
+        match self {
+            PostStatus::Draft | PostStatus::Pending => Ok(None),
+            PostStatus::Published => Ok(Some(requested.unwrap_or_else(Utc::now))),
+            PostStatus::Scheduled => match requested {
+                Some(publish_at) if publish_at > Utc::now() => Ok(Some(publish_at)),
+                Some(_publish_at) => Err(Error::from(
+                    "A scheduled post needs a publish time in the future.",
+                )),
+                None => Err(Error::from("A scheduled post needs a publish time.")),
+            },
+            PostStatus::Archived => Ok(requested),
+        }
+    }
+
+    // The publish date after an update from `previous`. A date that was sent
+    // wins, otherwise the post keeps its date unless the status changes.
+    pub fn updated_published_at(
+        &self,
+        previous: PostStatus,
+        previous_published_at: Option<DateTime<Utc>>,
+        requested: Option<DateTime<Utc>>,
+    ) -> Result<Option<DateTime<Utc>>> {
+        match requested {
+            None if *self == previous => Ok(previous_published_at),
+            None if *self == PostStatus::Archived => Ok(previous_published_at),
+            requested => self.published_at(requested),
+        }
+    }
+}
+
+#[derive(sqlx::FromRow, SimpleObject, Debug, Deserialize, Serialize, Clone)]
+pub struct Post {
+    pub id: i32,
+    pub slug: String,
+    pub title: String,
+    pub text: String,
+    pub summary: String,
+    pub status: PostStatus,
+    pub published_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    // Goes up with every change, so editors can tell when they are out of date.
+    pub version: i32,
+    // Set while the post is in the trash.
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub cover_image_url: Option<String>,
+    // Tag names, in alphabetical order.
+    pub tags: Vec<String>,
+    pub category_id: Option<i32>,
+    // Shown to search engines and link previews instead of the title and summary.
+    pub seo_title: Option<String>,
+    pub seo_description: Option<String>,
+    #[graphql(skip)]
+    pub created_by: Option<i32>,
+    // Empty once the author has deleted their account.
+    pub author: Option<Author>,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Viewer {
+    pub user_id: Option<i32>,
+    pub unpublished: bool,
+}
+
+impl Viewer {
+    pub fn anonymous() -> Self {
+        Self::default()
+    }
+
+    pub fn everything() -> Self {
+        Self {
+            user_id: None,
+            unpublished: true,
+        }
+    }
+
+    pub fn from_user(user: Option<&SimpleUser>) -> Self {
+        match user {
+            Some(user) => Self {
+                user_id: Some(user.id),
+                unpublished: user.role.can_edit_others_posts(),
+            },
+            None => Self::anonymous(),
+        }
+    }
+}
+
+impl Post {
+    pub async fn all(
+        pg_pool: &PgPool,
+        viewer: Viewer,
+        author_id: Option<i32>,
+        tag: Option<&str>,
+    ) -> Result<Vec<Self>> {
+        match sqlx::query_as!(
+            Self,
+            r#"
+            SELECT
+                posts.id,
+                posts.slug,
+                posts.title,
+                posts.text,
+                posts.summary,
+                posts.status AS "status: PostStatus",
+                posts.published_at,
+                posts.created_at,
+                posts.version,
+                posts.deleted_at,
+                posts.cover_image_url,
+                tags_of(posts.id) AS "tags!",
+                posts.category_id,
+                posts.seo_title,
+                posts.seo_description,
+                posts.created_by,
+                author_of(posts.created_by) AS "author: Author"
+            FROM
+                posts
+            WHERE
+                posts.deleted_at IS NULL
+            AND (
+                posts.status = 'published'
+                OR posts.created_by = $1
+                OR $2
+            )
+            AND
+                ($3::integer IS NULL OR posts.created_by = $3)
+            AND (
+                $4::text IS NULL
+                OR EXISTS (
+                    SELECT
+                        1
+                    FROM
+                        post_tags
+                    INNER JOIN
+                        tags
+                    ON
+                        tags.id = post_tags.tag_id
+                    WHERE
+                        post_tags.post_id = posts.id
+                    AND
+                        tags.slug = $4
+                )
+            )
+            ORDER BY
+                COALESCE(posts.published_at, posts.created_at) DESC
+            "#,
+            viewer.user_id,
+            viewer.unpublished,
+            author_id,
+            tag
+        )
+        .fetch_all(pg_pool)
+        .await
+        {
+            Ok(posts) => Ok(posts),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while retrieving the posts from the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn get(pg_pool: &PgPool, post_id: i32, viewer: Viewer) -> Result<Option<Self>> {
+        match sqlx::query_as!(
+            Self,
+            r#"
+            SELECT
+                posts.id,
+                posts.slug,
+                posts.title,
+                posts.text,
+                posts.summary,
+                posts.status AS "status: PostStatus",
+                posts.published_at,
+                posts.created_at,
+                posts.version,
+                posts.deleted_at,
+                posts.cover_image_url,
+                tags_of(posts.id) AS "tags!",
+                posts.category_id,
+                posts.seo_title,
+                posts.seo_description,
+                posts.created_by,
+                author_of(posts.created_by) AS "author: Author"
+            FROM
+                posts
+            WHERE
+                posts.id = $1
+            AND
+                posts.deleted_at IS NULL
+            AND (
+                posts.status = 'published'
+                OR posts.created_by = $2
+                OR $3
+            )
+            "#,
+            post_id,
+            viewer.user_id,
+            viewer.unpublished
+        )
+        .fetch_optional(pg_pool)
+        .await
+        {
+            Ok(post) => match post {
+                Some(exists) => Ok(Some(exists)),
+                None => Ok(None),
+            },
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while retrieving the posts from the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn by_slug(pg_pool: &PgPool, slug: &str, viewer: Viewer) -> Result<Option<Self>> {
+        match sqlx::query_as!(
+            Self,
+            r#"
+            SELECT
+                posts.id,
+                posts.slug,
+                posts.title,
+                posts.text,
+                posts.summary,
+                posts.status AS "status: PostStatus",
+                posts.published_at,
+                posts.created_at,
+                posts.version,
+                posts.deleted_at,
+                posts.cover_image_url,
+                tags_of(posts.id) AS "tags!",
+                posts.category_id,
+                posts.seo_title,
+                posts.seo_description,
+                posts.created_by,
+                author_of(posts.created_by) AS "author: Author"
+            FROM
+                posts
+            WHERE
+                posts.slug = $1
+            AND
+                posts.deleted_at IS NULL
+            AND (
+                posts.status = 'published'
+                OR posts.created_by = $2
+                OR $3
+            )
+            "#,
+            slug,
+            viewer.user_id,
+            viewer.unpublished
+        )
+        .fetch_optional(pg_pool)
+        .await
+        {
+            Ok(Some(post)) => Ok(Some(post)),
+            Ok(None) => PostSlugHistory::post(pg_pool, slug, viewer).await,
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while retrieving the post from the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn publish_due(pg_pool: &PgPool, now: DateTime<Utc>) -> Result<Vec<Self>> {
+        match sqlx::query_as!(
+            Self,
+            r#"
+            UPDATE
+                posts
+            SET
+                status = 'published',
+                version = version + 1
+            WHERE
+                posts.id IN (
+                    SELECT
+                        due.id
+                    FROM
+                        posts AS due
+                    WHERE
+                        due.status = 'scheduled'
+                    AND
+                        due.deleted_at IS NULL
+                    AND
+                        due.published_at <= $1
+                    FOR UPDATE SKIP LOCKED
+                )
+            RETURNING
+                id,
+                slug,
+                title,
+                text,
+                summary,
+                status AS "status: PostStatus",
+                published_at,
+                created_at,
+                version,
+                deleted_at,
+                cover_image_url,
+                tags_of(id) AS "tags!",
+                category_id,
+                seo_title,
+                seo_description,
+                created_by,
+                author_of(created_by) AS "author: Author"
+            "#,
+            now
+        )
+        .fetch_all(pg_pool)
+        .await
+        {
+            Ok(posts) => Ok(posts),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while publishing the scheduled posts in the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn trash(pg_pool: &PgPool, viewer: Viewer) -> Result<Vec<Self>> {
+        match sqlx::query_as!(
+            Self,
+            r#"
+            SELECT
+                posts.id,
+                posts.slug,
+                posts.title,
+                posts.text,
+                posts.summary,
+                posts.status AS "status: PostStatus",
+                posts.published_at,
+                posts.created_at,
+                posts.version,
+                posts.deleted_at,
+                posts.cover_image_url,
+                tags_of(posts.id) AS "tags!",
+                posts.category_id,
+                posts.seo_title,
+                posts.seo_description,
+                posts.created_by,
+                author_of(posts.created_by) AS "author: Author"
+            FROM
+                posts
+            WHERE
+                posts.deleted_at IS NOT NULL
+            AND (
+                posts.created_by = $1
+                OR $2
+            )
+            ORDER BY
+                posts.deleted_at DESC
+            "#,
+            viewer.user_id,
+            viewer.unpublished
+        )
+        .fetch_all(pg_pool)
+        .await
+        {
+            Ok(posts) => Ok(posts),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while retrieving the trash from the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn trashed(pg_pool: &PgPool, post_id: i32) -> Result<Option<Self>> {
+        match sqlx::query_as!(
+            Self,
+            r#"
+            SELECT
+                posts.id,
+                posts.slug,
+                posts.title,
+                posts.text,
+                posts.summary,
+                posts.status AS "status: PostStatus",
+                posts.published_at,
+                posts.created_at,
+                posts.version,
+                posts.deleted_at,
+                posts.cover_image_url,
+                tags_of(posts.id) AS "tags!",
+                posts.category_id,
+                posts.seo_title,
+                posts.seo_description,
+                posts.created_by,
+                author_of(posts.created_by) AS "author: Author"
+            FROM
+                posts
+            WHERE
+                posts.id = $1
+            AND
+                posts.deleted_at IS NOT NULL
+            "#,
+            post_id
+        )
+        .fetch_optional(pg_pool)
+        .await
+        {
+            Ok(post) => Ok(post),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while retrieving the post from the database.",
+                ))
+            }
+        }
+    }
+
+    // Moves the post to the trash. It disappears for readers but keeps its
+    // slug, revisions and everything else until it is purged.
+    pub async fn delete(pg_pool: &PgPool, post_id: i32) -> Result<()> {
+        match sqlx::query!(
+            r#"
+            UPDATE
+                posts
+            SET
+                deleted_at = now(),
+                version = version + 1
+            WHERE
+                id = $1
+            AND
+                deleted_at IS NULL
+            "#,
+            post_id
+        )
+        .execute(pg_pool)
+        .await
+        {
+            Ok(done) if done.rows_affected() == 0 => Err(Error::from("The post doesn't exist.")),
+            Ok(_done) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while deleting the post in the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn restore(pg_pool: &PgPool, post_id: i32) -> Result<Self> {
+        match sqlx::query_as!(
+            Self,
+            r#"
+            UPDATE
+                posts
+            SET
+                deleted_at = NULL,
+                version = version + 1
+            WHERE
+                id = $1
+            AND
+                deleted_at IS NOT NULL
+            RETURNING
+                id,
+                slug,
+                title,
+                text,
+                summary,
+                status AS "status: PostStatus",
+                published_at,
+                created_at,
+                version,
+                deleted_at,
+                cover_image_url,
+                tags_of(id) AS "tags!",
+                category_id,
+                seo_title,
+                seo_description,
+                created_by,
+                author_of(created_by) AS "author: Author"
+            "#,
+            post_id
+        )
+        .fetch_optional(pg_pool)
+        .await
+        {
+            Ok(Some(post)) => Ok(post),
+            Ok(None) => Err(Error::from("The post isn't in the trash.")),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while restoring the post in the database.",
+                ))
+            }
+        }
+    }
+
+    // Only posts in the trash can be purged. Revisions and old slugs go with them.
+    pub async fn purge(pg_pool: &PgPool, post_id: i32) -> Result<()> {
+        match sqlx::query!(
+            r#"
+            DELETE FROM
+                posts
+            WHERE
+                id = $1
+            AND
+                deleted_at IS NOT NULL
+            "#,
+            post_id
+        )
+        .execute(pg_pool)
+        .await
+        {
+            Ok(done) if done.rows_affected() == 0 => {
+                Err(Error::from("The post isn't in the trash."))
+            }
+            Ok(_done) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while purging the post from the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn purge_trashed_before(
+        pg_pool: &PgPool,
+        before: DateTime<Utc>,
+    ) -> Result<Vec<Self>> {
+        match sqlx::query_as!(
+            Self,
+            r#"
+            DELETE FROM
+                posts
+            WHERE
+                deleted_at <= $1
+            RETURNING
+                id,
+                slug,
+                title,
+                text,
+                summary,
+                status AS "status: PostStatus",
+                published_at,
+                created_at,
+                version,
+                deleted_at,
+                cover_image_url,
+                tags_of(id) AS "tags!",
+                category_id,
+                seo_title,
+                seo_description,
+                created_by,
+                author_of(created_by) AS "author: Author"
+            "#,
+            before
+        )
+        .fetch_all(pg_pool)
+        .await
+        {
+            Ok(posts) => Ok(posts),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while purging the trash from the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn reassign<'e, E>(executor: E, from_user_id: i32, to_user_id: i32) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        match sqlx::query!(
+            r#"
+            UPDATE
+                posts
+            SET
+                created_by = $2
+            WHERE
+                created_by = $1
+            "#,
+            from_user_id,
+            to_user_id
+        )
+        .execute(executor)
+        .await
+        {
+            Ok(_done) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while reassigning the posts in the database.",
+                ))
+            }
+        }
+    }
+}
+
+#[derive(sqlx::FromRow, Debug, Deserialize, Serialize)]
+pub struct NewPost<'a> {
+    pub slug: &'a str,
+    pub title: &'a str,
+    pub text: &'a str,
+    pub summary: &'a str,
+    pub status: PostStatus,
+    pub published_at: Option<DateTime<Utc>>,
+    pub created_by: i32,
+}
+
+impl<'a> NewPost<'a> {
+    pub fn new(
+        slug: &'a str,
+        title: &'a str,
+        text: &'a str,
+        summary: &'a str,
+        status: PostStatus,
+        published_at: Option<DateTime<Utc>>,
+        created_by: i32,
+    ) -> Result<Self> {
+        Ok(Self {
+            slug,
+            title,
+            text,
+            summary,
+            status,
+            published_at: status.published_at(published_at)?,
+            created_by,
+        })
+    }
+
+    pub async fn insert(&self, pg_pool: &PgPool) -> Result<Post> {
+        match sqlx::query_as!(
+            Post,
+            r#"
+            INSERT INTO posts
+                (slug, title, text, summary, status, published_at, created_by)
+            SELECT
+                $1::varchar, $2, $3, $4, $5, $6, $7
+            WHERE NOT EXISTS (
+                SELECT 1 FROM post_slug_history WHERE slug = $1::varchar
+            )
+            RETURNING
+                id,
+                slug,
+                title,
+                text,
+                summary,
+                status AS "status: PostStatus",
+                published_at,
+                created_at,
+                version,
+                deleted_at,
+                cover_image_url,
+                tags_of(id) AS "tags!",
+                category_id,
+                seo_title,
+                seo_description,
+                created_by,
+                author_of(created_by) AS "author: Author"
+            "#,
+            &self.slug,
+            &self.title,
+            &self.text,
+            &self.summary,
+            self.status as PostStatus,
+            self.published_at,
+            &self.created_by
+        )
+        .fetch_optional(pg_pool)
+        .await
+        {
+            Ok(Some(post)) => Ok(post),
+            Ok(None) => Err(field_error(
+                "slug",
+                "This slug used to belong to another post and still redirects to it.",
+            )),
+            Err(sqlx::Error::Database(error)) if error.code().as_deref() == Some("23505") => {
+                Err(Error::from("A post with this slug already exists."))
+            }
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from("Unable to insert post in database."))
+            }
+        }
+    }
+}
+
+const MAX_TITLE_LENGTH: usize = 255;
+const MAX_TAGS: usize = 20;
+const MAX_SEO_DESCRIPTION_LENGTH: usize = 320;
+
+// The fields an editor changed. Everything left out keeps its current value;
+// an empty cover image or SEO field and a null category clear it.
+#[derive(InputObject)]
+pub struct UpdatePostInput {
+    pub slug: Option<String>,
+    pub title: Option<String>,
+    pub text: Option<String>,
+    pub summary: Option<String>,
+    pub cover_image_url: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub status: Option<PostStatus>,
+    pub published_at: Option<DateTime<Utc>>,
+    pub seo_title: Option<String>,
+    pub seo_description: Option<String>,
+    pub category_id: MaybeUndefined<i32>,
+}
+
+pub struct PostUpdate {
+    pub slug: Option<String>,
+    pub title: Option<String>,
+    pub text: Option<String>,
+    pub summary: Option<String>,
+    pub cover_image_url: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub status: Option<PostStatus>,
+    pub published_at: Option<DateTime<Utc>>,
+    pub seo_title: Option<String>,
+    pub seo_description: Option<String>,
+    pub category_id: Option<Option<i32>>,
+}
+
+impl PostUpdate {
+    pub fn new(input: UpdatePostInput) -> Result<Self> {
+        let slug = input.slug.map(|slug| slug.trim().to_string());
+        let title = input.title.map(|title| title.trim().to_string());
+        let summary = input.summary.map(|summary| summary.trim().to_string());
+        let cover_image_url = input.cover_image_url.map(|url| url.trim().to_string());
+        let seo_title = input.seo_title.map(|title| title.trim().to_string());
+        let seo_description = input
+            .seo_description
+            .map(|description| description.trim().to_string());
+        // Names that end up with the same slug are the same tag.
+        let tags = input.tags.map(|tags| {
+            let mut unique: Vec<String> = vec![];
+            for tag in tags.iter().map(|tag| tag.trim()) {
+                if !tag.is_empty() && !unique.iter().any(|seen| slugify(seen) == slugify(tag)) {
+                    unique.push(tag.to_string());
+                }
+            }
+            unique
+        });
+        let category_id = match input.category_id {
+            MaybeUndefined::Undefined => None,
+            MaybeUndefined::Null => Some(None),
+            MaybeUndefined::Value(category_id) => Some(Some(category_id)),
+        };
+
+        let mut errors = FieldErrors::default();
+        if matches!(slug, Some(ref slug) if slug.is_empty() || slug.contains(char::is_whitespace)) {
+            errors.add("slug", "The slug can't be empty or contain spaces.");
+        }
+        if matches!(title, Some(ref title) if title.is_empty() || title.chars().count() > MAX_TITLE_LENGTH)
+        {
+            errors.add(
+                "title",
+                format!(
+                    "The title must be between 1 and {} characters long.",
+                    MAX_TITLE_LENGTH
+                ),
+            );
+        }
+        if matches!(cover_image_url, Some(ref url) if !url.is_empty() && !is_web_url(url)) {
+            errors.add(
+                "coverImageUrl",
+                "The cover image must be an http or https URL.",
+            );
+        }
+        if matches!(tags, Some(ref tags) if tags.len() > MAX_TAGS) {
+            errors.add("tags", format!("Add at most {} tags.", MAX_TAGS));
+        } else if let Some(invalid) = tags.iter().flatten().find(|tag| slugify(tag).is_empty()) {
+            errors.add(
+                "tags",
+                format!("\"{}\" needs at least one letter or digit.", invalid),
+            );
+        }
+        if matches!(seo_title, Some(ref title) if title.chars().count() > MAX_TITLE_LENGTH) {
+            errors.add(
+                "seoTitle",
+                format!(
+                    "The SEO title can't be longer than {} characters.",
+                    MAX_TITLE_LENGTH
+                ),
+            );
+        }
+        if matches!(seo_description, Some(ref description) if description.chars().count() > MAX_SEO_DESCRIPTION_LENGTH)
+        {
+            errors.add(
+                "seoDescription",
+                format!(
+                    "The SEO description can't be longer than {} characters.",
+                    MAX_SEO_DESCRIPTION_LENGTH
+                ),
+            );
+        }
+        errors.into_result()?;
+
+        Ok(Self {
+            slug,
+            title,
+            text: input.text,
+            summary,
+            cover_image_url,
+            tags,
+            status: input.status,
+            published_at: input.published_at,
+            seo_title,
+            seo_description,
+            category_id,
+        })
+    }
+
+    // Applies the update only if nobody saved the post since `expected_version`
+    // was read. The slug history and a revision are recorded along with it.
+    pub async fn save(
+        &self,
+        pg_pool: &PgPool,
+        post_id: i32,
+        expected_version: i32,
+        user_id: i32,
+    ) -> Result<Post> {
+        let mut transaction = match pg_pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => {
+                println!("{}", error);
+                return Err(Error::from(
+                    "An error occured while updating the post in the database.",
+                ));
+            }
+        };
+
+        let (previous_status, previous_published_at) =
+            match Self::lock_status(&mut transaction, post_id).await? {
+                Some(current) => current,
+                None => return Err(Error::from("The post doesn't exist.")),
+            };
+        let status = self.status.unwrap_or(previous_status);
+        let published_at = status.updated_published_at(
+            previous_status,
+            previous_published_at,
+            self.published_at,
+        )?;
+
+        let updated = match sqlx::query!(
+            r#"
+            WITH previous AS (
+                SELECT
+                    id,
+                    slug
+                FROM
+                    posts
+                WHERE
+                    id = $1
+            )
+            UPDATE
+                posts
+            SET
+                slug = COALESCE($3, posts.slug),
+                title = COALESCE($4, posts.title),
+                text = COALESCE($5, posts.text),
+                summary = COALESCE($6, posts.summary),
+                cover_image_url = CASE
+                    WHEN $7::text IS NULL THEN posts.cover_image_url
+                    ELSE NULLIF($7, '')
+                END,
+                status = $8,
+                published_at = $9,
+                seo_title = CASE
+                    WHEN $10::varchar IS NULL THEN posts.seo_title
+                    ELSE NULLIF($10, '')
+                END,
+                seo_description = CASE
+                    WHEN $11::text IS NULL THEN posts.seo_description
+                    ELSE NULLIF($11, '')
+                END,
+                category_id = CASE
+                    WHEN $12 THEN $13
+                    ELSE posts.category_id
+                END,
+                version = posts.version + 1
+            FROM
+                previous
+            WHERE
+                posts.id = previous.id
+            AND
+                posts.version = $2
+            RETURNING
+                previous.slug AS previous_slug,
+                posts.slug
+            "#,
+            post_id,
+            expected_version,
+            self.slug,
+            self.title,
+            self.text,
+            self.summary,
+            self.cover_image_url,
+            status as PostStatus,
+            published_at,
+            self.seo_title,
+            self.seo_description,
+            self.category_id.is_some(),
+            self.category_id.flatten()
+        )
+        .fetch_optional(&mut transaction)
+        .await
+        {
+            Ok(updated) => updated,
+            Err(sqlx::Error::Database(error)) if error.code().as_deref() == Some("23505") => {
+                return Err(Error::from("A post with this slug already exists."));
+            }
+            Err(sqlx::Error::Database(error)) if error.code().as_deref() == Some("23503") => {
+                return Err(field_error("categoryId", "The category doesn't exist."));
+            }
+            Err(error) => {
+                println!("{}", error);
+                return Err(Error::from(
+                    "An error occured while updating the post in the database.",
+                ));
+            }
+        };
+
+        let updated = match updated {
+            Some(updated) => updated,
+            None => {
+                return match Self::current_version(&mut transaction, post_id).await? {
+                    Some(current_version) => Err(ApiError::Conflict(
+                        "Someone else saved this post while you were editing it.",
+                        current_version,
+                    )
+                    .extend()),
+                    None => Err(Error::from("The post doesn't exist.")),
+                };
+            }
+        };
+
+        if updated.previous_slug != updated.slug {
+            PostSlugHistory::record(
+                &mut transaction,
+                post_id,
+                &updated.previous_slug,
+                &updated.slug,
+            )
+            .await?;
+        }
+        if let Some(ref tags) = self.tags {
+            Tag::set_for_post(&mut transaction, post_id, tags).await?;
+        }
+        PostRevision::record(&mut transaction, post_id, user_id).await?;
+
+        match transaction.commit().await {
+            Ok(()) => {}
+            Err(error) => {
+                println!("{}", error);
+                return Err(Error::from(
+                    "An error occured while updating the post in the database.",
+                ));
+            }
+        }
+
+        match Post::get(pg_pool, post_id, Viewer::everything()).await? {
+            Some(post) => Ok(post),
+            None => Err(Error::from("The post doesn't exist.")),
+        }
+    }
+
+    // Holds the row until the update is saved, so the publish date is worked
+    // out from the status it replaces.
+    async fn lock_status<'e, E>(
+        executor: E,
+        post_id: i32,
+    ) -> Result<Option<(PostStatus, Option<DateTime<Utc>>)>>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        match sqlx::query!(
+            r#"
+            SELECT
+                status AS "status: PostStatus",
+                published_at
+            FROM
+                posts
+            WHERE
+                id = $1
+            FOR UPDATE
+            "#,
+            post_id
+        )
+        .fetch_optional(executor)
+        .await
+        {
+            Ok(current) => Ok(current.map(|current| (current.status, current.published_at))),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while retrieving the post from the database.",
+                ))
+            }
+        }
+    }
+
+    async fn current_version<'e, E>(executor: E, post_id: i32) -> Result<Option<i32>>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        match sqlx::query!(
+            r#"
+            SELECT
+                version
+            FROM
+                posts
+            WHERE
+                id = $1
+            "#,
+            post_id
+        )
+        .fetch_optional(executor)
+        .await
+        {
+            Ok(post) => Ok(post.map(|post| post.version)),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while retrieving the post from the database.",
+                ))
+            }
+        }
+    }
+}
@@ -0,0 +1,265 @@
+use crate::records::users::author::Author;
+use async_graphql::{Enum, Error, Result, SimpleObject};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use similar::{ChangeTag, TextDiff};
+use sqlx::{Executor, PgPool, Postgres};
+
+#[derive(sqlx::FromRow, SimpleObject, Debug, Deserialize, Serialize, Clone)]
+pub struct PostRevision {
+    pub id: i32,
+    pub post_id: i32,
+    pub title: String,
+    pub text: String,
+    pub summary: String,
+    pub created_at: DateTime<Utc>,
+    pub author: Option<Author>,
+}
+
+impl PostRevision {
+    pub async fn for_post(pg_pool: &PgPool, post_id: i32) -> Result<Vec<Self>> {
+        match sqlx::query_as!(
+            Self,
+            r#"
+            SELECT
+                id,
+                post_id,
+                title,
+                text,
+                summary,
+                created_at,
+                author_of(created_by) AS "author: Author"
+            FROM
+                post_revisions
+            WHERE
+                post_id = $1
+            ORDER BY
+                id DESC
+            "#,
+            post_id
+        )
+        .fetch_all(pg_pool)
+        .await
+        {
+            Ok(revisions) => Ok(revisions),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while retrieving the post revisions from the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn get(pg_pool: &PgPool, revision_id: i32) -> Result<Option<Self>> {
+        match sqlx::query_as!(
+            Self,
+            r#"
+            SELECT
+                id,
+                post_id,
+                title,
+                text,
+                summary,
+                created_at,
+                author_of(created_by) AS "author: Author"
+            FROM
+                post_revisions
+            WHERE
+                id = $1
+            "#,
+            revision_id
+        )
+        .fetch_optional(pg_pool)
+        .await
+        {
+            Ok(revision) => Ok(revision),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while retrieving the post revision from the database.",
+                ))
+            }
+        }
+    }
+
+    // Snapshots the post as it is now. Nothing is recorded when the content is
+    // the same as in the latest revision, so saving without changes adds no noise.
+    pub async fn record<'e, E>(executor: E, post_id: i32, user_id: i32) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        match sqlx::query!(
+            r#"
+            INSERT INTO post_revisions
+                (post_id, title, text, summary, created_by)
+            SELECT
+                posts.id,
+                posts.title,
+                posts.text,
+                posts.summary,
+                $2
+            FROM
+                posts
+            WHERE
+                posts.id = $1
+            AND NOT EXISTS (
+                SELECT
+                    1
+                FROM (
+                    SELECT
+                        title,
+                        text,
+                        summary
+                    FROM
+                        post_revisions
+                    WHERE
+                        post_id = $1
+                    ORDER BY
+                        id DESC
+                    LIMIT 1
+                ) AS latest
+                WHERE
+                    latest.title = posts.title
+                AND
+                    latest.text = posts.text
+                AND
+                    latest.summary = posts.summary
+            )
+            "#,
+            post_id,
+            user_id
+        )
+        .execute(executor)
+        .await
+        {
+            Ok(_done) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while recording the post revision in the database.",
+                ))
+            }
+        }
+    }
+
+    // Puts the content of the revision back on its post and records that as
+    // a new revision, so restoring can be undone like any other edit.
+    pub async fn restore(&self, pg_pool: &PgPool, user_id: i32) -> Result<()> {
+        let mut transaction = match pg_pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => {
+                println!("{}", error);
+                return Err(Error::from(
+                    "An error occured while restoring the post revision in the database.",
+                ));
+            }
+        };
+
+        match sqlx::query!(
+            r#"
+            UPDATE
+                posts
+            SET
+                title = $1,
+                text = $2,
+                summary = $3,
+                version = version + 1
+            WHERE
+                id = $4
+            "#,
+            self.title,
+            self.text,
+            self.summary,
+            self.post_id
+        )
+        .execute(&mut transaction)
+        .await
+        {
+            Ok(done) if done.rows_affected() == 0 => {
+                return Err(Error::from("The post doesn't exist."))
+            }
+            Ok(_done) => {}
+            Err(error) => {
+                println!("{}", error);
+                return Err(Error::from(
+                    "An error occured while restoring the post revision in the database.",
+                ));
+            }
+        }
+
+        Self::record(&mut transaction, self.post_id, user_id).await?;
+
+        match transaction.commit().await {
+            Ok(()) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while restoring the post revision in the database.",
+                ))
+            }
+        }
+    }
+}
+
+#[derive(Enum, Copy, Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub enum DiffTag {
+    Equal,
+    Delete,
+    Insert,
+}
+
+#[derive(SimpleObject, Debug, Deserialize, Serialize, Clone)]
+pub struct DiffLine {
+    pub tag: DiffTag,
+    // Line numbers start at 1 and are missing on the side the line isn't on.
+    pub old_line: Option<i32>,
+    pub new_line: Option<i32>,
+    pub text: String,
+}
+
+// Compares lines rather than raw text, so a missing newline at the end
+// doesn't show up as a change of the last line.
+fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    TextDiff::from_slices(&old, &new)
+        .iter_all_changes()
+        .map(|change| DiffLine {
+            tag: match change.tag() {
+                ChangeTag::Equal => DiffTag::Equal,
+                ChangeTag::Delete => DiffTag::Delete,
+                ChangeTag::Insert => DiffTag::Insert,
+            },
+            old_line: change.old_index().map(|index| index as i32 + 1),
+            new_line: change.new_index().map(|index| index as i32 + 1),
+            text: change.value().to_string(),
+        })
+        .collect()
+}
+
+#[derive(SimpleObject, Debug, Deserialize, Serialize, Clone)]
+pub struct RevisionDiff {
+    pub from: PostRevision,
+    pub to: PostRevision,
+    pub title: Vec<DiffLine>,
+    pub summary: Vec<DiffLine>,
+    pub text: Vec<DiffLine>,
+}
+
+impl RevisionDiff {
+    pub fn new(from: PostRevision, to: PostRevision) -> Result<Self> {
+        if from.post_id != to.post_id {
+            return Err(Error::from(
+                "Only revisions of the same post can be compared.",
+            ));
+        }
+        Ok(Self {
+            title: diff_lines(&from.title, &to.title),
+            summary: diff_lines(&from.summary, &to.summary),
+            text: diff_lines(&from.text, &to.text),
+            from,
+            to,
+        })
+    }
+}
@@ -0,0 +1,152 @@
+use crate::errors::field_error;
+use crate::records::posts::{Post, PostStatus, Viewer};
+use crate::records::users::author::Author;
+use async_graphql::{Error, Result};
+use sqlx::{PgPool, Postgres, Transaction};
+
+pub struct PostSlugHistory;
+
+impl PostSlugHistory {
+    pub async fn post(pg_pool: &PgPool, slug: &str, viewer: Viewer) -> Result<Option<Post>> {
+        match sqlx::query_as!(
+            Post,
+            r#"
+            SELECT
+                posts.id,
+                posts.slug,
+                posts.title,
+                posts.text,
+                posts.summary,
+                posts.status AS "status: PostStatus",
+                posts.published_at,
+                posts.created_at,
+                posts.version,
+                posts.deleted_at,
+                posts.cover_image_url,
+                tags_of(posts.id) AS "tags!",
+                posts.category_id,
+                posts.seo_title,
+                posts.seo_description,
+                posts.created_by,
+                author_of(posts.created_by) AS "author: Author"
+            FROM
+                post_slug_history
+            INNER JOIN
+                posts
+            ON
+                posts.id = post_slug_history.post_id
+            WHERE
+                post_slug_history.slug = $1
+            AND
+                posts.deleted_at IS NULL
+            AND (
+                posts.status = 'published'
+                OR posts.created_by = $2
+                OR $3
+            )
+            AND NOT EXISTS (
+                SELECT 1 FROM posts AS current WHERE current.slug = $1
+            )
+            "#,
+            slug,
+            viewer.user_id,
+            viewer.unpublished
+        )
+        .fetch_optional(pg_pool)
+        .await
+        {
+            Ok(post) => Ok(post),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while retrieving the post from the database.",
+                ))
+            }
+        }
+    }
+
+    // A slug keeps redirecting to the post that gave it up, so no other post
+    // can take it. The post itself can go back to one of its old slugs.
+    pub async fn record(
+        transaction: &mut Transaction<'_, Postgres>,
+        post_id: i32,
+        previous_slug: &str,
+        new_slug: &str,
+    ) -> Result<()> {
+        match sqlx::query!(
+            r#"
+            DELETE FROM
+                post_slug_history
+            WHERE
+                slug = $1
+            AND
+                post_id = $2
+            "#,
+            new_slug,
+            post_id
+        )
+        .execute(&mut *transaction)
+        .await
+        {
+            Ok(_done) => {}
+            Err(error) => {
+                println!("{}", error);
+                return Err(Error::from(
+                    "An error occured while updating the post slug history in the database.",
+                ));
+            }
+        }
+
+        match sqlx::query!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM post_slug_history WHERE slug = $1
+            ) AS "taken!"
+            "#,
+            new_slug
+        )
+        .fetch_one(&mut *transaction)
+        .await
+        {
+            Ok(row) if row.taken => {
+                return Err(field_error(
+                    "slug",
+                    "This slug used to belong to another post and still redirects to it.",
+                ));
+            }
+            Ok(_row) => {}
+            Err(error) => {
+                println!("{}", error);
+                return Err(Error::from(
+                    "An error occured while updating the post slug history in the database.",
+                ));
+            }
+        }
+
+        // The previous slug can only be in the history already if this post
+        // was created with it while it redirected elsewhere, which is no
+        // longer allowed. The first post to give it up keeps the redirect.
+        match sqlx::query!(
+            r#"
+            INSERT INTO post_slug_history
+                (post_id, slug)
+            VALUES
+                ($1, $2)
+            ON CONFLICT (slug) DO NOTHING
+            "#,
+            post_id,
+            previous_slug
+        )
+        .execute(&mut *transaction)
+        .await
+        {
+            Ok(_done) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while updating the post slug history in the database.",
+                ))
+            }
+        }
+    }
+}
@@ -0,0 +1,334 @@
+use crate::errors::{field_error, FieldErrors};
+use async_graphql::{Error, Result, SimpleObject};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, PgPool, Postgres};
+
+const MAX_NAME_LENGTH: usize = 255;
+
+// Lowercase letters and digits separated by single dashes. The taxonomy
+// migration slugs the tags it moved over the same way.
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for character in name.chars().flat_map(char::to_lowercase) {
+        if character.is_ascii_alphanumeric() {
+            slug.push(character);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+// The name and slug shared by tags and categories. The slug is made from the
+// name when none is given.
+pub struct Label {
+    pub name: String,
+    pub slug: String,
+}
+
+impl Label {
+    pub fn new(name: &str, slug: Option<&str>) -> Result<Self> {
+        let name = name.trim();
+        let slug = match slug.map(str::trim).filter(|slug| !slug.is_empty()) {
+            Some(slug) => slug.to_string(),
+            None => slugify(name),
+        };
+
+        let mut errors = FieldErrors::default();
+        if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+            errors.add(
+                "name",
+                format!(
+                    "The name must be between 1 and {} characters long.",
+                    MAX_NAME_LENGTH
+                ),
+            );
+        }
+        if slug.is_empty() || slug != slugify(&slug) {
+            errors.add(
+                "slug",
+                "The slug can only contain lowercase letters, digits and dashes.",
+            );
+        }
+        errors.into_result()?;
+
+        Ok(Self {
+            name: name.to_string(),
+            slug,
+        })
+    }
+}
+
+#[derive(sqlx::FromRow, SimpleObject, Debug, Deserialize, Serialize, Clone)]
+pub struct Tag {
+    pub id: i32,
+    pub slug: String,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    // Published posts with this tag.
+    pub post_count: i64,
+}
+
+impl Tag {
+    pub async fn all(pg_pool: &PgPool) -> Result<Vec<Self>> {
+        match sqlx::query_as!(
+            Self,
+            r#"
+            SELECT
+                id,
+                slug,
+                name,
+                created_at,
+                tag_post_count(id) AS "post_count!"
+            FROM
+                tags
+            ORDER BY
+                name
+            "#
+        )
+        .fetch_all(pg_pool)
+        .await
+        {
+            Ok(tags) => Ok(tags),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while retrieving the tags from the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn by_slug(pg_pool: &PgPool, slug: &str) -> Result<Option<Self>> {
+        match sqlx::query_as!(
+            Self,
+            r#"
+            SELECT
+                id,
+                slug,
+                name,
+                created_at,
+                tag_post_count(id) AS "post_count!"
+            FROM
+                tags
+            WHERE
+                slug = $1
+            "#,
+            slug
+        )
+        .fetch_optional(pg_pool)
+        .await
+        {
+            Ok(tag) => Ok(tag),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while retrieving the tag from the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn for_post(pg_pool: &PgPool, post_id: i32) -> Result<Vec<Self>> {
+        match sqlx::query_as!(
+            Self,
+            r#"
+            SELECT
+                tags.id,
+                tags.slug,
+                tags.name,
+                tags.created_at,
+                tag_post_count(tags.id) AS "post_count!"
+            FROM
+                tags
+            INNER JOIN
+                post_tags
+            ON
+                post_tags.tag_id = tags.id
+            WHERE
+                post_tags.post_id = $1
+            ORDER BY
+                tags.name
+            "#,
+            post_id
+        )
+        .fetch_all(pg_pool)
+        .await
+        {
+            Ok(tags) => Ok(tags),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while retrieving the post tags from the database.",
+                ))
+            }
+        }
+    }
+
+    // Replaces the tags of a post with the given names. Tags that don't exist
+    // yet are created along the way.
+    pub async fn set_for_post<'e, E>(executor: E, post_id: i32, names: &[String]) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        let slugs: Vec<String> = names.iter().map(|name| slugify(name)).collect();
+        match sqlx::query!(
+            r#"
+            WITH wanted AS (
+                SELECT
+                    slug,
+                    name
+                FROM
+                    UNNEST($2::text[], $3::text[]) AS wanted (slug, name)
+            ), created AS (
+                INSERT INTO tags
+                    (slug, name)
+                SELECT
+                    slug,
+                    name
+                FROM
+                    wanted
+                ON CONFLICT (slug) DO NOTHING
+                RETURNING
+                    id
+            ), removed AS (
+                DELETE FROM
+                    post_tags
+                WHERE
+                    post_id = $1
+                AND
+                    tag_id NOT IN (
+                        SELECT tags.id FROM tags INNER JOIN wanted ON wanted.slug = tags.slug
+                    )
+            )
+            INSERT INTO post_tags
+                (post_id, tag_id)
+            SELECT
+                $1, id
+            FROM
+                created
+            UNION
+            SELECT
+                $1, tags.id
+            FROM
+                tags
+            INNER JOIN
+                wanted
+            ON
+                wanted.slug = tags.slug
+            ON CONFLICT DO NOTHING
+            "#,
+            post_id,
+            &slugs,
+            names
+        )
+        .execute(executor)
+        .await
+        {
+            Ok(_done) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while saving the post tags in the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn insert(pg_pool: &PgPool, label: &Label) -> Result<Self> {
+        match sqlx::query_as!(
+            Self,
+            r#"
+            INSERT INTO tags
+                (slug, name)
+            VALUES
+                ($1, $2)
+            RETURNING
+                id,
+                slug,
+                name,
+                created_at,
+                tag_post_count(id) AS "post_count!"
+            "#,
+            label.slug,
+            label.name
+        )
+        .fetch_one(pg_pool)
+        .await
+        {
+            Ok(tag) => Ok(tag),
+            Err(sqlx::Error::Database(error)) if error.code().as_deref() == Some("23505") => {
+                Err(field_error("slug", "A tag with this slug already exists."))
+            }
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from("Unable to insert tag in database."))
+            }
+        }
+    }
+
+    pub async fn update(pg_pool: &PgPool, tag_id: i32, label: &Label) -> Result<Self> {
+        match sqlx::query_as!(
+            Self,
+            r#"
+            UPDATE
+                tags
+            SET
+                slug = $2,
+                name = $3
+            WHERE
+                id = $1
+            RETURNING
+                id,
+                slug,
+                name,
+                created_at,
+                tag_post_count(id) AS "post_count!"
+            "#,
+            tag_id,
+            label.slug,
+            label.name
+        )
+        .fetch_optional(pg_pool)
+        .await
+        {
+            Ok(Some(tag)) => Ok(tag),
+            Ok(None) => Err(Error::from("The tag doesn't exist.")),
+            Err(sqlx::Error::Database(error)) if error.code().as_deref() == Some("23505") => {
+                Err(field_error("slug", "A tag with this slug already exists."))
+            }
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while updating the tag in the database.",
+                ))
+            }
+        }
+    }
+
+    // Posts lose the tag, they are not deleted with it.
+    pub async fn delete(pg_pool: &PgPool, tag_id: i32) -> Result<()> {
+        match sqlx::query!(
+            r#"
+            DELETE FROM
+                tags
+            WHERE
+                id = $1
+            "#,
+            tag_id
+        )
+        .execute(pg_pool)
+        .await
+        {
+            Ok(done) if done.rows_affected() == 0 => Err(Error::from("The tag doesn't exist.")),
+            Ok(_done) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while deleting the tag from the database.",
+                ))
+            }
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use async_graphql::{Error, Result, SimpleObject};
+use chrono::DateTime;
+use registry::SettingDefinition;
+use serde::{Deserialize, Serialize};
+use sqlx::{Executor, PgPool, Postgres};
+
+pub mod registry;
+
+#[derive(sqlx::FromRow, SimpleObject, Debug, Deserialize, Serialize, Clone)]
+pub struct Settings {
+    pub id: i32,
+    pub key: String,
+    pub value: String,
+    pub created_at: DateTime<chrono::Utc>,
+    pub updated_at: DateTime<chrono::Utc>,
+}
+
+impl Settings {
+    pub async fn all(pg_pool: &PgPool) -> Result<Vec<Self>> {
+        match sqlx::query_as!(
+            Self,
+            r#"
+            SELECT
+                settings.id,
+                settings.key,
+                settings.value,
+                settings.created_at,
+                settings.updated_at
+            FROM
+                settings
+            ORDER BY
+                settings.key
+            "#,
+        )
+        .fetch_all(pg_pool)
+        .await
+        {
+            Ok(settings) => Ok(settings),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while retrieving the settings from the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn update(pg_pool: &PgPool, key: &str, value: &str) -> Result<Self> {
+        SettingDefinition::find(key)?.validate(value)?;
+        match sqlx::query_as!(
+            Self,
+            r#"
+            UPDATE
+                settings
+            SET
+                value = $2,
+                updated_at = now()
+            WHERE
+                key = $1
+            RETURNING
+                id,
+                key,
+                value,
+                created_at,
+                updated_at
+            "#,
+            key,
+            value
+        )
+        .fetch_optional(pg_pool)
+        .await
+        {
+            Ok(Some(setting)) => Ok(setting),
+            Ok(None) => Err(Error::from("The setting doesn't exist.")),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while updating the setting in the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn delete(pg_pool: &PgPool, key: &str) -> Result<()> {
+        match sqlx::query!(
+            r#"
+            DELETE FROM
+                settings
+            WHERE
+                key = $1
+            "#,
+            key
+        )
+        .execute(pg_pool)
+        .await
+        {
+            Ok(done) if done.rows_affected() == 0 => Err(Error::from("The setting doesn't exist.")),
+            Ok(_done) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while deleting the setting from the database.",
+                ))
+            }
+        }
+    }
+}
+
+#[derive(sqlx::FromRow, Debug, Deserialize, Serialize)]
+pub struct NewSetting<'a> {
+    pub key: &'a str,
+    pub value: &'a str,
+}
+
+impl<'a> NewSetting<'a> {
+    pub fn new(key: &'a str, value: &'a str) -> Result<Self> {
+        SettingDefinition::find(key)?.validate(value)?;
+        Ok(Self { key, value })
+    }
+
+    pub async fn insert<'e, E>(&self, executor: E) -> Result<Settings>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        match sqlx::query_as!(
+            Settings,
+            r#"
+                INSERT INTO settings
+                    (key, value)
+                VALUES
+                    ($1, $2)
+                RETURNING
+                    id,
+                    key,
+                    value,
+                    created_at,
+                    updated_at
+            "#,
+            &self.key,
+            &self.value
+        )
+        .fetch_one(executor)
+        .await
+        {
+            Ok(setting) => Ok(setting),
+            Err(sqlx::Error::Database(error)) if error.code().as_deref() == Some("23505") => {
+                Err(Error::from("A setting with this key already exists."))
+            }
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from("Unable to insert setting in database."))
+            }
+        }
+    }
+
+    pub async fn upsert(&self, pg_pool: &PgPool) -> Result<Settings> {
+        match sqlx::query_as!(
+            Settings,
+            r#"
+                INSERT INTO settings
+                    (key, value)
+                VALUES
+                    ($1, $2)
+                ON CONFLICT (key) DO UPDATE SET
+                    value = EXCLUDED.value,
+                    updated_at = now()
+                RETURNING
+                    id,
+                    key,
+                    value,
+                    created_at,
+                    updated_at
+            "#,
+            &self.key,
+            &self.value
+        )
+        .fetch_one(pg_pool)
+        .await
+        {
+            Ok(setting) => Ok(setting),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from("Unable to save setting in database."))
+            }
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use crate::records::settings::Settings;
+use async_graphql::{Error, Result, SimpleObject};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+static URL_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^https?://[^\s/?#]+[^\s]*$").unwrap());
+
+#[derive(Debug)]
+pub enum SettingKind {
+    Text { max_length: usize },
+    Url,
+    Integer { min: i32, max: i32 },
+    Boolean,
+    Choice(&'static [&'static str]),
+}
+
+#[derive(Debug)]
+pub struct SettingDefinition {
+    pub key: &'static str,
+    pub kind: SettingKind,
+    pub default: &'static str,
+}
+
+pub const REGISTRY: &[SettingDefinition] = &[
+    SettingDefinition {
+        key: "title",
+        kind: SettingKind::Text { max_length: 100 },
+        default: "",
+    },
+    SettingDefinition {
+        key: "tagline",
+        kind: SettingKind::Text { max_length: 255 },
+        default: "",
+    },
+    SettingDefinition {
+        key: "site_url",
+        kind: SettingKind::Url,
+        default: "",
+    },
+    SettingDefinition {
+        key: "posts_per_page",
+        kind: SettingKind::Integer { min: 1, max: 100 },
+        default: "10",
+    },
+    SettingDefinition {
+        key: "allow_indexing",
+        kind: SettingKind::Boolean,
+        default: "true",
+    },
+    SettingDefinition {
+        key: "require_verified_email",
+        kind: SettingKind::Boolean,
+        default: "false",
+    },
+    SettingDefinition {
+        key: "date_format",
+        kind: SettingKind::Choice(&["iso", "short", "long"]),
+        default: "iso",
+    },
+];
+
+impl SettingDefinition {
+    pub fn find(key: &str) -> Result<&'static Self> {
+        match REGISTRY.iter().find(|definition| definition.key == key) {
+            Some(definition) => Ok(definition),
+            None => Err(Error::from(format!("Unknown setting \"{}\".", key))),
+        }
+    }
+
+    pub fn validate(&self, value: &str) -> Result<()> {
+        let valid = match self.kind {
+            SettingKind::Text { max_length } => value.chars().count() <= max_length,
+            SettingKind::Url => value.is_empty() || URL_REGEX.is_match(value),
+            SettingKind::Integer { min, max } => match value.parse::<i32>() {
+                Ok(number) => number >= min && number <= max,
+                Err(_) => false,
+            },
+            SettingKind::Boolean => value == "true" || value == "false",
+            SettingKind::Choice(choices) => choices.contains(&value),
+        };
+
+        if valid {
+            Ok(())
+        } else {
+            Err(Error::from(format!(
+                "The value for \"{}\" must be {}.",
+                self.key,
+                self.kind.describe()
+            )))
+        }
+    }
+}
+
+impl SettingKind {
+    fn describe(&self) -> String {
+        match self {
+            SettingKind::Text { max_length } => format!("at most {} characters", max_length),
+            SettingKind::Url => "an http or https URL".to_string(),
+            SettingKind::Integer { min, max } => format!("an integer from {} to {}", min, max),
+            SettingKind::Boolean => "true or false".to_string(),
+            SettingKind::Choice(choices) => format!("one of {}", choices.join(", ")),
+        }
+    }
+}
+
+#[derive(SimpleObject, Debug, Deserialize, Serialize, Clone)]
+pub struct SiteSettings {
+    pub title: String,
+    pub tagline: String,
+    pub site_url: Option<String>,
+    pub posts_per_page: i32,
+    pub allow_indexing: bool,
+    pub require_verified_email: bool,
+    pub date_format: String,
+}
+
+impl SiteSettings {
+    pub fn from_settings(settings: &[Settings]) -> Self {
+        let value = |key: &str| -> String {
+            let definition = SettingDefinition::find(key).unwrap();
+            settings
+                .iter()
+                .find(|setting| setting.key == key)
+                .filter(|setting| definition.validate(&setting.value).is_ok())
+                .map(|setting| setting.value.clone())
+                .unwrap_or_else(|| definition.default.to_string())
+        };
+
+        let site_url = value("site_url");
+        Self {
+            title: value("title"),
+            tagline: value("tagline"),
+            site_url: if site_url.is_empty() {
+                None
+            } else {
+                Some(site_url)
+            },
+            posts_per_page: value("posts_per_page").parse().unwrap(),
+            allow_indexing: value("allow_indexing") == "true",
+            require_verified_email: value("require_verified_email") == "true",
+            date_format: value("date_format"),
+        }
+    }
+
+    // Links in emails are opened outside the site, so they need its address.
+    pub fn site_url_for_emails(&self) -> Result<&str> {
+        match self.site_url {
+            Some(ref site_url) => Ok(site_url.trim_end_matches('/')),
+            None => Err(Error::from(
+                "The site URL isn't set, so emailed links wouldn't work. An admin can set it in the settings.",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SettingDefinition, SettingKind};
+
+    fn definition(kind: SettingKind) -> SettingDefinition {
+        SettingDefinition {
+            key: "test",
+            kind,
+            default: "",
+        }
+    }
+
+    #[test]
+    fn limits_text_to_its_length_in_characters() {
+        let text = definition(SettingKind::Text { max_length: 3 });
+        assert!(text.validate("").is_ok());
+        assert!(text.validate("åäö").is_ok());
+        assert!(text.validate("abcd").is_err());
+    }
+
+    #[test]
+    fn accepts_empty_or_http_urls() {
+        let url = definition(SettingKind::Url);
+        assert!(url.validate("").is_ok());
+        assert!(url.validate("https://example.com").is_ok());
+        assert!(url.validate("http://localhost:3030/blog").is_ok());
+        assert!(url.validate("ftp://example.com").is_err());
+        assert!(url.validate("https://").is_err());
+        assert!(url.validate("https://example.com/a b").is_err());
+    }
+
+    #[test]
+    fn keeps_integers_within_their_bounds() {
+        let integer = definition(SettingKind::Integer { min: 1, max: 100 });
+        assert!(integer.validate("1").is_ok());
+        assert!(integer.validate("100").is_ok());
+        assert!(integer.validate("0").is_err());
+        assert!(integer.validate("101").is_err());
+        assert!(integer.validate("ten").is_err());
+    }
+
+    #[test]
+    fn accepts_only_true_or_false_for_booleans() {
+        let boolean = definition(SettingKind::Boolean);
+        assert!(boolean.validate("true").is_ok());
+        assert!(boolean.validate("false").is_ok());
+        assert!(boolean.validate("yes").is_err());
+        assert!(boolean.validate("").is_err());
+    }
+
+    #[test]
+    fn accepts_only_listed_choices() {
+        let choice = definition(SettingKind::Choice(&["iso", "short"]));
+        assert!(choice.validate("iso").is_ok());
+        assert!(choice.validate("short").is_ok());
+        assert!(choice.validate("ISO").is_err());
+        let error = choice.validate("long").unwrap_err();
+        assert_eq!(
+            error.message,
+            "The value for \"test\" must be one of iso, short."
+        );
+    }
+}
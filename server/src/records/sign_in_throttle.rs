@@ -0,0 +1,161 @@
+use async_graphql::{Error, Result};
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+
+pub struct ThrottlePolicy {
+    pub free_attempts: i32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl ThrottlePolicy {
+    pub fn account() -> Self {
+        Self {
+            free_attempts: 5,
+            base_delay: Duration::seconds(30),
+            max_delay: Duration::hours(1),
+        }
+    }
+
+    pub fn ip_address() -> Self {
+        Self {
+            free_attempts: 20,
+            base_delay: Duration::seconds(30),
+            max_delay: Duration::hours(1),
+        }
+    }
+
+    fn delay(&self, failures: i32) -> Option<Duration> {
+        let over = failures - self.free_attempts;
+        if over < 0 {
+            return None;
+        }
+        let factor = 2_i32.saturating_pow(over.min(20) as u32);
+        Some(std::cmp::min(self.base_delay * factor, self.max_delay))
+    }
+}
+
+pub struct SignInThrottle;
+
+impl SignInThrottle {
+    pub fn account_key(email: &str) -> String {
+        format!("account:{}", email.trim().to_lowercase())
+    }
+
+    pub fn ip_address_key(ip_address: &str) -> String {
+        format!("ip:{}", ip_address)
+    }
+
+    pub async fn locked_until(pg_pool: &PgPool, keys: &[String]) -> Result<Option<DateTime<Utc>>> {
+        match sqlx::query!(
+            r#"
+            SELECT
+                MAX(locked_until) AS locked_until
+            FROM
+                sign_in_throttles
+            WHERE
+                key = ANY($1)
+            AND
+                locked_until > now()
+            "#,
+            keys
+        )
+        .fetch_one(pg_pool)
+        .await
+        {
+            Ok(row) => Ok(row.locked_until),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while checking the sign in attempts in the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn record_failure(pg_pool: &PgPool, key: &str, policy: ThrottlePolicy) -> Result<()> {
+        // A quiet period as long as the longest lockout starts the count over.
+        let failures = match sqlx::query!(
+            r#"
+            INSERT INTO sign_in_throttles
+                (key, failures)
+            VALUES
+                ($1, 1)
+            ON CONFLICT (key) DO UPDATE SET
+                failures = CASE
+                    WHEN sign_in_throttles.last_failure_at < now() - $2 * interval '1 second'
+                    THEN 1
+                    ELSE sign_in_throttles.failures + 1
+                END,
+                last_failure_at = now()
+            RETURNING
+                failures
+            "#,
+            key,
+            policy.max_delay.num_seconds() as f64
+        )
+        .fetch_one(pg_pool)
+        .await
+        {
+            Ok(row) => row.failures,
+            Err(error) => {
+                println!("{}", error);
+                return Err(Error::from(
+                    "An error occured while saving the sign in attempt in the database.",
+                ));
+            }
+        };
+
+        let delay = match policy.delay(failures) {
+            Some(delay) => delay,
+            None => return Ok(()),
+        };
+
+        match sqlx::query!(
+            r#"
+            UPDATE
+                sign_in_throttles
+            SET
+                locked_until = $2
+            WHERE
+                key = $1
+            "#,
+            key,
+            Utc::now() + delay
+        )
+        .execute(pg_pool)
+        .await
+        {
+            Ok(_done) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while saving the sign in attempt in the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn clear(pg_pool: &PgPool, key: &str) -> Result<()> {
+        match sqlx::query!(
+            r#"
+            DELETE FROM
+                sign_in_throttles
+            WHERE
+                key = $1
+            "#,
+            key
+        )
+        .execute(pg_pool)
+        .await
+        {
+            Ok(_done) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while clearing the sign in attempts in the database.",
+                ))
+            }
+        }
+    }
+}
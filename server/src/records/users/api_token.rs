@@ -0,0 +1,255 @@
+use crate::errors::FieldErrors;
+use crate::records::users::session::{generate_token, hash_token};
+use async_graphql::{Error, Result, SimpleObject};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+// Lets the server tell API tokens apart from session tokens without a lookup.
+pub const TOKEN_PREFIX: &str = "blog_";
+const MAX_NAME_LENGTH: usize = 100;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ApiScope {
+    PostsRead,
+    PostsWrite,
+    SettingsRead,
+    SettingsWrite,
+    UsersWrite,
+}
+
+const SCOPES: &[ApiScope] = &[
+    ApiScope::PostsRead,
+    ApiScope::PostsWrite,
+    ApiScope::SettingsRead,
+    ApiScope::SettingsWrite,
+    ApiScope::UsersWrite,
+];
+
+impl ApiScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiScope::PostsRead => "posts:read",
+            ApiScope::PostsWrite => "posts:write",
+            ApiScope::SettingsRead => "settings:read",
+            ApiScope::SettingsWrite => "settings:write",
+            ApiScope::UsersWrite => "users:write",
+        }
+    }
+
+    pub fn parse(scope: &str) -> Option<Self> {
+        SCOPES
+            .iter()
+            .copied()
+            .find(|candidate| candidate.as_str() == scope)
+    }
+}
+
+#[derive(sqlx::FromRow, SimpleObject, Debug, Deserialize, Serialize, Clone)]
+pub struct ApiToken {
+    pub id: i32,
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+pub struct ApiTokenOwner {
+    pub user_id: i32,
+    pub scopes: Vec<String>,
+}
+
+impl ApiTokenOwner {
+    pub fn has_scope(&self, scope: ApiScope) -> bool {
+        self.scopes.iter().any(|granted| granted == scope.as_str())
+    }
+}
+
+impl ApiToken {
+    pub async fn for_user(pg_pool: &PgPool, user_id: i32) -> Result<Vec<Self>> {
+        match sqlx::query_as!(
+            Self,
+            r#"
+            SELECT
+                id,
+                name,
+                scopes,
+                last_used_at,
+                expires_at,
+                created_at
+            FROM
+                api_tokens
+            WHERE
+                user_id = $1
+            ORDER BY
+                created_at DESC
+            "#,
+            user_id
+        )
+        .fetch_all(pg_pool)
+        .await
+        {
+            Ok(api_tokens) => Ok(api_tokens),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while retrieving the API tokens from the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn owner(pg_pool: &PgPool, token: &str) -> Result<Option<ApiTokenOwner>> {
+        match sqlx::query_as!(
+            ApiTokenOwner,
+            r#"
+            UPDATE
+                api_tokens
+            SET
+                last_used_at = now()
+            WHERE
+                token_hash = $1
+            AND
+                (expires_at IS NULL OR expires_at > now())
+            RETURNING
+                user_id,
+                scopes
+            "#,
+            hash_token(token)
+        )
+        .fetch_optional(pg_pool)
+        .await
+        {
+            Ok(owner) => Ok(owner),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while retrieving the API token from the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn revoke(pg_pool: &PgPool, user_id: i32, api_token_id: i32) -> Result<()> {
+        match sqlx::query!(
+            r#"
+            DELETE FROM
+                api_tokens
+            WHERE
+                id = $1
+            AND
+                user_id = $2
+            "#,
+            api_token_id,
+            user_id
+        )
+        .execute(pg_pool)
+        .await
+        {
+            Ok(done) if done.rows_affected() == 0 => {
+                Err(Error::from("The API token doesn't exist."))
+            }
+            Ok(_done) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while deleting the API token from the database.",
+                ))
+            }
+        }
+    }
+}
+
+pub struct NewApiToken {
+    pub token: String,
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl NewApiToken {
+    pub fn new(name: &str, scopes: &[String], expires_at: Option<DateTime<Utc>>) -> Result<Self> {
+        let mut errors = FieldErrors::default();
+        let name = name.trim();
+        if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+            errors.add(
+                "name",
+                format!(
+                    "The name must be between 1 and {} characters long.",
+                    MAX_NAME_LENGTH
+                ),
+            );
+        }
+        if scopes.is_empty() {
+            errors.add("scopes", "Choose at least one scope.");
+        } else if let Some(unknown) = scopes.iter().find(|scope| ApiScope::parse(scope).is_none()) {
+            errors.add(
+                "scopes",
+                format!(
+                    "Unknown scope \"{}\". Valid scopes are {}.",
+                    unknown,
+                    SCOPES
+                        .iter()
+                        .map(|scope| scope.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            );
+        }
+        if matches!(expires_at, Some(expires_at) if expires_at <= Utc::now()) {
+            errors.add("expiresAt", "The expiry date must be in the future.");
+        }
+        errors.into_result()?;
+
+        let mut scopes = scopes.to_vec();
+        scopes.sort();
+        scopes.dedup();
+        Ok(Self {
+            token: format!("{}{}", TOKEN_PREFIX, generate_token()),
+            name: name.to_string(),
+            scopes,
+            expires_at,
+        })
+    }
+
+    pub async fn insert(&self, pg_pool: &PgPool, user_id: i32) -> Result<ApiToken> {
+        match sqlx::query_as!(
+            ApiToken,
+            r#"
+            INSERT INTO api_tokens
+                (user_id, name, token_hash, scopes, expires_at)
+            VALUES
+                ($1, $2, $3, $4, $5)
+            RETURNING
+                id,
+                name,
+                scopes,
+                last_used_at,
+                expires_at,
+                created_at
+            "#,
+            user_id,
+            self.name,
+            hash_token(&self.token),
+            &self.scopes,
+            self.expires_at
+        )
+        .fetch_one(pg_pool)
+        .await
+        {
+            Ok(api_token) => Ok(api_token),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from("Unable to insert API token in database."))
+            }
+        }
+    }
+}
+
+// The plain token is only ever returned here, right after it is created.
+#[derive(SimpleObject, Debug, Deserialize, Serialize, Clone)]
+pub struct CreatedApiToken {
+    pub token: String,
+    pub api_token: ApiToken,
+}
@@ -0,0 +1,198 @@
+use crate::errors::FieldErrors;
+use async_graphql::{Error, Result, SimpleObject};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    error::BoxDynError,
+    postgres::{types::PgRecordDecoder, PgTypeInfo, PgValueRef},
+    PgPool, Postgres,
+};
+use url::Url;
+
+const MAX_DISPLAY_NAME_LENGTH: usize = 100;
+const MAX_BIO_LENGTH: usize = 2000;
+const MAX_SOCIAL_LINKS: usize = 10;
+
+// The public side of a user. Mirrors the `author` database type so posts can
+// select their author with `author_of(created_by)`.
+#[derive(SimpleObject, Debug, Deserialize, Serialize, Clone)]
+pub struct Author {
+    pub id: i32,
+    pub display_name: String,
+    pub bio: String,
+    pub avatar_url: Option<String>,
+    pub website_url: Option<String>,
+    pub social_links: Vec<String>,
+}
+
+// Written out because the derive can't decode the nullable fields of a record.
+impl sqlx::Type<Postgres> for Author {
+    fn type_info() -> PgTypeInfo {
+        PgTypeInfo::with_name("author")
+    }
+}
+
+impl<'r> sqlx::Decode<'r, Postgres> for Author {
+    fn decode(value: PgValueRef<'r>) -> std::result::Result<Self, BoxDynError> {
+        let mut decoder = PgRecordDecoder::new(value)?;
+        Ok(Self {
+            id: decoder.try_decode()?,
+            display_name: decoder.try_decode()?,
+            bio: decoder.try_decode()?,
+            avatar_url: decoder.try_decode()?,
+            website_url: decoder.try_decode()?,
+            social_links: decoder.try_decode()?,
+        })
+    }
+}
+
+impl Author {
+    pub async fn get(pg_pool: &PgPool, user_id: i32) -> Result<Option<Self>> {
+        match sqlx::query!(
+            r#"
+            SELECT
+                author_of($1) AS "author: Author"
+            "#,
+            user_id
+        )
+        .fetch_one(pg_pool)
+        .await
+        {
+            Ok(row) => Ok(row.author),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while retrieving the author from the database.",
+                ))
+            }
+        }
+    }
+}
+
+// What the signed in user sees of their own account.
+#[derive(SimpleObject, Debug, Deserialize, Serialize, Clone)]
+pub struct Profile {
+    pub email: String,
+    pub email_verified_at: Option<DateTime<Utc>>,
+    pub author: Author,
+}
+
+pub fn is_web_url(url: &str) -> bool {
+    matches!(Url::parse(url), Ok(url) if url.scheme() == "https" || url.scheme() == "http")
+}
+
+fn optional_url(url: Option<String>) -> Option<String> {
+    url.map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty())
+}
+
+pub struct ProfileUpdate {
+    pub display_name: String,
+    pub bio: String,
+    pub avatar_url: Option<String>,
+    pub website_url: Option<String>,
+    pub social_links: Vec<String>,
+}
+
+impl ProfileUpdate {
+    pub fn new(
+        display_name: &str,
+        bio: &str,
+        avatar_url: Option<String>,
+        website_url: Option<String>,
+        social_links: Vec<String>,
+    ) -> Result<Self> {
+        let display_name = display_name.trim();
+        let bio = bio.trim();
+        let avatar_url = optional_url(avatar_url);
+        let website_url = optional_url(website_url);
+        let social_links: Vec<String> = social_links
+            .iter()
+            .map(|link| link.trim().to_string())
+            .filter(|link| !link.is_empty())
+            .collect();
+
+        let mut errors = FieldErrors::default();
+        if display_name.is_empty() || display_name.chars().count() > MAX_DISPLAY_NAME_LENGTH {
+            errors.add(
+                "displayName",
+                format!(
+                    "The display name must be between 1 and {} characters long.",
+                    MAX_DISPLAY_NAME_LENGTH
+                ),
+            );
+        }
+        if bio.chars().count() > MAX_BIO_LENGTH {
+            errors.add(
+                "bio",
+                format!(
+                    "The bio can't be longer than {} characters.",
+                    MAX_BIO_LENGTH
+                ),
+            );
+        }
+        if matches!(avatar_url, Some(ref url) if !is_web_url(url)) {
+            errors.add("avatarUrl", "The avatar must be an http or https URL.");
+        }
+        if matches!(website_url, Some(ref url) if !is_web_url(url)) {
+            errors.add("websiteUrl", "The website must be an http or https URL.");
+        }
+        if social_links.len() > MAX_SOCIAL_LINKS {
+            errors.add(
+                "socialLinks",
+                format!("Add at most {} social links.", MAX_SOCIAL_LINKS),
+            );
+        } else if let Some(invalid) = social_links.iter().find(|link| !is_web_url(link)) {
+            errors.add(
+                "socialLinks",
+                format!("\"{}\" is not an http or https URL.", invalid),
+            );
+        }
+        errors.into_result()?;
+
+        Ok(Self {
+            display_name: display_name.to_string(),
+            bio: bio.to_string(),
+            avatar_url,
+            website_url,
+            social_links,
+        })
+    }
+
+    pub async fn save(&self, pg_pool: &PgPool, user_id: i32) -> Result<Author> {
+        match sqlx::query!(
+            r#"
+            UPDATE
+                users
+            SET
+                name = $1,
+                bio = $2,
+                avatar_url = $3,
+                website_url = $4,
+                social_links = $5
+            WHERE
+                id = $6
+            RETURNING
+                ROW(id, name, bio, avatar_url, website_url, social_links)::author AS "author!: Author"
+            "#,
+            self.display_name,
+            self.bio,
+            self.avatar_url,
+            self.website_url,
+            &self.social_links,
+            user_id
+        )
+        .fetch_optional(pg_pool)
+        .await
+        {
+            Ok(Some(row)) => Ok(row.author),
+            Ok(None) => Err(Error::from("The user doesn't exist.")),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while updating the profile in the database.",
+                ))
+            }
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use crate::records::posts::{Post, Viewer};
+use crate::records::users::{
+    api_token::ApiToken,
+    author::{Author, Profile},
+    session::Session,
+    SimpleUser,
+};
+use async_graphql::{Error, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+
+// Everything the blog keeps about a user, in the shape handed out by
+// `exportMyData`. Secrets like password and token hashes stay out of it.
+#[derive(Serialize)]
+pub struct DataExport {
+    pub exported_at: DateTime<Utc>,
+    pub account: SimpleUser,
+    pub profile: Profile,
+    pub posts: Vec<Post>,
+    pub trashed_posts: Vec<Post>,
+    pub sessions: Vec<Session>,
+    pub api_tokens: Vec<ApiToken>,
+}
+
+impl DataExport {
+    pub async fn collect(pg_pool: &PgPool, user: SimpleUser, token: &str) -> Result<Self> {
+        let author = match Author::get(pg_pool, user.id).await? {
+            Some(author) => author,
+            None => return Err(Error::from("The user doesn't exist.")),
+        };
+        Ok(Self {
+            exported_at: Utc::now(),
+            profile: Profile {
+                email: user.email.clone(),
+                email_verified_at: user.email_verified_at,
+                author,
+            },
+            posts: Post::all(pg_pool, Viewer::everything(), Some(user.id), None).await?,
+            trashed_posts: Post::trash(
+                pg_pool,
+                Viewer {
+                    user_id: Some(user.id),
+                    unpublished: false,
+                },
+            )
+            .await?,
+            sessions: Session::for_user(pg_pool, user.id, token).await?,
+            api_tokens: ApiToken::for_user(pg_pool, user.id).await?,
+            account: user,
+        })
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        match serde_json::to_string_pretty(self) {
+            Ok(json) => Ok(json),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from("Unable to export the account data."))
+            }
+        }
+    }
+}
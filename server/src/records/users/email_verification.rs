@@ -0,0 +1,119 @@
+use crate::records::users::session::{generate_token, hash_token};
+use async_graphql::{Error, Result};
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{Executor, PgPool, Postgres, Transaction};
+
+const LIFETIME_DAYS: i64 = 7;
+
+pub struct NewEmailVerification {
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl NewEmailVerification {
+    pub fn make() -> Self {
+        Self {
+            token: generate_token(),
+            expires_at: Utc::now() + Duration::days(LIFETIME_DAYS),
+        }
+    }
+
+    pub async fn insert(&self, pg_pool: &PgPool, user_id: i32) -> Result<()> {
+        match sqlx::query!(
+            r#"
+            INSERT INTO email_verifications
+                (user_id, token_hash, expires_at)
+            VALUES
+                ($1, $2, $3)
+            "#,
+            user_id,
+            hash_token(&self.token),
+            self.expires_at
+        )
+        .execute(pg_pool)
+        .await
+        {
+            Ok(_done) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "Unable to insert email verification in database.",
+                ))
+            }
+        }
+    }
+}
+
+pub struct EmailVerification;
+
+impl EmailVerification {
+    pub async fn consume(transaction: &mut Transaction<'_, Postgres>, token: &str) -> Result<i32> {
+        let user_id = match sqlx::query!(
+            r#"
+            UPDATE
+                email_verifications
+            SET
+                used_at = now()
+            WHERE
+                token_hash = $1
+            AND
+                used_at IS NULL
+            AND
+                expires_at > now()
+            RETURNING
+                user_id
+            "#,
+            hash_token(token)
+        )
+        .fetch_optional(&mut *transaction)
+        .await
+        {
+            Ok(Some(reset)) => reset.user_id,
+            Ok(None) => {
+                return Err(Error::from(
+                    "The email verification link is invalid or has expired.",
+                ))
+            }
+            Err(error) => {
+                println!("{}", error);
+                return Err(Error::from(
+                    "An error occured while retrieving the email verification from the database.",
+                ));
+            }
+        };
+
+        Self::discard_pending(&mut *transaction, user_id).await?;
+        Ok(user_id)
+    }
+
+    // Links sent before are for an address the user may no longer have.
+    pub async fn discard_pending<'e, E>(executor: E, user_id: i32) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        match sqlx::query!(
+            r#"
+            UPDATE
+                email_verifications
+            SET
+                used_at = now()
+            WHERE
+                user_id = $1
+            AND
+                used_at IS NULL
+            "#,
+            user_id
+        )
+        .execute(executor)
+        .await
+        {
+            Ok(_done) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while updating the email verifications in the database.",
+                ))
+            }
+        }
+    }
+}
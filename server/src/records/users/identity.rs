@@ -0,0 +1,69 @@
+use async_graphql::{Error, Result};
+use sqlx::{Executor, PgPool, Postgres};
+
+pub struct UserIdentity;
+
+impl UserIdentity {
+    pub async fn user_id(pg_pool: &PgPool, issuer: &str, subject: &str) -> Result<Option<i32>> {
+        match sqlx::query!(
+            r#"
+            UPDATE
+                user_identities
+            SET
+                last_used_at = now()
+            WHERE
+                issuer = $1
+            AND
+                subject = $2
+            RETURNING
+                user_id
+            "#,
+            issuer,
+            subject
+        )
+        .fetch_optional(pg_pool)
+        .await
+        {
+            Ok(identity) => Ok(identity.map(|identity| identity.user_id)),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while retrieving the identity from the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn link<'e, E>(
+        executor: E,
+        user_id: i32,
+        issuer: &str,
+        subject: &str,
+        email: Option<&str>,
+    ) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        match sqlx::query!(
+            r#"
+            INSERT INTO user_identities
+                (user_id, issuer, subject, email)
+            VALUES
+                ($1, $2, $3, $4)
+            "#,
+            user_id,
+            issuer,
+            subject,
+            email
+        )
+        .execute(executor)
+        .await
+        {
+            Ok(_done) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from("Unable to insert identity in database."))
+            }
+        }
+    }
+}
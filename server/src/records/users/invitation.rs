@@ -0,0 +1,135 @@
+use crate::errors::field_error;
+use crate::records::users::{
+    is_valid_email,
+    role::UserRole,
+    session::{generate_token, hash_token},
+};
+use async_graphql::{Error, Result, SimpleObject};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+
+const LIFETIME_DAYS: i64 = 7;
+
+#[derive(sqlx::FromRow, SimpleObject, Debug, Deserialize, Serialize, Clone)]
+pub struct Invitation {
+    pub email: String,
+    pub role: UserRole,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl Invitation {
+    pub async fn find(pg_pool: &PgPool, token: &str) -> Result<Option<Self>> {
+        match sqlx::query_as!(
+            Self,
+            r#"
+            SELECT
+                email,
+                role AS "role: UserRole",
+                expires_at
+            FROM
+                invitations
+            WHERE
+                token_hash = $1
+            AND
+                accepted_at IS NULL
+            AND
+                expires_at > now()
+            "#,
+            hash_token(token)
+        )
+        .fetch_optional(pg_pool)
+        .await
+        {
+            Ok(invitation) => Ok(invitation),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while retrieving the invitation from the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn accept(transaction: &mut Transaction<'_, Postgres>, token: &str) -> Result<Self> {
+        match sqlx::query_as!(
+            Self,
+            r#"
+            UPDATE
+                invitations
+            SET
+                accepted_at = now()
+            WHERE
+                token_hash = $1
+            AND
+                accepted_at IS NULL
+            AND
+                expires_at > now()
+            RETURNING
+                email,
+                role AS "role: UserRole",
+                expires_at
+            "#,
+            hash_token(token)
+        )
+        .fetch_optional(&mut *transaction)
+        .await
+        {
+            Ok(Some(invitation)) => Ok(invitation),
+            Ok(None) => Err(Error::from("The invitation is invalid or has expired.")),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while retrieving the invitation from the database.",
+                ))
+            }
+        }
+    }
+}
+
+pub struct NewInvitation<'a> {
+    pub email: &'a str,
+    pub role: UserRole,
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl<'a> NewInvitation<'a> {
+    pub fn new(email: &'a str, role: UserRole) -> Result<Self> {
+        let email = email.trim();
+        if !is_valid_email(email)? {
+            return Err(field_error("email", "Email is not valid."));
+        }
+        Ok(Self {
+            email,
+            role,
+            token: generate_token(),
+            expires_at: Utc::now() + Duration::days(LIFETIME_DAYS),
+        })
+    }
+
+    pub async fn insert(&self, pg_pool: &PgPool, invited_by: i32) -> Result<()> {
+        match sqlx::query!(
+            r#"
+            INSERT INTO invitations
+                (email, role, token_hash, invited_by, expires_at)
+            VALUES
+                ($1, $2, $3, $4, $5)
+            "#,
+            self.email,
+            self.role as UserRole,
+            hash_token(&self.token),
+            invited_by,
+            self.expires_at
+        )
+        .execute(pg_pool)
+        .await
+        {
+            Ok(_done) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from("Unable to insert invitation in database."))
+            }
+        }
+    }
+}
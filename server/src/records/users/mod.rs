@@ -1,30 +1,74 @@
-use async_graphql::{Error, Result, SimpleObject};
-use bcrypt::{hash, verify, DEFAULT_COST};
+use crate::errors::{field_error, FieldErrors};
+use async_graphql::{Error, Result};
+use bcrypt::verify;
 use chrono::DateTime;
+use password::PasswordPolicy;
 use regex::Regex;
+use role::UserRole;
 use serde::{Deserialize, Serialize};
-use sqlx::PgPool;
+use sqlx::{Executor, PgPool, Postgres};
 
+pub mod api_token;
+pub mod author;
+pub mod data_export;
+pub mod email_verification;
+pub mod identity;
+pub mod invitation;
+pub mod password;
+pub mod password_reset;
+pub mod role;
 pub mod session;
+pub mod sign_in_challenge;
+pub mod two_factor;
 
-#[derive(sqlx::FromRow, SimpleObject, Debug, Deserialize, Serialize, Clone)]
+// Account data for the server only. It is never returned through the API;
+// the public side of a user is `Author`.
+#[derive(sqlx::FromRow, Debug, Deserialize, Serialize, Clone)]
 pub struct SimpleUser {
     pub id: i32,
     pub email: String,
     pub name: String,
+    #[serde(skip_serializing)]
     pub password: String,
+    pub role: UserRole,
     pub date: DateTime<chrono::Utc>,
+    pub email_verified_at: Option<DateTime<chrono::Utc>>,
 }
 
 impl<'a> SimpleUser {
-    pub fn unwrap_user_session(maybe_user: Option<SimpleUser>) -> Result<SimpleUser> {
-        match maybe_user {
-            Some(user) => Ok(user),
-            None => Err(Error::from("The user session doesn't exist.")),
+    pub async fn find_by_email(pg_pool: &PgPool, email: &'a str) -> Result<Option<Self>> {
+        match sqlx::query_as!(
+            Self,
+            r#"
+                SELECT
+                    users.id,
+                    users.email,
+                    users.name,
+                    users.password,
+                    users.role AS "role: UserRole",
+                    users.date,
+                    users.email_verified_at
+                FROM
+                    users
+                WHERE
+                    lower(email) = lower($1)
+            "#,
+            email
+        )
+        .fetch_optional(pg_pool)
+        .await
+        {
+            Ok(maybe_user) => Ok(maybe_user),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while retrieving the user from the database.",
+                ))
+            }
         }
     }
 
-    pub async fn from_email(pg_pool: &PgPool, email: &'a str) -> Result<Self> {
+    pub async fn get(pg_pool: &PgPool, user_id: i32) -> Result<Option<Self>> {
         match sqlx::query_as!(
             Self,
             r#"
@@ -33,23 +77,22 @@ impl<'a> SimpleUser {
                     users.email,
                     users.name,
                     users.password,
-                    users.date
+                    users.role AS "role: UserRole",
+                    users.date,
+                    users.email_verified_at
                 FROM
                     users
                 WHERE
-                    email = $1
+                    id = $1
             "#,
-            email
+            user_id
         )
         .fetch_optional(pg_pool)
         .await
         {
-            Ok(maybe_user) => match maybe_user {
-                Some(user) => Ok(user),
-                None => Err(Error::from("The email and password combination failed.")),
-            },
+            Ok(maybe_user) => Ok(maybe_user),
             Err(error) => {
-                println!("{}", error.to_string());
+                println!("{}", error);
                 Err(Error::from(
                     "An error occured while retrieving the user from the database.",
                 ))
@@ -64,29 +107,41 @@ impl<'a> SimpleUser {
         match sqlx::query_as!(
             Self,
             r#"
+                WITH session AS (
+                    UPDATE
+                        user_sessions
+                    SET
+                        last_seen_at = now()
+                    WHERE
+                        token_hash = $1
+                    AND
+                        expires_at > now()
+                    RETURNING
+                        user_id
+                )
                 SELECT
-                    users.id,
-                    users.email,
-                    users.name,
-                    users.password,
-                    users.date
+                    users.id AS "id!",
+                    users.email AS "email!",
+                    users.name AS "name!",
+                    users.password AS "password!",
+                    users.role AS "role!: UserRole",
+                    users.date AS "date!",
+                    users.email_verified_at
                 FROM
                     users
                 INNER JOIN
-                    user_sessions
+                    session
                 ON
-                    users.id = user_sessions.user_id
-                WHERE
-                    user_sessions.token = $1
+                    users.id = session.user_id
             "#,
-            session_token
+            session::hash_token(session_token)
         )
         .fetch_optional(pg_pool)
         .await
         {
             Ok(maybe_user) => Ok(maybe_user),
             Err(error) => {
-                println!("{}", error.to_string());
+                println!("{}", error);
                 Err(Error::from(
                     "An error occured while retrieving the user from the database.",
                 ))
@@ -94,11 +149,185 @@ impl<'a> SimpleUser {
         }
     }
 
+    pub async fn set_role(pg_pool: &PgPool, user_id: i32, role: UserRole) -> Result<()> {
+        match sqlx::query!(
+            r#"
+                UPDATE
+                    users
+                SET
+                    role = $1
+                WHERE
+                    id = $2
+                AND (
+                    $1::user_role = 'admin'
+                    OR role <> 'admin'
+                    OR EXISTS (
+                        SELECT 1 FROM users AS other WHERE other.role = 'admin' AND other.id <> $2
+                    )
+                )
+            "#,
+            role as UserRole,
+            user_id
+        )
+        .execute(pg_pool)
+        .await
+        {
+            Ok(done) if done.rows_affected() == 0 => match Self::get(pg_pool, user_id).await? {
+                Some(_user) => Err(Error::from(
+                    "The blog needs at least one admin. Make someone else an admin first.",
+                )),
+                None => Err(Error::from("The user doesn't exist.")),
+            },
+            Ok(_done) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while updating the user role in the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn set_password<'e, E>(executor: E, user_id: i32, password: &str) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        let hashed_password = password::hash(password)?;
+        match sqlx::query!(
+            r#"
+                UPDATE
+                    users
+                SET
+                    password = $1
+                WHERE
+                    id = $2
+            "#,
+            hashed_password,
+            user_id
+        )
+        .execute(executor)
+        .await
+        {
+            Ok(done) if done.rows_affected() == 0 => Err(Error::from("The user doesn't exist.")),
+            Ok(_done) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while updating the user password in the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn set_email<'e, E>(executor: E, user_id: i32, email: &str) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        match sqlx::query!(
+            r#"
+                UPDATE
+                    users
+                SET
+                    email = $1,
+                    email_verified_at = NULL
+                WHERE
+                    id = $2
+            "#,
+            email,
+            user_id
+        )
+        .execute(executor)
+        .await
+        {
+            Ok(done) if done.rows_affected() == 0 => Err(Error::from("The user doesn't exist.")),
+            Ok(_done) => Ok(()),
+            Err(sqlx::Error::Database(error)) if error.code().as_deref() == Some("23505") => Err(
+                field_error("newEmail", "There is already an account with this email."),
+            ),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while updating the user email in the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn mark_email_verified<'e, E>(executor: E, user_id: i32) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        match sqlx::query!(
+            r#"
+                UPDATE
+                    users
+                SET
+                    email_verified_at = COALESCE(email_verified_at, now())
+                WHERE
+                    id = $1
+            "#,
+            user_id
+        )
+        .execute(executor)
+        .await
+        {
+            Ok(done) if done.rows_affected() == 0 => Err(Error::from("The user doesn't exist.")),
+            Ok(_done) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while verifying the user email in the database.",
+                ))
+            }
+        }
+    }
+
+    // Sessions, tokens and the rest of the account go with the row. Posts keep
+    // existing without an author unless they were reassigned first.
+    pub async fn delete<'e, E>(executor: E, user_id: i32) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        match sqlx::query!(
+            r#"
+                DELETE FROM
+                    users
+                WHERE
+                    id = $1
+                AND (
+                    role <> 'admin'
+                    OR EXISTS (
+                        SELECT 1 FROM users AS other WHERE other.role = 'admin' AND other.id <> $1
+                    )
+                )
+            "#,
+            user_id
+        )
+        .execute(executor)
+        .await
+        {
+            Ok(done) if done.rows_affected() == 0 => Err(Error::from(
+                "The only admin can't delete their account. Make someone else an admin first.",
+            )),
+            Ok(_done) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while deleting the user from the database.",
+                ))
+            }
+        }
+    }
+
+    pub fn email_verified(&self) -> bool {
+        self.email_verified_at.is_some()
+    }
+
     pub async fn password_matches(&self, password_to_test: &'a str) -> Result<bool> {
         match verify(password_to_test, &self.password) {
             Ok(matches) => Ok(matches),
             Err(error) => {
-                println!("{}", error.to_string());
+                println!("{}", error);
                 Err(Error::from(
                     "We were unable compare the password with our saved password.",
                 ))
@@ -107,81 +336,128 @@ impl<'a> SimpleUser {
     }
 }
 
+pub fn is_valid_email(email: &str) -> Result<bool> {
+    match Regex::new(r"(^[a-zA-Z0-9_.+-]+@[a-zA-Z0-9-]+\.[a-zA-Z0-9-.]+$)") {
+        Ok(re) => Ok(re.is_match(email)),
+        Err(error) => {
+            println!("{}", error);
+            Err(Error::from("Email regex could not be compiled."))
+        }
+    }
+}
+
 #[derive(sqlx::FromRow, Debug, Deserialize, Serialize)]
 pub struct NewUser<'a> {
     pub email: &'a str,
     pub name: &'a str,
     pub password: String,
+    pub role: UserRole,
 }
 
 impl<'a> NewUser<'a> {
-    pub fn new(email: &'a str, name: &'a str, password: &'a str) -> Result<Self> {
-        let re = match Regex::new(r"(^[a-zA-Z0-9_.+-]+@[a-zA-Z0-9-]+\.[a-zA-Z0-9-.]+$)") {
-            Ok(re) => re,
-            Err(error) => {
-                println!("{}", error.to_string());
-                return Err(Error::from("Email regex could not be compiled."));
-            }
-        };
-
-        if !re.is_match(email) {
-            return Err(Error::from("Email is not valid."));
+    pub fn new(email: &'a str, name: &'a str, password: &'a str, role: UserRole) -> Result<Self> {
+        let mut errors = FieldErrors::default();
+        if !is_valid_email(email)? {
+            errors.add("email", "Email is not valid.");
         }
-
-        let re = match Regex::new(r"(^[a-zA-Z0-9]{8,}$)") {
-            Ok(re) => re,
-            Err(error) => {
-                println!("{}", error.to_string());
-                return Err(Error::from("Password regex could not be compiled."));
-            }
-        };
-
-        if !re.is_match(password) {
-            return Err(Error::from("Password is not secure enough."));
+        if let Err(message) = PasswordPolicy::from_env().check(password, &[email, name]) {
+            errors.add("password", message);
         }
+        errors.into_result()?;
 
-        let hashed_password = match hash(&password, DEFAULT_COST) {
-            Ok(hashed) => hashed,
-            Err(error) => {
-                println!("{}", error.to_string());
-                return Err(Error::from("Could not hash password."));
-            }
-        };
+        Ok(NewUser {
+            email,
+            name,
+            password: password::hash(password)?,
+            role,
+        })
+    }
 
+    // Accounts created through single sign-on get a random password nobody knows,
+    // so they can only sign in through the identity provider until it is reset.
+    pub fn external(email: &'a str, name: &'a str, role: UserRole) -> Result<Self> {
         Ok(NewUser {
             email,
             name,
-            password: hashed_password,
+            password: password::hash(&session::generate_token())?,
+            role,
         })
     }
 
-    pub async fn insert(&self, pg_pool: &PgPool) -> Result<SimpleUser> {
+    pub async fn insert<'e, E>(&self, executor: E) -> Result<SimpleUser>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
         match sqlx::query_as!(
             SimpleUser,
             r#"
                 INSERT INTO users
-                    (email, name, password)
+                    (email, name, password, role)
                 VALUES
-                    ($1, $2, $3)
+                    ($1, $2, $3, $4)
                 RETURNING
                     id,
                     email,
                     name,
                     password,
-                    date
+                    role AS "role: UserRole",
+                    date,
+                    email_verified_at
             "#,
             &self.email,
             &self.name,
-            &self.password
+            &self.password,
+            self.role as UserRole
         )
-        .fetch_one(pg_pool)
+        .fetch_one(executor)
         .await
         {
             Ok(user) => Ok(user),
+            Err(sqlx::Error::Database(error)) if error.code().as_deref() == Some("23505") => Err(
+                field_error("email", "There is already an account with this email."),
+            ),
             Err(error) => {
-                println!("{}", error.to_string());
+                println!("{}", error);
                 Err(Error::from("Unable to insert user in database."))
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{role::UserRole, SimpleUser};
+    use crate::test_support::TestDatabase;
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL, run with --ignored"]
+    async fn keeps_at_least_one_admin() {
+        let database = TestDatabase::new().await;
+        let pg_pool = &database.pg_pool;
+        let jane = database.insert_user("jane@example.com", "Jane").await;
+        let sam = database.insert_user("sam@example.com", "Sam").await;
+        SimpleUser::set_role(pg_pool, jane, UserRole::Admin)
+            .await
+            .unwrap();
+
+        let error = SimpleUser::set_role(pg_pool, jane, UserRole::Editor)
+            .await
+            .unwrap_err();
+        assert!(error.message.contains("at least one admin"));
+        let error = SimpleUser::set_role(pg_pool, 0, UserRole::Editor)
+            .await
+            .unwrap_err();
+        assert_eq!(error.message, "The user doesn't exist.");
+
+        SimpleUser::set_role(pg_pool, sam, UserRole::Admin)
+            .await
+            .unwrap();
+        SimpleUser::set_role(pg_pool, jane, UserRole::Editor)
+            .await
+            .unwrap();
+        let jane = SimpleUser::get(pg_pool, jane).await.unwrap().unwrap();
+        assert_eq!(jane.role, UserRole::Editor);
+
+        database.drop().await;
+    }
+}
@@ -0,0 +1,227 @@
+use async_graphql::{Error, Result};
+use bcrypt::{hash as bcrypt_hash, DEFAULT_COST};
+use std::env;
+
+const COMMON_PASSWORDS: &str = include_str!("common_passwords.txt");
+const DEFAULT_MIN_LENGTH: usize = 8;
+const DEFAULT_MIN_STRENGTH: u8 = 2;
+// bcrypt silently ignores everything after the first 72 bytes.
+const MAX_BYTES: usize = 72;
+
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub min_strength: u8,
+}
+
+impl PasswordPolicy {
+    pub fn from_env() -> Self {
+        let min_length = env::var("PASSWORD_MIN_LENGTH")
+            .ok()
+            .and_then(|length| length.parse().ok())
+            .unwrap_or(DEFAULT_MIN_LENGTH);
+        let min_strength = env::var("PASSWORD_MIN_STRENGTH")
+            .ok()
+            .and_then(|strength| strength.parse().ok())
+            .unwrap_or(DEFAULT_MIN_STRENGTH);
+        Self {
+            min_length,
+            min_strength: min_strength.min(4),
+        }
+    }
+
+    pub fn check(&self, password: &str, user_inputs: &[&str]) -> std::result::Result<(), String> {
+        if password.chars().count() < self.min_length {
+            return Err(format!(
+                "Password must be at least {} characters long.",
+                self.min_length
+            ));
+        }
+        if password.len() > MAX_BYTES {
+            return Err(format!(
+                "Password must be at most {} bytes long.",
+                MAX_BYTES
+            ));
+        }
+        if is_common(&password.to_lowercase()) {
+            return Err(String::from("This is one of the most common passwords."));
+        }
+        if strength(password, user_inputs) < self.min_strength {
+            return Err(String::from(
+                "Password is too easy to guess. Try a longer passphrase or mix in other kinds of characters.",
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn is_common(password: &str) -> bool {
+    COMMON_PASSWORDS.lines().any(|common| common == password)
+}
+
+fn unleet(password: &str) -> String {
+    password
+        .chars()
+        .map(|character| match character {
+            '0' => 'o',
+            '1' | '!' => 'i',
+            '3' => 'e',
+            '4' | '@' => 'a',
+            '5' | '$' => 's',
+            '7' => 't',
+            other => other,
+        })
+        .collect()
+}
+
+fn charset_size(password: &str) -> f64 {
+    let mut size = 0;
+    if password
+        .chars()
+        .any(|character| character.is_ascii_lowercase())
+    {
+        size += 26;
+    }
+    if password
+        .chars()
+        .any(|character| character.is_ascii_uppercase())
+    {
+        size += 26;
+    }
+    if password.chars().any(|character| character.is_ascii_digit()) {
+        size += 10;
+    }
+    if password.chars().any(|character| character == ' ') {
+        size += 1;
+    }
+    if password
+        .chars()
+        .any(|character| character.is_ascii_punctuation())
+    {
+        size += 33;
+    }
+    if !password.is_ascii() {
+        size += 100;
+    }
+    size.max(1) as f64
+}
+
+fn effective_length(password: &str) -> f64 {
+    let mut length = 0.0;
+    let mut previous: Option<char> = None;
+    for character in password.chars() {
+        length += match previous {
+            Some(previous) if previous == character => 0.2,
+            Some(previous) if (previous as i64 - character as i64).abs() == 1 => 0.3,
+            _ => 1.0,
+        };
+        previous = Some(character);
+    }
+    length
+}
+
+fn strip_common(password: &str) -> (String, f64) {
+    let mut remaining = String::from(password);
+    let mut bits = 0.0;
+    for common in COMMON_PASSWORDS.lines().filter(|common| common.len() >= 4) {
+        if remaining.contains(common) {
+            remaining = remaining.replacen(common, "", 1);
+            bits += 6.0;
+        }
+    }
+    (remaining, bits)
+}
+
+// A rough, zxcvbn-like estimate from 0 (trivial) to 4 (very strong). Common
+// passwords and the user's own details hidden inside the password barely count.
+pub fn strength(password: &str, user_inputs: &[&str]) -> u8 {
+    let lowercase = password.to_lowercase();
+    if is_common(&lowercase) {
+        return 0;
+    }
+
+    let (mut remaining, mut bits) = strip_common(&lowercase);
+    let (unleeted, unleeted_bits) = strip_common(&unleet(&remaining));
+    if unleeted_bits > 0.0 {
+        remaining = unleeted;
+        bits += unleeted_bits;
+    }
+    for input in user_inputs {
+        let input = input.to_lowercase();
+        for part in input
+            .split(|character: char| !character.is_alphanumeric())
+            .filter(|part| part.len() >= 3)
+        {
+            if remaining.contains(part) {
+                remaining = remaining.replacen(part, "", 1);
+                bits += 4.0;
+            }
+        }
+    }
+    bits += effective_length(&remaining) * charset_size(password).log2();
+
+    match bits {
+        bits if bits < 25.0 => 0,
+        bits if bits < 35.0 => 1,
+        bits if bits < 50.0 => 2,
+        bits if bits < 65.0 => 3,
+        _ => 4,
+    }
+}
+
+pub fn hash(password: &str) -> Result<String> {
+    match bcrypt_hash(password, DEFAULT_COST) {
+        Ok(hashed) => Ok(hashed),
+        Err(error) => {
+            println!("{}", error);
+            Err(Error::from("Could not hash password."))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> PasswordPolicy {
+        PasswordPolicy {
+            min_length: DEFAULT_MIN_LENGTH,
+            min_strength: DEFAULT_MIN_STRENGTH,
+        }
+    }
+
+    #[test]
+    fn rejects_short_and_overlong_passwords() {
+        assert!(policy().check("tugboat", &[]).is_err());
+        assert!(policy().check(&"violet tugboat ".repeat(5), &[]).is_err());
+    }
+
+    #[test]
+    fn rejects_repeated_characters() {
+        assert_eq!(strength("aaaaaaaa", &[]), 0);
+        assert!(policy().check("aaaaaaaa", &[]).is_err());
+    }
+
+    #[test]
+    fn rejects_common_passwords_and_their_variations() {
+        assert_eq!(
+            policy().check("password1", &[]),
+            Err(String::from("This is one of the most common passwords."))
+        );
+        assert!(policy().check("P@ssw0rd2021", &[]).is_err());
+    }
+
+    #[test]
+    fn rejects_passwords_built_from_the_email_or_name() {
+        let email = "margaret.hamilton@example.com";
+        let name = "Margaret Hamilton";
+        assert!(policy().check("margarethamilton1", &[]).is_ok());
+        assert!(policy().check("margarethamilton1", &[email, name]).is_err());
+        assert!(policy().check("hamiltonexample", &[email]).is_err());
+    }
+
+    #[test]
+    fn accepts_long_passphrases() {
+        assert!(policy().check("violet tugboat anthem", &[]).is_ok());
+        assert!(strength("violet tugboat anthem drifting north", &[]) >= 3);
+    }
+}
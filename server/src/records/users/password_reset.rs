@@ -0,0 +1,108 @@
+use crate::records::users::session::{generate_token, hash_token};
+use async_graphql::{Error, Result};
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{PgPool, Postgres, Transaction};
+
+const LIFETIME_MINUTES: i64 = 60;
+
+pub struct NewPasswordReset {
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl NewPasswordReset {
+    pub fn make() -> Self {
+        Self {
+            token: generate_token(),
+            expires_at: Utc::now() + Duration::minutes(LIFETIME_MINUTES),
+        }
+    }
+
+    pub async fn insert(&self, pg_pool: &PgPool, user_id: i32) -> Result<()> {
+        match sqlx::query!(
+            r#"
+            INSERT INTO password_resets
+                (user_id, token_hash, expires_at)
+            VALUES
+                ($1, $2, $3)
+            "#,
+            user_id,
+            hash_token(&self.token),
+            self.expires_at
+        )
+        .execute(pg_pool)
+        .await
+        {
+            Ok(_done) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from("Unable to insert password reset in database."))
+            }
+        }
+    }
+}
+
+pub struct PasswordReset;
+
+impl PasswordReset {
+    pub async fn consume(transaction: &mut Transaction<'_, Postgres>, token: &str) -> Result<i32> {
+        let user_id = match sqlx::query!(
+            r#"
+            UPDATE
+                password_resets
+            SET
+                used_at = now()
+            WHERE
+                token_hash = $1
+            AND
+                used_at IS NULL
+            AND
+                expires_at > now()
+            RETURNING
+                user_id
+            "#,
+            hash_token(token)
+        )
+        .fetch_optional(&mut *transaction)
+        .await
+        {
+            Ok(Some(reset)) => reset.user_id,
+            Ok(None) => {
+                return Err(Error::from(
+                    "The password reset link is invalid or has expired.",
+                ))
+            }
+            Err(error) => {
+                println!("{}", error);
+                return Err(Error::from(
+                    "An error occured while retrieving the password reset from the database.",
+                ));
+            }
+        };
+
+        match sqlx::query!(
+            r#"
+            UPDATE
+                password_resets
+            SET
+                used_at = now()
+            WHERE
+                user_id = $1
+            AND
+                used_at IS NULL
+            "#,
+            user_id
+        )
+        .execute(&mut *transaction)
+        .await
+        {
+            Ok(_done) => Ok(user_id),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while updating the password resets in the database.",
+                ))
+            }
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use crate::records::posts::PostStatus;
+use async_graphql::Enum;
+use serde::{Deserialize, Serialize};
+
+#[derive(sqlx::Type, Enum, Copy, Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[sqlx(type_name = "user_role", rename_all = "lowercase")]
+pub enum UserRole {
+    Admin,
+    Editor,
+    Author,
+    Contributor,
+}
+
+impl UserRole {
+    pub fn can_manage_users(&self) -> bool {
+        matches!(self, UserRole::Admin)
+    }
+
+    pub fn can_edit_others_posts(&self) -> bool {
+        matches!(self, UserRole::Admin | UserRole::Editor)
+    }
+
+    pub fn can_manage_taxonomy(&self) -> bool {
+        matches!(self, UserRole::Admin | UserRole::Editor)
+    }
+
+    pub fn can_set_status(&self, status: PostStatus) -> bool {
+        match self {
+            UserRole::Contributor => matches!(status, PostStatus::Draft | PostStatus::Pending),
+            _ => true,
+        }
+    }
+}
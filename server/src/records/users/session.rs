@@ -1,51 +1,242 @@
-use async_graphql::{Error, Result};
+use async_graphql::{Error, Result, SimpleObject};
+use chrono::{DateTime, Duration, Utc};
 use rand::{distributions::Alphanumeric, thread_rng, Rng};
 use serde::{Deserialize, Serialize};
-use sqlx::{postgres::PgQueryResult, PgPool};
+use sha2::{Digest, Sha256};
+use sqlx::{postgres::PgQueryResult, Executor, PgPool, Postgres};
+use std::env;
+
+const DEFAULT_LIFETIME_DAYS: i64 = 30;
+
+pub fn generate_token() -> String {
+    thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(50)
+        .map(char::from)
+        .collect()
+}
+
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+#[derive(sqlx::FromRow, SimpleObject, Debug, Deserialize, Serialize, Clone)]
+pub struct Session {
+    pub id: i32,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub current: bool,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl Session {
+    pub async fn for_user(pg_pool: &PgPool, user_id: i32, token: &str) -> Result<Vec<Self>> {
+        match sqlx::query_as!(
+            Self,
+            r#"
+            SELECT
+                id,
+                user_agent,
+                ip_address,
+                token_hash = $2 AS "current!",
+                date AS created_at,
+                last_seen_at,
+                expires_at
+            FROM
+                user_sessions
+            WHERE
+                user_id = $1
+            AND
+                expires_at > now()
+            ORDER BY
+                last_seen_at DESC
+            "#,
+            user_id,
+            hash_token(token)
+        )
+        .fetch_all(pg_pool)
+        .await
+        {
+            Ok(sessions) => Ok(sessions),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while retrieving the sessions from the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn revoke(pg_pool: &PgPool, user_id: i32, session_id: i32) -> Result<()> {
+        match sqlx::query!(
+            r#"
+            DELETE FROM
+                user_sessions
+            WHERE
+                id = $1
+            AND
+                user_id = $2
+            "#,
+            session_id,
+            user_id
+        )
+        .execute(pg_pool)
+        .await
+        {
+            Ok(done) if done.rows_affected() == 0 => Err(Error::from("The session doesn't exist.")),
+            Ok(_done) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while deleting the session from the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn revoke_token(pg_pool: &PgPool, token: &str) -> Result<()> {
+        match sqlx::query!(
+            r#"
+            DELETE FROM
+                user_sessions
+            WHERE
+                token_hash = $1
+            "#,
+            hash_token(token)
+        )
+        .execute(pg_pool)
+        .await
+        {
+            Ok(_done) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while deleting the session from the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn revoke_others<'e, E>(executor: E, user_id: i32, token: &str) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        match sqlx::query!(
+            r#"
+            DELETE FROM
+                user_sessions
+            WHERE
+                user_id = $1
+            AND
+                token_hash <> $2
+            "#,
+            user_id,
+            hash_token(token)
+        )
+        .execute(executor)
+        .await
+        {
+            Ok(_done) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while deleting the sessions from the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn revoke_all<'e, E>(executor: E, user_id: i32) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        match sqlx::query!(
+            r#"
+            DELETE FROM
+                user_sessions
+            WHERE
+                user_id = $1
+            "#,
+            user_id
+        )
+        .execute(executor)
+        .await
+        {
+            Ok(_done) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while deleting the sessions from the database.",
+                ))
+            }
+        }
+    }
+}
 
 #[derive(sqlx::FromRow, Debug, Deserialize, Serialize)]
 pub struct NewSession {
     pub token: String,
+    pub expires_at: DateTime<Utc>,
 }
 
-impl<'a> NewSession {
+impl NewSession {
     pub fn get_token(&self) -> String {
         self.token.clone()
     }
 
-    fn generate_token() -> String {
-        thread_rng()
-            .sample_iter(&Alphanumeric)
-            .take(50)
-            .map(char::from)
-            .collect()
+    fn lifetime() -> Duration {
+        let days = env::var("SESSION_LIFETIME_DAYS")
+            .ok()
+            .and_then(|days| days.parse().ok())
+            .unwrap_or(DEFAULT_LIFETIME_DAYS);
+        Duration::days(days)
     }
 
     pub fn make() -> Self {
         NewSession {
-            token: Self::generate_token(),
+            token: generate_token(),
+            expires_at: Utc::now() + Self::lifetime(),
         }
     }
 
-    pub async fn insert(&self, pg_pool: &PgPool, user_id: i32) -> Result<PgQueryResult> {
+    pub async fn insert(
+        &self,
+        pg_pool: &PgPool,
+        user_id: i32,
+        user_agent: Option<&str>,
+        ip_address: Option<&str>,
+    ) -> Result<PgQueryResult> {
         match sqlx::query!(
             r#"
             INSERT INTO user_sessions
-                (user_id, token)
+                (user_id, token_hash, expires_at, user_agent, ip_address)
             VALUES
-                ($1, $2)
+                ($1, $2, $3, $4, $5)
             "#,
             user_id,
-            &self.token
+            hash_token(&self.token),
+            self.expires_at,
+            user_agent,
+            ip_address
         )
         .execute(pg_pool)
         .await
         {
             Ok(done) => Ok(done),
             Err(error) => {
-                println!("{}", error.to_string());
+                println!("{}", error);
                 Err(Error::from("Unable to insert user session in database."))
             }
         }
     }
 }
+
+// Accounts with two-factor authentication get a challenge instead of a token,
+// which completeSignIn exchanges for a token once a code is provided.
+#[derive(SimpleObject, Debug, Deserialize, Serialize, Clone)]
+pub struct SignInResult {
+    pub token: Option<String>,
+    pub challenge: Option<String>,
+}
@@ -0,0 +1,140 @@
+use crate::records::users::session::{generate_token, hash_token};
+use async_graphql::{Error, Result};
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+
+const LIFETIME_MINUTES: i64 = 5;
+const MAX_FAILURES: i32 = 5;
+
+pub struct NewSignInChallenge {
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl NewSignInChallenge {
+    pub fn make() -> Self {
+        Self {
+            token: generate_token(),
+            expires_at: Utc::now() + Duration::minutes(LIFETIME_MINUTES),
+        }
+    }
+
+    pub async fn insert(&self, pg_pool: &PgPool, user_id: i32) -> Result<()> {
+        match sqlx::query!(
+            r#"
+            INSERT INTO sign_in_challenges
+                (user_id, token_hash, expires_at)
+            VALUES
+                ($1, $2, $3)
+            "#,
+            user_id,
+            hash_token(&self.token),
+            self.expires_at
+        )
+        .execute(pg_pool)
+        .await
+        {
+            Ok(_done) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "Unable to insert sign in challenge in database.",
+                ))
+            }
+        }
+    }
+}
+
+pub struct SignInChallenge {
+    pub user_id: i32,
+    pub email: String,
+}
+
+impl SignInChallenge {
+    pub async fn find(pg_pool: &PgPool, token: &str) -> Result<Self> {
+        match sqlx::query_as!(
+            Self,
+            r#"
+            SELECT
+                sign_in_challenges.user_id,
+                users.email
+            FROM
+                sign_in_challenges
+            INNER JOIN
+                users ON users.id = sign_in_challenges.user_id
+            WHERE
+                sign_in_challenges.token_hash = $1
+            AND
+                sign_in_challenges.expires_at > now()
+            AND
+                sign_in_challenges.failures < $2
+            "#,
+            hash_token(token),
+            MAX_FAILURES
+        )
+        .fetch_optional(pg_pool)
+        .await
+        {
+            Ok(Some(challenge)) => Ok(challenge),
+            Ok(None) => Err(Error::from(
+                "The sign in challenge is invalid or has expired. Sign in again.",
+            )),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while retrieving the sign in challenge from the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn record_failure(pg_pool: &PgPool, token: &str) -> Result<()> {
+        match sqlx::query!(
+            r#"
+            UPDATE
+                sign_in_challenges
+            SET
+                failures = failures + 1
+            WHERE
+                token_hash = $1
+            "#,
+            hash_token(token)
+        )
+        .execute(pg_pool)
+        .await
+        {
+            Ok(_done) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while updating the sign in challenge in the database.",
+                ))
+            }
+        }
+    }
+
+    // Deletes the challenge so it can only be completed once. Returns false
+    // when a concurrent request already completed it.
+    pub async fn consume(pg_pool: &PgPool, token: &str) -> Result<bool> {
+        match sqlx::query!(
+            r#"
+            DELETE FROM
+                sign_in_challenges
+            WHERE
+                token_hash = $1
+            "#,
+            hash_token(token)
+        )
+        .execute(pg_pool)
+        .await
+        {
+            Ok(done) => Ok(done.rows_affected() == 1),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while deleting the sign in challenge from the database.",
+                ))
+            }
+        }
+    }
+}
@@ -0,0 +1,514 @@
+use crate::records::users::session::hash_token;
+use async_graphql::{Error, Result, SimpleObject};
+use base32::Alphabet;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac, NewMac};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use qrcode::{render::svg, QrCode};
+use rand::{distributions::Alphanumeric, thread_rng, Rng};
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sqlx::{Executor, PgPool, Postgres, Transaction};
+
+const SECRET_ALPHABET: Alphabet = Alphabet::RFC4648 { padding: false };
+const SECRET_BYTES: usize = 20;
+const DIGITS: u32 = 6;
+const PERIOD_SECONDS: i64 = 30;
+// Accept the previous and next code as well to allow for clock drift.
+const ALLOWED_DRIFT_STEPS: i64 = 1;
+const RECOVERY_CODE_COUNT: usize = 10;
+
+pub fn generate_secret() -> String {
+    let bytes: Vec<u8> = (0..SECRET_BYTES).map(|_| thread_rng().gen()).collect();
+    base32::encode(SECRET_ALPHABET, &bytes)
+}
+
+// RFC 4226 HOTP with HMAC-SHA1, which is what authenticator apps expect.
+fn hotp(secret: &[u8], counter: u64) -> String {
+    let mut mac = Hmac::<Sha1>::new_varkey(secret).expect("HMAC accepts keys of any length");
+    mac.update(&counter.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+    format!(
+        "{:0width$}",
+        binary % 10u32.pow(DIGITS),
+        width = DIGITS as usize
+    )
+}
+
+// Returns the time step the code belongs to, so the caller can refuse to
+// accept the same code twice.
+pub fn verify_code(secret: &str, code: &str, now: DateTime<Utc>) -> Option<i64> {
+    let secret = base32::decode(SECRET_ALPHABET, secret)?;
+    let code: String = code
+        .chars()
+        .filter(|character| !character.is_whitespace())
+        .collect();
+    if code.len() != DIGITS as usize {
+        return None;
+    }
+    let current = now.timestamp() / PERIOD_SECONDS;
+    (current - ALLOWED_DRIFT_STEPS..=current + ALLOWED_DRIFT_STEPS)
+        .find(|step| hotp(&secret, *step as u64) == code)
+}
+
+pub fn otpauth_uri(issuer: &str, account: &str, secret: &str) -> String {
+    let issuer = utf8_percent_encode(issuer, NON_ALPHANUMERIC).to_string();
+    format!(
+        "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm=SHA1&digits={}&period={}",
+        issuer,
+        utf8_percent_encode(account, NON_ALPHANUMERIC),
+        secret,
+        issuer,
+        DIGITS,
+        PERIOD_SECONDS
+    )
+}
+
+pub fn qr_code_svg(data: &str) -> Result<String> {
+    match QrCode::new(data.as_bytes()) {
+        Ok(code) => Ok(code.render::<svg::Color>().min_dimensions(200, 200).build()),
+        Err(error) => {
+            println!("{}", error);
+            Err(Error::from("Unable to render the QR code."))
+        }
+    }
+}
+
+fn generate_recovery_code() -> String {
+    let code: String = thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(10)
+        .map(|byte| char::from(byte).to_ascii_lowercase())
+        .collect();
+    format!("{}-{}", &code[..5], &code[5..])
+}
+
+fn hash_recovery_code(code: &str) -> String {
+    let normalized: String = code
+        .chars()
+        .filter(|character| character.is_ascii_alphanumeric())
+        .map(|character| character.to_ascii_lowercase())
+        .collect();
+    hash_token(&normalized)
+}
+
+#[derive(SimpleObject, Debug, Deserialize, Serialize, Clone)]
+pub struct TwoFactorEnrollment {
+    pub secret: String,
+    pub otpauth_uri: String,
+    pub qr_code_svg: String,
+}
+
+#[derive(SimpleObject, Debug, Deserialize, Serialize, Clone)]
+pub struct TwoFactorStatus {
+    pub enabled_at: Option<DateTime<Utc>>,
+    pub recovery_codes_left: i64,
+}
+
+#[derive(sqlx::FromRow, Debug)]
+pub struct TwoFactor {
+    pub secret: Option<String>,
+    pub enabled_at: Option<DateTime<Utc>>,
+}
+
+impl TwoFactor {
+    pub async fn get(pg_pool: &PgPool, user_id: i32) -> Result<Self> {
+        match sqlx::query_as!(
+            Self,
+            r#"
+            SELECT
+                totp_secret AS secret,
+                totp_enabled_at AS enabled_at
+            FROM
+                users
+            WHERE
+                id = $1
+            "#,
+            user_id
+        )
+        .fetch_one(pg_pool)
+        .await
+        {
+            Ok(two_factor) => Ok(two_factor),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while retrieving the two-factor settings from the database.",
+                ))
+            }
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled_at.is_some()
+    }
+
+    pub async fn status(pg_pool: &PgPool, user_id: i32) -> Result<TwoFactorStatus> {
+        match sqlx::query_as!(
+            TwoFactorStatus,
+            r#"
+            SELECT
+                users.totp_enabled_at AS enabled_at,
+                COUNT(recovery_codes.id) FILTER (
+                    WHERE recovery_codes.used_at IS NULL
+                ) AS "recovery_codes_left!"
+            FROM
+                users
+            LEFT JOIN
+                recovery_codes ON recovery_codes.user_id = users.id
+            WHERE
+                users.id = $1
+            GROUP BY
+                users.id
+            "#,
+            user_id
+        )
+        .fetch_one(pg_pool)
+        .await
+        {
+            Ok(status) => Ok(status),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while retrieving the two-factor settings from the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn begin(pg_pool: &PgPool, user_id: i32, secret: &str) -> Result<()> {
+        match sqlx::query!(
+            r#"
+            UPDATE
+                users
+            SET
+                totp_secret = $2,
+                totp_last_step = NULL
+            WHERE
+                id = $1
+            AND
+                totp_enabled_at IS NULL
+            "#,
+            user_id,
+            secret
+        )
+        .execute(pg_pool)
+        .await
+        {
+            Ok(done) if done.rows_affected() == 0 => {
+                Err(Error::from("Two-factor authentication is already enabled."))
+            }
+            Ok(_done) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while updating the two-factor settings in the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn enable<'e, E>(executor: E, user_id: i32) -> Result<()>
+    where
+        E: Executor<'e, Database = Postgres>,
+    {
+        match sqlx::query!(
+            r#"
+            UPDATE
+                users
+            SET
+                totp_enabled_at = now()
+            WHERE
+                id = $1
+            AND
+                totp_secret IS NOT NULL
+            "#,
+            user_id
+        )
+        .execute(executor)
+        .await
+        {
+            Ok(_done) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while updating the two-factor settings in the database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn disable(transaction: &mut Transaction<'_, Postgres>, user_id: i32) -> Result<()> {
+        if let Err(error) = sqlx::query!(
+            r#"
+            UPDATE
+                users
+            SET
+                totp_secret = NULL,
+                totp_enabled_at = NULL,
+                totp_last_step = NULL
+            WHERE
+                id = $1
+            "#,
+            user_id
+        )
+        .execute(&mut *transaction)
+        .await
+        {
+            println!("{}", error);
+            return Err(Error::from(
+                "An error occured while updating the two-factor settings in the database.",
+            ));
+        }
+
+        match sqlx::query!(
+            r#"
+            DELETE FROM
+                recovery_codes
+            WHERE
+                user_id = $1
+            "#,
+            user_id
+        )
+        .execute(&mut *transaction)
+        .await
+        {
+            Ok(_done) => Ok(()),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while deleting the recovery codes from the database.",
+                ))
+            }
+        }
+    }
+
+    // Records the time step as used. Returns false when this or a later code
+    // has already been accepted, which stops a code from being replayed.
+    pub async fn use_step(pg_pool: &PgPool, user_id: i32, step: i64) -> Result<bool> {
+        match sqlx::query!(
+            r#"
+            UPDATE
+                users
+            SET
+                totp_last_step = $2
+            WHERE
+                id = $1
+            AND
+                (totp_last_step IS NULL OR totp_last_step < $2)
+            "#,
+            user_id,
+            step
+        )
+        .execute(pg_pool)
+        .await
+        {
+            Ok(done) => Ok(done.rows_affected() == 1),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while updating the two-factor settings in the database.",
+                ))
+            }
+        }
+    }
+
+    // Accepts either a code from the authenticator app or an unused recovery code.
+    pub async fn check_code(&self, pg_pool: &PgPool, user_id: i32, code: &str) -> Result<bool> {
+        let secret = match &self.secret {
+            Some(secret) if self.enabled() => secret,
+            _ => return Ok(false),
+        };
+        match verify_code(secret, code, Utc::now()) {
+            Some(step) => Self::use_step(pg_pool, user_id, step).await,
+            None => RecoveryCode::consume(pg_pool, user_id, code).await,
+        }
+    }
+}
+
+pub struct RecoveryCode;
+
+impl RecoveryCode {
+    pub async fn replace(
+        transaction: &mut Transaction<'_, Postgres>,
+        user_id: i32,
+    ) -> Result<Vec<String>> {
+        if let Err(error) = sqlx::query!(
+            r#"
+            DELETE FROM
+                recovery_codes
+            WHERE
+                user_id = $1
+            "#,
+            user_id
+        )
+        .execute(&mut *transaction)
+        .await
+        {
+            println!("{}", error);
+            return Err(Error::from(
+                "An error occured while deleting the recovery codes from the database.",
+            ));
+        }
+
+        let codes: Vec<String> = (0..RECOVERY_CODE_COUNT)
+            .map(|_| generate_recovery_code())
+            .collect();
+        let hashes: Vec<String> = codes.iter().map(|code| hash_recovery_code(code)).collect();
+        match sqlx::query!(
+            r#"
+            INSERT INTO recovery_codes
+                (user_id, code_hash)
+            SELECT
+                $1, code_hash
+            FROM
+                UNNEST($2::varchar[]) AS code_hash
+            "#,
+            user_id,
+            &hashes
+        )
+        .execute(&mut *transaction)
+        .await
+        {
+            Ok(_done) => Ok(codes),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "Unable to insert the recovery codes in database.",
+                ))
+            }
+        }
+    }
+
+    pub async fn consume(pg_pool: &PgPool, user_id: i32, code: &str) -> Result<bool> {
+        match sqlx::query!(
+            r#"
+            UPDATE
+                recovery_codes
+            SET
+                used_at = now()
+            WHERE
+                user_id = $1
+            AND
+                code_hash = $2
+            AND
+                used_at IS NULL
+            "#,
+            user_id,
+            hash_recovery_code(code)
+        )
+        .execute(pg_pool)
+        .await
+        {
+            Ok(done) => Ok(done.rows_affected() == 1),
+            Err(error) => {
+                println!("{}", error);
+                Err(Error::from(
+                    "An error occured while updating the recovery codes in the database.",
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestDatabase;
+    use chrono::TimeZone;
+
+    // The shared secret of the RFC 4226 and RFC 6238 test vectors.
+    const RFC_SECRET: &[u8] = b"12345678901234567890";
+
+    fn rfc_secret() -> String {
+        base32::encode(SECRET_ALPHABET, RFC_SECRET)
+    }
+
+    fn code_at(step: i64) -> String {
+        hotp(RFC_SECRET, step as u64)
+    }
+
+    #[test]
+    fn matches_the_rfc_4226_hotp_vectors() {
+        let expected = [
+            "755224", "287082", "359152", "969429", "338314", "254676", "287922", "162583",
+            "399871", "520489",
+        ];
+        for (counter, code) in expected.iter().enumerate() {
+            assert_eq!(hotp(RFC_SECRET, counter as u64), *code);
+        }
+    }
+
+    // RFC 6238 lists eight digit SHA-1 codes; the last six are what we show.
+    #[test]
+    fn matches_the_rfc_6238_totp_vectors() {
+        let expected = [
+            (59, "94287082"),
+            (1_111_111_109, "07081804"),
+            (1_111_111_111, "14050471"),
+            (1_234_567_890, "89005924"),
+            (2_000_000_000, "69279037"),
+            (20_000_000_000, "65353130"),
+        ];
+        for (time, code) in expected.iter() {
+            let now = Utc.timestamp(*time, 0);
+            assert_eq!(
+                verify_code(&rfc_secret(), &code[2..], now),
+                Some(time / PERIOD_SECONDS)
+            );
+        }
+    }
+
+    #[test]
+    fn accepts_one_step_of_clock_drift() {
+        let now = Utc.timestamp(1_234_567_890, 0);
+        let step = now.timestamp() / PERIOD_SECONDS;
+        let secret = rfc_secret();
+        assert_eq!(
+            verify_code(&secret, &code_at(step - 1), now),
+            Some(step - 1)
+        );
+        assert_eq!(
+            verify_code(&secret, &code_at(step + 1), now),
+            Some(step + 1)
+        );
+        assert_eq!(verify_code(&secret, &code_at(step - 2), now), None);
+        assert_eq!(verify_code(&secret, &code_at(step + 2), now), None);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL, run with --ignored"]
+    async fn refuses_a_code_that_was_already_used() {
+        let database = TestDatabase::new().await;
+        let user_id = database.insert_user("reader@example.com", "Reader").await;
+        let secret = generate_secret();
+        TwoFactor::begin(&database.pg_pool, user_id, &secret)
+            .await
+            .unwrap();
+        TwoFactor::enable(&database.pg_pool, user_id).await.unwrap();
+        let two_factor = TwoFactor::get(&database.pg_pool, user_id).await.unwrap();
+
+        let step = Utc::now().timestamp() / PERIOD_SECONDS;
+        let code = hotp(
+            &base32::decode(SECRET_ALPHABET, &secret).unwrap(),
+            step as u64,
+        );
+        assert!(two_factor
+            .check_code(&database.pg_pool, user_id, &code)
+            .await
+            .unwrap());
+        assert!(!two_factor
+            .check_code(&database.pg_pool, user_id, &code)
+            .await
+            .unwrap());
+
+        database.drop().await;
+    }
+}
@@ -0,0 +1,218 @@
+use crate::records::posts::Post;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+const DEFAULT_INTERVAL_SECONDS: u64 = 60;
+const DEFAULT_TRASH_RETENTION_DAYS: i64 = 30;
+// A hundred years, well within what chrono can add to a date.
+const MAX_TRASH_RETENTION_DAYS: i64 = 36_500;
+
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum SchedulerEvent {
+    PostPublished(Post),
+    PostPurged(Post),
+}
+
+pub struct Scheduler {
+    pg_pool: PgPool,
+    clock: Arc<dyn Clock>,
+    interval: Duration,
+    // How long deleted posts stay in the trash before they are purged.
+    trash_retention: chrono::Duration,
+    events: broadcast::Sender<SchedulerEvent>,
+}
+
+impl Scheduler {
+    pub fn new(
+        pg_pool: PgPool,
+        clock: Arc<dyn Clock>,
+        interval: Duration,
+        trash_retention: chrono::Duration,
+    ) -> Self {
+        let (events, _receiver) = broadcast::channel(64);
+        Self {
+            pg_pool,
+            clock,
+            interval,
+            trash_retention,
+            events,
+        }
+    }
+
+    pub fn from_env(pg_pool: PgPool) -> Self {
+        Self::new(
+            pg_pool,
+            Arc::new(SystemClock),
+            interval(env::var("SCHEDULER_INTERVAL_SECONDS").ok().as_deref()),
+            trash_retention(env::var("TRASH_RETENTION_DAYS").ok().as_deref()),
+        )
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<SchedulerEvent> {
+        self.events.subscribe()
+    }
+
+    pub async fn tick(&self) -> async_graphql::Result<Vec<Post>> {
+        let published = Post::publish_due(&self.pg_pool, self.clock.now()).await?;
+        for post in &published {
+            // Nobody listening is fine, the posts are published either way.
+            let _ = self
+                .events
+                .send(SchedulerEvent::PostPublished(post.clone()));
+        }
+        Ok(published)
+    }
+
+    pub async fn purge_trash(&self) -> async_graphql::Result<Vec<Post>> {
+        let purged =
+            Post::purge_trashed_before(&self.pg_pool, self.clock.now() - self.trash_retention)
+                .await?;
+        for post in &purged {
+            let _ = self.events.send(SchedulerEvent::PostPurged(post.clone()));
+        }
+        Ok(purged)
+    }
+
+    pub fn spawn(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.interval);
+            loop {
+                interval.tick().await;
+                if let Err(error) = self.tick().await {
+                    println!("{}", error.message);
+                }
+                if let Err(error) = self.purge_trash().await {
+                    println!("{}", error.message);
+                }
+            }
+        })
+    }
+}
+
+// Tokio can't tick every zero seconds, so the interval is at least one.
+fn interval(seconds: Option<&str>) -> Duration {
+    let seconds = seconds
+        .and_then(|seconds| seconds.parse().ok())
+        .unwrap_or(DEFAULT_INTERVAL_SECONDS);
+    Duration::from_secs(seconds.max(1))
+}
+
+fn trash_retention(days: Option<&str>) -> chrono::Duration {
+    let days = days
+        .and_then(|days| days.parse().ok())
+        .unwrap_or(DEFAULT_TRASH_RETENTION_DAYS);
+    chrono::Duration::days(days.clamp(0, MAX_TRASH_RETENTION_DAYS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::records::posts::{NewPost, PostStatus, Viewer};
+    use crate::test_support::TestDatabase;
+    use chrono::Timelike;
+    use std::sync::Mutex;
+
+    struct FixedClock(Mutex<DateTime<Utc>>);
+
+    impl FixedClock {
+        fn advance(&self, by: chrono::Duration) {
+            let mut now = self.0.lock().unwrap();
+            *now = *now + by;
+        }
+    }
+
+    impl Clock for FixedClock {
+        fn now(&self) -> DateTime<Utc> {
+            *self.0.lock().unwrap()
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL, run with --ignored"]
+    async fn publishes_scheduled_posts_once_they_are_due() {
+        let database = TestDatabase::new().await;
+        let user_id = database.insert_user("author@example.com", "Author").await;
+        let publish_at = (Utc::now() + chrono::Duration::hours(1))
+            .with_nanosecond(0)
+            .unwrap();
+        let post = NewPost::new(
+            "scheduled",
+            "Scheduled",
+            "Text",
+            "Summary",
+            PostStatus::Scheduled,
+            Some(publish_at),
+            user_id,
+        )
+        .unwrap()
+        .insert(&database.pg_pool)
+        .await
+        .unwrap();
+
+        let clock = Arc::new(FixedClock(Mutex::new(Utc::now())));
+        let scheduler = Scheduler::new(
+            database.pg_pool.clone(),
+            clock.clone(),
+            Duration::from_secs(60),
+            chrono::Duration::days(30),
+        );
+        let mut events = scheduler.subscribe();
+
+        assert!(scheduler.tick().await.unwrap().is_empty());
+
+        clock.advance(chrono::Duration::hours(1) + chrono::Duration::minutes(1));
+        let published = scheduler.tick().await.unwrap();
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].id, post.id);
+        match events.try_recv() {
+            Ok(SchedulerEvent::PostPublished(event_post)) => assert_eq!(event_post.id, post.id),
+            _ => panic!("expected a PostPublished event"),
+        }
+
+        let stored = Post::get(&database.pg_pool, post.id, Viewer::everything())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.status, PostStatus::Published);
+        assert_eq!(stored.published_at, Some(publish_at));
+
+        database.drop().await;
+    }
+
+    #[test]
+    fn keeps_the_settings_within_bounds() {
+        assert_eq!(
+            interval(None),
+            Duration::from_secs(DEFAULT_INTERVAL_SECONDS)
+        );
+        assert_eq!(interval(Some("0")), Duration::from_secs(1));
+        assert_eq!(interval(Some("15")), Duration::from_secs(15));
+        assert_eq!(
+            interval(Some("soon")),
+            Duration::from_secs(DEFAULT_INTERVAL_SECONDS)
+        );
+        assert_eq!(trash_retention(Some("7")), chrono::Duration::days(7));
+        assert_eq!(trash_retention(Some("-1")), chrono::Duration::days(0));
+        assert_eq!(
+            trash_retention(Some("9223372036854775807")),
+            chrono::Duration::days(MAX_TRASH_RETENTION_DAYS)
+        );
+    }
+}
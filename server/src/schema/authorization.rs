@@ -1,37 +1,258 @@
+use crate::errors::{field_error, ApiError};
+use crate::oidc::{OidcClient, SingleSignOn};
 use crate::records::authorities::Authorities;
-use crate::records::users::{session::NewSession, NewUser, SimpleUser};
-use crate::AuthToken;
-use async_graphql::{Context, Error, Result};
+use crate::records::oidc_login::{NewOidcLogin, LIFETIME_MINUTES};
+use crate::records::sign_in_throttle::{SignInThrottle, ThrottlePolicy};
+use crate::records::users::{
+    api_token::{ApiScope, ApiToken, ApiTokenOwner, CreatedApiToken, NewApiToken, TOKEN_PREFIX},
+    role::UserRole,
+    session::{NewSession, Session, SignInResult},
+    sign_in_challenge::NewSignInChallenge,
+    two_factor::TwoFactor,
+    SimpleUser,
+};
+use crate::{AuthToken, ClientInfo};
+use async_graphql::{Context, Error, ErrorExtensions, Result};
+use chrono::{DateTime, Utc};
 use sqlx::PgPool;
+use std::sync::Arc;
 
-pub async fn get<'a>(ctx: &'a Context<'_>) -> Result<Option<Authorities>> {
+const SIGN_IN_FAILED: &str = "The email and password combination failed.";
+
+pub async fn get(ctx: &Context<'_>) -> Result<Option<Authorities>> {
     let pg_pool = ctx.data::<PgPool>()?;
     let token = ctx.data_opt::<AuthToken>();
     match token {
-        Some(token) => Authorities::get(&pg_pool, &token.0).await,
+        Some(token) => Authorities::get(pg_pool, &token.0).await,
         None => Ok(None),
     }
 }
 
-pub async fn sign_up<'a>(
-    ctx: &'a Context<'_>,
-    email: String,
-    name: String,
-    password: String,
-) -> Result<&'a str> {
+// Sessions carry every permission of the user, API tokens only their scopes.
+enum Credential {
+    Session,
+    ApiToken(ApiTokenOwner),
+}
+
+async fn authenticate(ctx: &Context<'_>) -> Result<Option<(SimpleUser, Credential)>> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let token = match ctx.data_opt::<AuthToken>() {
+        Some(token) => &token.0,
+        None => return Ok(None),
+    };
+    if !token.starts_with(TOKEN_PREFIX) {
+        let user = SimpleUser::from_session_token(pg_pool, token).await?;
+        return Ok(user.map(|user| (user, Credential::Session)));
+    }
+    match ApiToken::owner(pg_pool, token).await? {
+        Some(owner) => {
+            let user = SimpleUser::get(pg_pool, owner.user_id).await?;
+            Ok(user.map(|user| (user, Credential::ApiToken(owner))))
+        }
+        None => Ok(None),
+    }
+}
+
+// Reads fall back to what anonymous visitors see when the token lacks the scope.
+pub async fn current_user(ctx: &Context<'_>, scope: ApiScope) -> Result<Option<SimpleUser>> {
+    match authenticate(ctx).await? {
+        Some((_user, Credential::ApiToken(owner))) if !owner.has_scope(scope) => Ok(None),
+        Some((user, _credential)) => Ok(Some(user)),
+        None => Ok(None),
+    }
+}
+
+// Public data needs no credentials, but an API token only reads what its
+// scopes allow.
+pub async fn require_scope(ctx: &Context<'_>, scope: ApiScope) -> Result<()> {
+    match authenticate(ctx).await? {
+        Some((_user, Credential::ApiToken(owner))) if !owner.has_scope(scope) => {
+            Err(ApiError::MissingScope(scope.as_str()).extend())
+        }
+        _ => Ok(()),
+    }
+}
+
+pub async fn require_user(ctx: &Context<'_>, scope: ApiScope) -> Result<SimpleUser> {
+    match authenticate(ctx).await? {
+        Some((_user, Credential::ApiToken(owner))) if !owner.has_scope(scope) => {
+            Err(ApiError::MissingScope(scope.as_str()).extend())
+        }
+        Some((user, _credential)) => Ok(user),
+        None => Err(ApiError::Unauthorized.extend()),
+    }
+}
+
+// Account security is only managed from a signed in session, never with an API token.
+pub async fn require_session_user(ctx: &Context<'_>) -> Result<SimpleUser> {
+    match authenticate(ctx).await? {
+        Some((user, Credential::Session)) => Ok(user),
+        Some((_user, Credential::ApiToken(_owner))) => {
+            Err(ApiError::Forbidden("API tokens can't be used to manage the account.").extend())
+        }
+        None => Err(ApiError::Unauthorized.extend()),
+    }
+}
+
+pub async fn require_admin(ctx: &Context<'_>, scope: ApiScope) -> Result<SimpleUser> {
+    let user = require_user(ctx, scope).await?;
+    if !user.role.can_manage_users() {
+        return Err(ApiError::Forbidden("Only admins can do this.").extend());
+    }
+    Ok(user)
+}
+
+pub async fn set_role<'a>(ctx: &'a Context<'_>, user_id: i32, role: UserRole) -> Result<&'a str> {
     let pg_pool = ctx.data::<PgPool>()?;
-    let new_user = NewUser::new(&email, &name, &password)?;
-    new_user.insert(&pg_pool).await?;
+    require_admin(ctx, ApiScope::UsersWrite).await?;
+    SimpleUser::set_role(pg_pool, user_id, role).await?;
     Ok("OK")
 }
 
-pub async fn sign_in<'a>(ctx: &'a Context<'_>, email: String, password: String) -> Result<String> {
+pub async fn single_sign_on(ctx: &Context<'_>) -> Result<Option<SingleSignOn>> {
+    let oidc_client = ctx.data::<Option<Arc<OidcClient>>>()?;
+    Ok(oidc_client
+        .as_ref()
+        .map(|oidc_client| oidc_client.single_sign_on()))
+}
+
+// Starts a login that links the provider identity to the signed in user, and
+// returns the address to send the browser to.
+pub async fn link_single_sign_on(ctx: &Context<'_>) -> Result<String> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let user = require_session_user(ctx).await?;
+    let oidc_client = match ctx.data::<Option<Arc<OidcClient>>>()? {
+        Some(oidc_client) => oidc_client,
+        None => return Err(Error::from("Single sign-on is not configured.")),
+    };
+    let login = NewOidcLogin::for_user(user.id);
+    login.insert(pg_pool).await?;
+    ctx.insert_http_header(
+        "set-cookie",
+        oidc_client.state_cookie(&login.state, LIFETIME_MINUTES * 60),
+    );
+    oidc_client.authorization_url(&login).await
+}
+
+pub async fn sign_in(ctx: &Context<'_>, email: String, password: String) -> Result<SignInResult> {
     let pg_pool = ctx.data::<PgPool>()?;
-    let user = SimpleUser::from_email(&pg_pool, &email).await?;
-    if !user.password_matches(&password).await? {
-        return Err(Error::from("The email and password combination failed."));
+    let client = ctx.data::<ClientInfo>()?;
+
+    let account_key = SignInThrottle::account_key(&email);
+    let mut keys = vec![account_key.clone()];
+    if let Some(ip_address) = &client.ip_address {
+        keys.push(SignInThrottle::ip_address_key(ip_address));
     }
+    if let Some(locked_until) = SignInThrottle::locked_until(pg_pool, &keys).await? {
+        return Err(ApiError::TooManyAttempts(locked_until).extend());
+    }
+
+    let user = match SimpleUser::find_by_email(pg_pool, &email).await? {
+        Some(user) if user.password_matches(&password).await? => user,
+        _ => {
+            SignInThrottle::record_failure(pg_pool, &account_key, ThrottlePolicy::account())
+                .await?;
+            if let Some(ip_address) = &client.ip_address {
+                let ip_address_key = SignInThrottle::ip_address_key(ip_address);
+                SignInThrottle::record_failure(
+                    pg_pool,
+                    &ip_address_key,
+                    ThrottlePolicy::ip_address(),
+                )
+                .await?;
+            }
+            return Err(field_error("password", SIGN_IN_FAILED));
+        }
+    };
+
+    // The account throttle is only cleared once the second factor is passed too,
+    // so failed codes keep counting towards the lockout.
+    if TwoFactor::get(pg_pool, user.id).await?.enabled() {
+        let challenge = NewSignInChallenge::make();
+        challenge.insert(pg_pool, user.id).await?;
+        return Ok(SignInResult {
+            token: None,
+            challenge: Some(challenge.token),
+        });
+    }
+    SignInThrottle::clear(pg_pool, &account_key).await?;
+
+    Ok(SignInResult {
+        token: Some(start_session(ctx, user.id).await?),
+        challenge: None,
+    })
+}
+
+pub async fn start_session(ctx: &Context<'_>, user_id: i32) -> Result<String> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let client = ctx.data::<ClientInfo>()?;
     let user_session = NewSession::make();
-    user_session.insert(&pg_pool, user.id).await?;
+    user_session
+        .insert(
+            pg_pool,
+            user_id,
+            client.user_agent.as_deref(),
+            client.ip_address.as_deref(),
+        )
+        .await?;
     Ok(user_session.get_token())
 }
+
+pub async fn sign_out<'a>(ctx: &'a Context<'_>) -> Result<&'a str> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    match ctx.data_opt::<AuthToken>() {
+        Some(token) => Session::revoke_token(pg_pool, &token.0).await?,
+        None => return Err(ApiError::Unauthorized.extend()),
+    }
+    Ok("OK")
+}
+
+pub async fn sign_out_everywhere<'a>(ctx: &'a Context<'_>) -> Result<&'a str> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let user = require_session_user(ctx).await?;
+    Session::revoke_all(pg_pool, user.id).await?;
+    Ok("OK")
+}
+
+pub async fn sessions(ctx: &Context<'_>) -> Result<Vec<Session>> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let user = require_session_user(ctx).await?;
+    let token = ctx.data::<AuthToken>()?;
+    Session::for_user(pg_pool, user.id, &token.0).await
+}
+
+pub async fn revoke_session<'a>(ctx: &'a Context<'_>, session_id: i32) -> Result<&'a str> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let user = require_session_user(ctx).await?;
+    Session::revoke(pg_pool, user.id, session_id).await?;
+    Ok("OK")
+}
+
+pub async fn api_tokens(ctx: &Context<'_>) -> Result<Vec<ApiToken>> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let user = require_session_user(ctx).await?;
+    ApiToken::for_user(pg_pool, user.id).await
+}
+
+pub async fn create_api_token(
+    ctx: &Context<'_>,
+    name: String,
+    scopes: Vec<String>,
+    expires_at: Option<DateTime<Utc>>,
+) -> Result<CreatedApiToken> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let user = require_session_user(ctx).await?;
+    let new_api_token = NewApiToken::new(&name, &scopes, expires_at)?;
+    let api_token = new_api_token.insert(pg_pool, user.id).await?;
+    Ok(CreatedApiToken {
+        token: new_api_token.token,
+        api_token,
+    })
+}
+
+pub async fn revoke_api_token<'a>(ctx: &'a Context<'_>, api_token_id: i32) -> Result<&'a str> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let user = require_session_user(ctx).await?;
+    ApiToken::revoke(pg_pool, user.id, api_token_id).await?;
+    Ok("OK")
+}
@@ -0,0 +1,118 @@
+use {
+    super::authorization,
+    crate::{
+        mailer::{Email, Mailer},
+        records::{
+            settings::{registry::SiteSettings, Settings},
+            users::{
+                email_verification::{EmailVerification, NewEmailVerification},
+                SimpleUser,
+            },
+        },
+    },
+    async_graphql::{Context, Error, Result},
+    sqlx::PgPool,
+    std::sync::Arc,
+};
+
+pub async fn send(pg_pool: &PgPool, mailer: &Arc<dyn Mailer>, user: &SimpleUser) -> Result<()> {
+    let settings = SiteSettings::from_settings(&Settings::all(pg_pool).await?);
+    let site_url = settings.site_url_for_emails()?;
+    let verification = NewEmailVerification::make();
+    verification.insert(pg_pool, user.id).await?;
+
+    let link = format!("{}/email/verify/{}", site_url, verification.token);
+    mailer
+        .send(Email {
+            to: user.email.clone(),
+            subject: format!("Verify your email address for {}", settings.title),
+            body: format!(
+                "Hi {},\n\nPlease confirm that this is your email address by following \
+                 this link within the next seven days:\n\n{}\n",
+                user.name, link
+            ),
+        })
+        .await
+}
+
+pub async fn verify<'a>(ctx: &'a Context<'_>, token: String) -> Result<&'a str> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let mut transaction = match pg_pool.begin().await {
+        Ok(transaction) => transaction,
+        Err(error) => {
+            println!("{}", error);
+            return Err(Error::from("Unable to start the email verification."));
+        }
+    };
+
+    let user_id = EmailVerification::consume(&mut transaction, &token).await?;
+    SimpleUser::mark_email_verified(&mut transaction, user_id).await?;
+
+    match transaction.commit().await {
+        Ok(()) => Ok("OK"),
+        Err(error) => {
+            println!("{}", error);
+            Err(Error::from("Unable to complete the email verification."))
+        }
+    }
+}
+
+pub async fn resend<'a>(ctx: &'a Context<'_>) -> Result<&'a str> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let mailer = ctx.data::<Arc<dyn Mailer>>()?;
+    let user = authorization::require_session_user(ctx).await?;
+    if user.email_verified() {
+        return Err(Error::from("Your email address is already verified."));
+    }
+    send(pg_pool, mailer, &user).await?;
+    Ok("OK")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::send;
+    use crate::mailer::{Mailer, MemoryMailer};
+    use crate::records::users::SimpleUser;
+    use crate::test_support::{execute, TestDatabase};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL, run with --ignored"]
+    async fn verifies_the_email_with_the_emailed_link() {
+        let database = TestDatabase::new().await;
+        database.set_site_url().await;
+        database.insert_user("reader@example.com", "Reader").await;
+        let mailer = Arc::new(MemoryMailer::default());
+        let schema = database.schema(mailer.clone());
+        let user = SimpleUser::find_by_email(&database.pg_pool, "reader@example.com")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!user.email_verified());
+
+        let dyn_mailer: Arc<dyn Mailer> = mailer.clone();
+        send(&database.pg_pool, &dyn_mailer, &user).await.unwrap();
+        let sent = mailer.sent();
+        assert_eq!(sent.len(), 1);
+        let token = sent[0]
+            .body
+            .split("/email/verify/")
+            .nth(1)
+            .and_then(|rest| rest.split_whitespace().next())
+            .unwrap();
+
+        let verify = format!(r#"mutation {{ verifyEmail(token: "{}") }}"#, token);
+        let response = execute(&schema, &verify, None).await;
+        assert_eq!(response["data"]["verifyEmail"], "OK");
+        let user = SimpleUser::find_by_email(&database.pg_pool, "reader@example.com")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(user.email_verified());
+
+        let response = execute(&schema, &verify, None).await;
+        assert!(response["errors"].is_array());
+
+        database.drop().await;
+    }
+}
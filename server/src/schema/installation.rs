@@ -0,0 +1,51 @@
+use {
+    crate::{
+        errors::field_error,
+        records::{
+            installation::Installation,
+            settings::NewSetting,
+            users::{role::UserRole, NewUser, SimpleUser},
+        },
+    },
+    async_graphql::{Context, Error, Result},
+    sqlx::PgPool,
+};
+
+pub async fn get(ctx: &Context<'_>) -> Result<bool> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    Installation::is_completed(pg_pool).await
+}
+
+pub async fn install<'a>(
+    ctx: &'a Context<'_>,
+    email: String,
+    name: String,
+    password: String,
+    title: String,
+) -> Result<&'a str> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let new_user = NewUser::new(&email, &name, &password, UserRole::Admin)?;
+    let new_setting = match NewSetting::new("title", &title) {
+        Ok(new_setting) => new_setting,
+        Err(error) => return Err(field_error("title", error.message)),
+    };
+
+    let mut transaction = match pg_pool.begin().await {
+        Ok(transaction) => transaction,
+        Err(error) => {
+            println!("{}", error);
+            return Err(Error::from("Unable to start the installation."));
+        }
+    };
+    Installation::complete(&mut transaction).await?;
+    let user = new_user.insert(&mut transaction).await?;
+    SimpleUser::mark_email_verified(&mut transaction, user.id).await?;
+    new_setting.insert(&mut transaction).await?;
+    match transaction.commit().await {
+        Ok(()) => Ok("OK"),
+        Err(error) => {
+            println!("{}", error);
+            Err(Error::from("Unable to complete the installation."))
+        }
+    }
+}
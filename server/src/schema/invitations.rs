@@ -0,0 +1,127 @@
+use {
+    super::authorization,
+    crate::{
+        errors::field_error,
+        mailer::{Email, Mailer},
+        records::{
+            settings::{registry::SiteSettings, Settings},
+            users::{
+                api_token::ApiScope,
+                invitation::{Invitation, NewInvitation},
+                role::UserRole,
+                NewUser, SimpleUser,
+            },
+        },
+    },
+    async_graphql::{Context, Error, Result},
+    sqlx::PgPool,
+    std::sync::Arc,
+};
+
+pub async fn create<'a>(ctx: &'a Context<'_>, email: String, role: UserRole) -> Result<&'a str> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let mailer = ctx.data::<Arc<dyn Mailer>>()?;
+    let admin = authorization::require_admin(ctx, ApiScope::UsersWrite).await?;
+
+    let invitation = NewInvitation::new(&email, role)?;
+    if SimpleUser::find_by_email(pg_pool, invitation.email)
+        .await?
+        .is_some()
+    {
+        return Err(field_error(
+            "email",
+            "There is already an account with this email.",
+        ));
+    }
+    let settings = SiteSettings::from_settings(&Settings::all(pg_pool).await?);
+    let site_url = settings.site_url_for_emails()?;
+    invitation.insert(pg_pool, admin.id).await?;
+
+    let link = format!("{}/invite/{}", site_url, invitation.token);
+    mailer
+        .send(Email {
+            to: invitation.email.to_string(),
+            subject: format!("You're invited to write for {}", settings.title),
+            body: format!(
+                "Hi,\n\n{} invited you to write for {}. Follow this link within the \
+                 next seven days to create your account:\n\n{}\n",
+                admin.name, settings.title, link
+            ),
+        })
+        .await?;
+
+    Ok("OK")
+}
+
+pub async fn get(ctx: &Context<'_>, token: String) -> Result<Option<Invitation>> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    Invitation::find(pg_pool, &token).await
+}
+
+pub async fn accept(
+    ctx: &Context<'_>,
+    token: String,
+    name: String,
+    password: String,
+) -> Result<String> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(field_error("name", "Your name can't be empty."));
+    }
+
+    let mut transaction = match pg_pool.begin().await {
+        Ok(transaction) => transaction,
+        Err(error) => {
+            println!("{}", error);
+            return Err(Error::from("Unable to accept the invitation."));
+        }
+    };
+
+    let invitation = Invitation::accept(&mut transaction, &token).await?;
+    let new_user = NewUser::new(&invitation.email, name, &password, invitation.role)?;
+    let user = new_user.insert(&mut transaction).await?;
+    // Following the emailed link proves the address belongs to the new user.
+    SimpleUser::mark_email_verified(&mut transaction, user.id).await?;
+
+    match transaction.commit().await {
+        Ok(()) => authorization::start_session(ctx, user.id).await,
+        Err(error) => {
+            println!("{}", error);
+            Err(Error::from("Unable to accept the invitation."))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mailer::MemoryMailer;
+    use crate::records::users::{invitation::NewInvitation, role::UserRole};
+    use crate::test_support::{execute, TestDatabase};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL, run with --ignored"]
+    async fn refuses_an_invitation_for_a_taken_email_in_another_case() {
+        let database = TestDatabase::new().await;
+        let admin_id = database.insert_user("jane@example.com", "Jane").await;
+        let schema = database.schema(Arc::new(MemoryMailer::default()));
+        let invitation = NewInvitation::new("Jane@Example.com", UserRole::Author).unwrap();
+        invitation
+            .insert(&database.pg_pool, admin_id)
+            .await
+            .unwrap();
+
+        let accept = format!(
+            r#"mutation {{ acceptInvitation(token: "{}", name: "Jane", password: "quiet harbor lantern") }}"#,
+            invitation.token
+        );
+        let response = execute(&schema, &accept, None).await;
+        assert_eq!(
+            response["errors"][0]["extensions"]["fields"]["email"],
+            "There is already an account with this email."
+        );
+
+        database.drop().await;
+    }
+}
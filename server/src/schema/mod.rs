@@ -1,31 +1,132 @@
+use crate::oidc::SingleSignOn;
 use crate::records::authorities::Authorities;
-use crate::records::posts::Post;
+use crate::records::posts::{
+    category::Category,
+    revision::{PostRevision, RevisionDiff},
+    tag::Tag,
+    Post, PostStatus, UpdatePostInput,
+};
+use crate::records::settings::{registry::SiteSettings, Settings};
+use crate::records::users::{
+    api_token::{ApiToken, CreatedApiToken},
+    author::{Author, Profile},
+    invitation::Invitation,
+    role::UserRole,
+    session::{Session, SignInResult},
+    two_factor::{TwoFactorEnrollment, TwoFactorStatus},
+};
 use crate::{MutationRoot, QueryRoot};
 use async_graphql::{Context, Object, Result};
+use chrono::{DateTime, Utc};
 use std::collections::HashMap;
 
 mod authorization;
+mod email_verification;
+mod installation;
+mod invitations;
+mod password_reset;
 mod posts;
+mod profile;
 mod settings;
+mod taxonomy;
+mod two_factor;
 
 #[Object]
 impl QueryRoot {
-    async fn authorization<'a>(&self, ctx: &'a Context<'_>) -> Result<Option<Authorities>> {
+    async fn authorization(&self, ctx: &Context<'_>) -> Result<Option<Authorities>> {
         authorization::get(ctx).await
     }
 
-    async fn posts<'a>(&self, ctx: &'a Context<'_>) -> Result<Vec<Post>> {
-        posts::get_all(ctx).await
+    async fn posts(
+        &self,
+        ctx: &Context<'_>,
+        author_id: Option<i32>,
+        tag: Option<String>,
+    ) -> Result<Vec<Post>> {
+        posts::get_all(ctx, author_id, tag).await
     }
 
-    async fn post<'a>(&self, ctx: &'a Context<'_>, post_id: i32) -> Result<Option<Post>> {
+    async fn post(&self, ctx: &Context<'_>, post_id: i32) -> Result<Option<Post>> {
         posts::get(ctx, post_id).await
     }
 
-    async fn settings<'a>(&self, ctx: &'a Context<'_>) -> Result<HashMap<String, String>> {
+    async fn post_by_slug(&self, ctx: &Context<'_>, slug: String) -> Result<Option<Post>> {
+        posts::get_by_slug(ctx, slug).await
+    }
+
+    async fn tags(&self, ctx: &Context<'_>) -> Result<Vec<Tag>> {
+        taxonomy::tags(ctx).await
+    }
+
+    async fn tag(&self, ctx: &Context<'_>, slug: String) -> Result<Option<Tag>> {
+        taxonomy::tag(ctx, slug).await
+    }
+
+    async fn post_tags(&self, ctx: &Context<'_>, post_id: i32) -> Result<Vec<Tag>> {
+        taxonomy::post_tags(ctx, post_id).await
+    }
+
+    async fn categories(&self, ctx: &Context<'_>) -> Result<Vec<Category>> {
+        taxonomy::categories(ctx).await
+    }
+
+    async fn post_breadcrumbs(&self, ctx: &Context<'_>, post_id: i32) -> Result<Vec<Category>> {
+        taxonomy::breadcrumbs(ctx, post_id).await
+    }
+
+    async fn trashed_posts(&self, ctx: &Context<'_>) -> Result<Vec<Post>> {
+        posts::trash(ctx).await
+    }
+
+    async fn post_revisions(&self, ctx: &Context<'_>, post_id: i32) -> Result<Vec<PostRevision>> {
+        posts::revisions(ctx, post_id).await
+    }
+
+    async fn revision_diff(&self, ctx: &Context<'_>, from: i32, to: i32) -> Result<RevisionDiff> {
+        posts::revision_diff(ctx, from, to).await
+    }
+
+    async fn author(&self, ctx: &Context<'_>, author_id: i32) -> Result<Option<Author>> {
+        profile::get_author(ctx, author_id).await
+    }
+
+    async fn profile(&self, ctx: &Context<'_>) -> Result<Profile> {
+        profile::get(ctx).await
+    }
+
+    #[graphql(deprecation = "Use siteSettings instead.")]
+    async fn settings(&self, ctx: &Context<'_>) -> Result<HashMap<String, String>> {
         settings::get_all(ctx).await
     }
 
+    async fn site_settings(&self, ctx: &Context<'_>) -> Result<SiteSettings> {
+        settings::get_site(ctx).await
+    }
+
+    async fn sessions(&self, ctx: &Context<'_>) -> Result<Vec<Session>> {
+        authorization::sessions(ctx).await
+    }
+
+    async fn api_tokens(&self, ctx: &Context<'_>) -> Result<Vec<ApiToken>> {
+        authorization::api_tokens(ctx).await
+    }
+
+    async fn two_factor(&self, ctx: &Context<'_>) -> Result<TwoFactorStatus> {
+        two_factor::status(ctx).await
+    }
+
+    async fn invitation(&self, ctx: &Context<'_>, token: String) -> Result<Option<Invitation>> {
+        invitations::get(ctx, token).await
+    }
+
+    async fn single_sign_on(&self, ctx: &Context<'_>) -> Result<Option<SingleSignOn>> {
+        authorization::single_sign_on(ctx).await
+    }
+
+    async fn installed(&self, ctx: &Context<'_>) -> Result<bool> {
+        installation::get(ctx).await
+    }
+
     async fn ping<'a>(&self, _ctx: &'a Context<'_>) -> &'a str {
         "Pong"
     }
@@ -33,6 +134,17 @@ impl QueryRoot {
 
 #[Object]
 impl MutationRoot {
+    async fn install<'a>(
+        &self,
+        ctx: &'a Context<'_>,
+        email: String,
+        name: String,
+        password: String,
+        title: String,
+    ) -> Result<&'a str> {
+        installation::install(ctx, email, name, password, title).await
+    }
+
     async fn add_setting<'a>(
         &self,
         ctx: &'a Context<'_>,
@@ -42,43 +154,297 @@ impl MutationRoot {
         settings::add(ctx, key, value).await
     }
 
-    async fn sign_up<'a>(
+    async fn update_setting(
+        &self,
+        ctx: &Context<'_>,
+        key: String,
+        value: String,
+    ) -> Result<Settings> {
+        settings::update(ctx, key, value).await
+    }
+
+    async fn delete_setting<'a>(&self, ctx: &'a Context<'_>, key: String) -> Result<&'a str> {
+        settings::delete(ctx, key).await
+    }
+
+    async fn upsert_setting(
+        &self,
+        ctx: &Context<'_>,
+        key: String,
+        value: String,
+    ) -> Result<Settings> {
+        settings::upsert(ctx, key, value).await
+    }
+
+    async fn create_invitation<'a>(
         &self,
         ctx: &'a Context<'_>,
         email: String,
+        role: UserRole,
+    ) -> Result<&'a str> {
+        invitations::create(ctx, email, role).await
+    }
+
+    async fn accept_invitation(
+        &self,
+        ctx: &Context<'_>,
+        token: String,
         name: String,
         password: String,
+    ) -> Result<String> {
+        invitations::accept(ctx, token, name, password).await
+    }
+
+    async fn update_profile(
+        &self,
+        ctx: &Context<'_>,
+        display_name: String,
+        bio: String,
+        avatar_url: Option<String>,
+        website_url: Option<String>,
+        social_links: Vec<String>,
+    ) -> Result<Author> {
+        profile::update(
+            ctx,
+            display_name,
+            bio,
+            avatar_url,
+            website_url,
+            social_links,
+        )
+        .await
+    }
+
+    async fn change_email<'a>(
+        &self,
+        ctx: &'a Context<'_>,
+        new_email: String,
+        password: String,
+    ) -> Result<&'a str> {
+        profile::change_email(ctx, new_email, password).await
+    }
+
+    async fn change_password<'a>(
+        &self,
+        ctx: &'a Context<'_>,
+        current_password: String,
+        new_password: String,
+    ) -> Result<&'a str> {
+        profile::change_password(ctx, current_password, new_password).await
+    }
+
+    async fn export_my_data(&self, ctx: &Context<'_>) -> Result<String> {
+        profile::export_data(ctx).await
+    }
+
+    async fn delete_account<'a>(
+        &self,
+        ctx: &'a Context<'_>,
+        password: String,
+        reassign_posts_to: Option<i32>,
     ) -> Result<&'a str> {
-        authorization::sign_up(ctx, email, name, password).await
+        profile::delete_account(ctx, password, reassign_posts_to).await
     }
 
-    async fn sign_in<'a>(
+    async fn set_user_role<'a>(
         &self,
         ctx: &'a Context<'_>,
+        user_id: i32,
+        role: UserRole,
+    ) -> Result<&'a str> {
+        authorization::set_role(ctx, user_id, role).await
+    }
+
+    async fn sign_in(
+        &self,
+        ctx: &Context<'_>,
         email: String,
         password: String,
-    ) -> Result<String> {
+    ) -> Result<SignInResult> {
         authorization::sign_in(ctx, email, password).await
     }
 
-    async fn new_post<'a>(
+    async fn complete_sign_in(
+        &self,
+        ctx: &Context<'_>,
+        challenge: String,
+        code: String,
+    ) -> Result<String> {
+        two_factor::complete_sign_in(ctx, challenge, code).await
+    }
+
+    async fn begin_two_factor_enrollment(&self, ctx: &Context<'_>) -> Result<TwoFactorEnrollment> {
+        two_factor::begin_enrollment(ctx).await
+    }
+
+    async fn confirm_two_factor_enrollment(
+        &self,
+        ctx: &Context<'_>,
+        code: String,
+    ) -> Result<Vec<String>> {
+        two_factor::confirm_enrollment(ctx, code).await
+    }
+
+    async fn disable_two_factor<'a>(&self, ctx: &'a Context<'_>, code: String) -> Result<&'a str> {
+        two_factor::disable(ctx, code).await
+    }
+
+    async fn regenerate_recovery_codes(
+        &self,
+        ctx: &Context<'_>,
+        code: String,
+    ) -> Result<Vec<String>> {
+        two_factor::regenerate_recovery_codes(ctx, code).await
+    }
+
+    async fn link_single_sign_on(&self, ctx: &Context<'_>) -> Result<String> {
+        authorization::link_single_sign_on(ctx).await
+    }
+
+    async fn request_password_reset<'a>(
         &self,
         ctx: &'a Context<'_>,
+        email: String,
+    ) -> Result<&'a str> {
+        password_reset::request(ctx, email).await
+    }
+
+    async fn reset_password<'a>(
+        &self,
+        ctx: &'a Context<'_>,
+        token: String,
+        new_password: String,
+    ) -> Result<&'a str> {
+        password_reset::reset(ctx, token, new_password).await
+    }
+
+    async fn verify_email<'a>(&self, ctx: &'a Context<'_>, token: String) -> Result<&'a str> {
+        email_verification::verify(ctx, token).await
+    }
+
+    async fn resend_verification_email<'a>(&self, ctx: &'a Context<'_>) -> Result<&'a str> {
+        email_verification::resend(ctx).await
+    }
+
+    async fn sign_out<'a>(&self, ctx: &'a Context<'_>) -> Result<&'a str> {
+        authorization::sign_out(ctx).await
+    }
+
+    async fn sign_out_everywhere<'a>(&self, ctx: &'a Context<'_>) -> Result<&'a str> {
+        authorization::sign_out_everywhere(ctx).await
+    }
+
+    async fn revoke_session<'a>(&self, ctx: &'a Context<'_>, session_id: i32) -> Result<&'a str> {
+        authorization::revoke_session(ctx, session_id).await
+    }
+
+    async fn create_api_token(
+        &self,
+        ctx: &Context<'_>,
+        name: String,
+        scopes: Vec<String>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<CreatedApiToken> {
+        authorization::create_api_token(ctx, name, scopes, expires_at).await
+    }
+
+    async fn revoke_api_token<'a>(
+        &self,
+        ctx: &'a Context<'_>,
+        api_token_id: i32,
+    ) -> Result<&'a str> {
+        authorization::revoke_api_token(ctx, api_token_id).await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn new_post(
+        &self,
+        ctx: &Context<'_>,
         slug: String,
         title: String,
         text: String,
         summary: String,
+        status: Option<PostStatus>,
+        published_at: Option<DateTime<Utc>>,
     ) -> Result<Post> {
-        posts::new(ctx, &slug, &title, &text, &summary).await
+        posts::new(ctx, &slug, &title, &text, &summary, status, published_at).await
     }
 
-    async fn update_post<'a>(
+    async fn update_post(
         &self,
-        ctx: &'a Context<'_>,
+        ctx: &Context<'_>,
         post_id: i32,
-        title: Option<String>,
-        text: Option<String>,
+        expected_version: i32,
+        input: UpdatePostInput,
+    ) -> Result<Post> {
+        posts::update(ctx, post_id, expected_version, input).await
+    }
+
+    async fn restore_revision<'a>(
+        &self,
+        ctx: &'a Context<'_>,
+        revision_id: i32,
     ) -> Result<&'a str> {
-        posts::update(ctx, post_id, title, text).await
+        posts::restore_revision(ctx, revision_id).await
+    }
+
+    async fn delete_post<'a>(&self, ctx: &'a Context<'_>, post_id: i32) -> Result<&'a str> {
+        posts::delete(ctx, post_id).await
+    }
+
+    async fn restore_post(&self, ctx: &Context<'_>, post_id: i32) -> Result<Post> {
+        posts::restore(ctx, post_id).await
+    }
+
+    async fn purge_post<'a>(&self, ctx: &'a Context<'_>, post_id: i32) -> Result<&'a str> {
+        posts::purge(ctx, post_id).await
+    }
+
+    async fn create_tag(
+        &self,
+        ctx: &Context<'_>,
+        name: String,
+        slug: Option<String>,
+    ) -> Result<Tag> {
+        taxonomy::create_tag(ctx, name, slug).await
+    }
+
+    async fn update_tag(
+        &self,
+        ctx: &Context<'_>,
+        tag_id: i32,
+        name: String,
+        slug: Option<String>,
+    ) -> Result<Tag> {
+        taxonomy::update_tag(ctx, tag_id, name, slug).await
+    }
+
+    async fn delete_tag<'a>(&self, ctx: &'a Context<'_>, tag_id: i32) -> Result<&'a str> {
+        taxonomy::delete_tag(ctx, tag_id).await
+    }
+
+    async fn create_category(
+        &self,
+        ctx: &Context<'_>,
+        name: String,
+        slug: Option<String>,
+        parent_id: Option<i32>,
+    ) -> Result<Category> {
+        taxonomy::create_category(ctx, name, slug, parent_id).await
+    }
+
+    async fn update_category(
+        &self,
+        ctx: &Context<'_>,
+        category_id: i32,
+        name: String,
+        slug: Option<String>,
+        parent_id: Option<i32>,
+    ) -> Result<Category> {
+        taxonomy::update_category(ctx, category_id, name, slug, parent_id).await
+    }
+
+    async fn delete_category<'a>(&self, ctx: &'a Context<'_>, category_id: i32) -> Result<&'a str> {
+        taxonomy::delete_category(ctx, category_id).await
     }
 }
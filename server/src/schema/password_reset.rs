@@ -0,0 +1,200 @@
+use {
+    crate::{
+        errors::field_error,
+        mailer::{Email, Mailer},
+        records::{
+            settings::{registry::SiteSettings, Settings},
+            users::{
+                password::PasswordPolicy,
+                password_reset::{NewPasswordReset, PasswordReset},
+                session::Session,
+                SimpleUser,
+            },
+        },
+    },
+    async_graphql::{Context, Error, Result},
+    sqlx::PgPool,
+    std::sync::Arc,
+};
+
+pub async fn request<'a>(ctx: &'a Context<'_>, email: String) -> Result<&'a str> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let mailer = ctx.data::<Arc<dyn Mailer>>()?;
+    let settings = SiteSettings::from_settings(&Settings::all(pg_pool).await?);
+    let site_url = settings.site_url_for_emails()?;
+
+    // Answer the same way whether or not the account exists.
+    let user = match SimpleUser::find_by_email(pg_pool, &email).await? {
+        Some(user) => user,
+        None => return Ok("OK"),
+    };
+
+    let reset = NewPasswordReset::make();
+    reset.insert(pg_pool, user.id).await?;
+
+    let link = format!("{}/password/reset/{}", site_url, reset.token);
+    mailer
+        .send(Email {
+            to: user.email,
+            subject: format!("Reset your {} password", settings.title),
+            body: format!(
+                "Hi {},\n\nSomeone asked to reset the password for your account. \
+                 Follow this link within the next hour to choose a new one:\n\n{}\n\n\
+                 If it wasn't you, you can ignore this email.\n",
+                user.name, link
+            ),
+        })
+        .await?;
+
+    Ok("OK")
+}
+
+pub async fn reset<'a>(
+    ctx: &'a Context<'_>,
+    token: String,
+    new_password: String,
+) -> Result<&'a str> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let mut transaction = match pg_pool.begin().await {
+        Ok(transaction) => transaction,
+        Err(error) => {
+            println!("{}", error);
+            return Err(Error::from("Unable to start the password reset."));
+        }
+    };
+
+    let user_id = PasswordReset::consume(&mut transaction, &token).await?;
+    // Rejecting the password here rolls the transaction back, so the link can
+    // be used again with a better one.
+    let user = match SimpleUser::get(pg_pool, user_id).await? {
+        Some(user) => user,
+        None => {
+            return Err(Error::from(
+                "The password reset link is invalid or has expired.",
+            ))
+        }
+    };
+    if let Err(message) =
+        PasswordPolicy::from_env().check(&new_password, &[&user.email, &user.name])
+    {
+        return Err(field_error("newPassword", message));
+    }
+    SimpleUser::set_password(&mut transaction, user_id, &new_password).await?;
+    Session::revoke_all(&mut transaction, user_id).await?;
+
+    match transaction.commit().await {
+        Ok(()) => Ok("OK"),
+        Err(error) => {
+            println!("{}", error);
+            Err(Error::from("Unable to complete the password reset."))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mailer::MemoryMailer;
+    use crate::test_support::{execute, TestDatabase};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL, run with --ignored"]
+    async fn resets_the_password_with_the_emailed_link() {
+        let database = TestDatabase::new().await;
+        database.set_site_url().await;
+        database.insert_user("reader@example.com", "Reader").await;
+        let mailer = Arc::new(MemoryMailer::default());
+        let schema = database.schema(mailer.clone());
+
+        let response = execute(
+            &schema,
+            r#"mutation { requestPasswordReset(email: "reader@example.com") }"#,
+            None,
+        )
+        .await;
+        assert_eq!(response["data"]["requestPasswordReset"], "OK");
+        let sent = mailer.sent();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].to, "reader@example.com");
+        assert!(sent[0]
+            .body
+            .contains("https://blog.example.com/password/reset/"));
+        let token = sent[0]
+            .body
+            .split("/password/reset/")
+            .nth(1)
+            .and_then(|rest| rest.split_whitespace().next())
+            .unwrap();
+
+        // A password made of the account's own details is refused without
+        // using up the link.
+        let weak = format!(
+            r#"mutation {{ resetPassword(token: "{}", newPassword: "readerexample2021") }}"#,
+            token
+        );
+        let response = execute(&schema, &weak, None).await;
+        assert!(response["errors"][0]["extensions"]["fields"]["newPassword"].is_string());
+
+        let reset = format!(
+            r#"mutation {{ resetPassword(token: "{}", newPassword: "cobalt lantern meadow") }}"#,
+            token
+        );
+        let response = execute(&schema, &reset, None).await;
+        assert_eq!(response["data"]["resetPassword"], "OK");
+
+        let response = execute(
+            &schema,
+            r#"mutation { signIn(email: "reader@example.com", password: "cobalt lantern meadow") { token } }"#,
+            None,
+        )
+        .await;
+        assert!(response["data"]["signIn"]["token"].is_string());
+
+        // The link only works once.
+        let response = execute(&schema, &reset, None).await;
+        assert!(response["errors"].is_array());
+
+        database.drop().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL, run with --ignored"]
+    async fn sends_nothing_for_an_unknown_email() {
+        let database = TestDatabase::new().await;
+        database.set_site_url().await;
+        let mailer = Arc::new(MemoryMailer::default());
+        let schema = database.schema(mailer.clone());
+
+        let response = execute(
+            &schema,
+            r#"mutation { requestPasswordReset(email: "nobody@example.com") }"#,
+            None,
+        )
+        .await;
+        assert_eq!(response["data"]["requestPasswordReset"], "OK");
+        assert!(mailer.sent().is_empty());
+
+        database.drop().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL, run with --ignored"]
+    async fn sends_no_link_without_a_site_url() {
+        let database = TestDatabase::new().await;
+        database.insert_user("reader@example.com", "Reader").await;
+        let mailer = Arc::new(MemoryMailer::default());
+        let schema = database.schema(mailer.clone());
+
+        for email in ["reader@example.com", "nobody@example.com"].iter() {
+            let request = format!(r#"mutation {{ requestPasswordReset(email: "{}") }}"#, email);
+            let response = execute(&schema, &request, None).await;
+            assert!(response["errors"][0]["message"]
+                .as_str()
+                .unwrap()
+                .starts_with("The site URL isn't set"));
+        }
+        assert!(mailer.sent().is_empty());
+
+        database.drop().await;
+    }
+}
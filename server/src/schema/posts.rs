@@ -1,23 +1,113 @@
 use {
+    super::authorization,
     crate::{
+        errors::ApiError,
         records::{
-            posts::{NewPost, Post},
-            users::SimpleUser,
+            posts::{
+                revision::{PostRevision, RevisionDiff},
+                NewPost, Post, PostStatus, PostUpdate, UpdatePostInput, Viewer,
+            },
+            settings::{registry::SiteSettings, Settings},
+            users::{api_token::ApiScope, SimpleUser},
         },
-        AuthToken,
     },
-    async_graphql::{Context, Error, Result},
+    async_graphql::{Context, Error, ErrorExtensions, Result},
+    chrono::{DateTime, Utc},
     sqlx::PgPool,
 };
 
-pub async fn get_all<'a>(ctx: &'a Context<'_>) -> Result<Vec<Post>> {
+fn authorize_status(user: &SimpleUser, status: PostStatus) -> Result<()> {
+    if !user.role.can_set_status(status) {
+        return Err(
+            ApiError::Forbidden("Contributors can only submit drafts for review.").extend(),
+        );
+    }
+    Ok(())
+}
+
+async fn authorize_publish(pg_pool: &PgPool, user: &SimpleUser, status: PostStatus) -> Result<()> {
+    if !matches!(status, PostStatus::Published | PostStatus::Scheduled) || user.email_verified() {
+        return Ok(());
+    }
+    let settings = SiteSettings::from_settings(&Settings::all(pg_pool).await?);
+    if settings.require_verified_email {
+        return Err(ApiError::Forbidden("Verify your email address before publishing.").extend());
+    }
+    Ok(())
+}
+
+async fn authorize_edit(pg_pool: &PgPool, user: &SimpleUser, post_id: i32) -> Result<Post> {
+    let post = match Post::get(pg_pool, post_id, Viewer::everything()).await? {
+        Some(post) => post,
+        None => return Err(Error::from("The post doesn't exist.")),
+    };
+    if post.created_by != Some(user.id) && !user.role.can_edit_others_posts() {
+        return Err(ApiError::Forbidden("You can only edit your own posts.").extend());
+    }
+    authorize_status(user, post.status)?;
+    Ok(post)
+}
+
+// Reading the history needs the same ownership as editing, but not the right
+// to change the status the post is in.
+async fn authorize_history(pg_pool: &PgPool, user: &SimpleUser, post_id: i32) -> Result<()> {
+    match Post::get(pg_pool, post_id, Viewer::everything()).await? {
+        Some(post) if post.created_by == Some(user.id) || user.role.can_edit_others_posts() => {
+            Ok(())
+        }
+        Some(_post) => {
+            Err(ApiError::Forbidden("You can only see the history of your own posts.").extend())
+        }
+        None => Err(Error::from("The post doesn't exist.")),
+    }
+}
+
+// Posts in the trash can only be restored or purged by someone who could edit them.
+async fn authorize_trashed(pg_pool: &PgPool, user: &SimpleUser, post_id: i32) -> Result<Post> {
+    let post = match Post::trashed(pg_pool, post_id).await? {
+        Some(post) => post,
+        None => return Err(Error::from("The post isn't in the trash.")),
+    };
+    if post.created_by != Some(user.id) && !user.role.can_edit_others_posts() {
+        return Err(ApiError::Forbidden("You can only edit your own posts.").extend());
+    }
+    authorize_status(user, post.status)?;
+    Ok(post)
+}
+
+async fn find_revision(pg_pool: &PgPool, revision_id: i32) -> Result<PostRevision> {
+    match PostRevision::get(pg_pool, revision_id).await? {
+        Some(revision) => Ok(revision),
+        None => Err(Error::from("The revision doesn't exist.")),
+    }
+}
+
+pub async fn get_all(
+    ctx: &Context<'_>,
+    author_id: Option<i32>,
+    tag: Option<String>,
+) -> Result<Vec<Post>> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let viewer = authorization::current_user(ctx, ApiScope::PostsRead).await?;
+    Post::all(
+        pg_pool,
+        Viewer::from_user(viewer.as_ref()),
+        author_id,
+        tag.as_deref(),
+    )
+    .await
+}
+
+pub async fn get(ctx: &Context<'_>, post_id: i32) -> Result<Option<Post>> {
     let pg_pool = ctx.data::<PgPool>()?;
-    Post::all(&pg_pool).await
+    let viewer = authorization::current_user(ctx, ApiScope::PostsRead).await?;
+    Post::get(pg_pool, post_id, Viewer::from_user(viewer.as_ref())).await
 }
 
-pub async fn get<'a>(ctx: &'a Context<'_>, post_id: i32) -> Result<Option<Post>> {
+pub async fn get_by_slug(ctx: &Context<'_>, slug: String) -> Result<Option<Post>> {
     let pg_pool = ctx.data::<PgPool>()?;
-    Post::get(&pg_pool, post_id).await
+    let viewer = authorization::current_user(ctx, ApiScope::PostsRead).await?;
+    Post::by_slug(pg_pool, &slug, Viewer::from_user(viewer.as_ref())).await
 }
 
 pub async fn new<'a>(
@@ -26,35 +116,169 @@ pub async fn new<'a>(
     title: &'a str,
     text: &'a str,
     summary: &'a str,
+    status: Option<PostStatus>,
+    published_at: Option<DateTime<Utc>>,
 ) -> Result<Post> {
     let pg_pool = ctx.data::<PgPool>()?;
-    let token = match ctx.data_opt::<AuthToken>() {
-        Some(token) => token,
-        None => {
-            return Err(Error::from("No session token found."));
-        }
-    };
-    let maybe_user = SimpleUser::from_session_token(&pg_pool, &token.0).await?;
-    let user = SimpleUser::unwrap_user_session(maybe_user)?;
-    let new_post = NewPost::new(slug, title, text, summary, user.id)?;
-    new_post.insert(&pg_pool).await
+    let user = authorization::require_user(ctx, ApiScope::PostsWrite).await?;
+    let status = status.unwrap_or(PostStatus::Draft);
+    authorize_status(&user, status)?;
+    authorize_publish(pg_pool, &user, status).await?;
+    let new_post = NewPost::new(slug, title, text, summary, status, published_at, user.id)?;
+    let post = new_post.insert(pg_pool).await?;
+    PostRevision::record(pg_pool, post.id, user.id).await?;
+    Ok(post)
 }
 
-pub async fn update<'a>(
-    ctx: &'a Context<'_>,
+pub async fn update(
+    ctx: &Context<'_>,
     post_id: i32,
-    title: Option<String>,
-    text: Option<String>,
-) -> Result<&'a str> {
-    let pg_pool = ctx.data::<PgPool>()?;
-    let token = match ctx.data_opt::<AuthToken>() {
-        Some(token) => token,
-        None => {
-            return Err(Error::from("No session token found."));
-        }
-    };
-    let maybe_user = SimpleUser::from_session_token(&pg_pool, &token.0).await?;
-    let user = SimpleUser::unwrap_user_session(maybe_user)?;
-    Post::update(&pg_pool, post_id, user.id, title, text).await?;
-    Ok("Updated!")
+    expected_version: i32,
+    input: UpdatePostInput,
+) -> Result<Post> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let user = authorization::require_user(ctx, ApiScope::PostsWrite).await?;
+    authorize_edit(pg_pool, &user, post_id).await?;
+    if let Some(status) = input.status {
+        authorize_status(&user, status)?;
+        authorize_publish(pg_pool, &user, status).await?;
+    }
+    let update = PostUpdate::new(input)?;
+    update
+        .save(pg_pool, post_id, expected_version, user.id)
+        .await
+}
+
+pub async fn revisions(ctx: &Context<'_>, post_id: i32) -> Result<Vec<PostRevision>> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let user = authorization::require_user(ctx, ApiScope::PostsRead).await?;
+    authorize_history(pg_pool, &user, post_id).await?;
+    PostRevision::for_post(pg_pool, post_id).await
+}
+
+pub async fn revision_diff(ctx: &Context<'_>, from: i32, to: i32) -> Result<RevisionDiff> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let user = authorization::require_user(ctx, ApiScope::PostsRead).await?;
+    let from = find_revision(pg_pool, from).await?;
+    let to = find_revision(pg_pool, to).await?;
+    authorize_history(pg_pool, &user, from.post_id).await?;
+    RevisionDiff::new(from, to)
+}
+
+pub async fn restore_revision<'a>(ctx: &'a Context<'_>, revision_id: i32) -> Result<&'a str> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let user = authorization::require_user(ctx, ApiScope::PostsWrite).await?;
+    let revision = find_revision(pg_pool, revision_id).await?;
+    authorize_edit(pg_pool, &user, revision.post_id).await?;
+    revision.restore(pg_pool, user.id).await?;
+    Ok("OK")
+}
+
+pub async fn trash(ctx: &Context<'_>) -> Result<Vec<Post>> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let user = authorization::require_user(ctx, ApiScope::PostsRead).await?;
+    Post::trash(pg_pool, Viewer::from_user(Some(&user))).await
+}
+
+pub async fn delete<'a>(ctx: &'a Context<'_>, post_id: i32) -> Result<&'a str> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let user = authorization::require_user(ctx, ApiScope::PostsWrite).await?;
+    authorize_edit(pg_pool, &user, post_id).await?;
+    Post::delete(pg_pool, post_id).await?;
+    Ok("OK")
+}
+
+pub async fn restore(ctx: &Context<'_>, post_id: i32) -> Result<Post> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let user = authorization::require_user(ctx, ApiScope::PostsWrite).await?;
+    authorize_trashed(pg_pool, &user, post_id).await?;
+    Post::restore(pg_pool, post_id).await
+}
+
+pub async fn purge<'a>(ctx: &'a Context<'_>, post_id: i32) -> Result<&'a str> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let user = authorization::require_user(ctx, ApiScope::PostsWrite).await?;
+    authorize_trashed(pg_pool, &user, post_id).await?;
+    Post::purge(pg_pool, post_id).await?;
+    Ok("OK")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mailer::MemoryMailer;
+    use crate::test_support::{execute, TestDatabase, TestSchema};
+    use chrono::{DateTime, Utc};
+    use serde_json::Value;
+    use std::sync::Arc;
+
+    fn published_at(post: &Value) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(post["publishedAt"].as_str().unwrap())
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    async fn update(schema: &TestSchema, token: &str, post: &Value, input: &str) -> Value {
+        let mutation = format!(
+            "mutation {{ updatePost(postId: {}, expectedVersion: {}, input: {{ {} }}) {{ id version publishedAt }} }}",
+            post["id"], post["version"], input
+        );
+        let response = execute(schema, &mutation, Some(token)).await;
+        response["data"]["updatePost"].clone()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL, run with --ignored"]
+    async fn keeps_the_publish_date_unless_one_is_sent() {
+        let database = TestDatabase::new().await;
+        database.insert_user("jane@example.com", "Jane").await;
+        let schema = database.schema(Arc::new(MemoryMailer::default()));
+        let response = execute(
+            &schema,
+            r#"mutation { signIn(email: "jane@example.com", password: "violet tugboat anthem") { token } }"#,
+            None,
+        )
+        .await;
+        let token = response["data"]["signIn"]["token"].as_str().unwrap();
+
+        let response = execute(
+            &schema,
+            r#"mutation { newPost(slug: "hello", title: "Hello", text: "Hi", summary: "Hi", status: PUBLISHED, publishedAt: "2021-03-01T10:00:00Z") { id version publishedAt } }"#,
+            Some(token),
+        )
+        .await;
+        let post = response["data"]["newPost"].clone();
+        let first_published_at = published_at(&post);
+
+        let post = update(
+            &schema,
+            token,
+            &post,
+            r#"status: PUBLISHED, title: "Hello again""#,
+        )
+        .await;
+        assert_eq!(published_at(&post), first_published_at);
+
+        let post = update(
+            &schema,
+            token,
+            &post,
+            r#"publishedAt: "2021-02-01T09:00:00Z""#,
+        )
+        .await;
+        assert_eq!(
+            published_at(&post),
+            DateTime::parse_from_rfc3339("2021-02-01T09:00:00Z").unwrap()
+        );
+
+        let post = update(&schema, token, &post, "status: ARCHIVED").await;
+        assert_eq!(
+            published_at(&post),
+            DateTime::parse_from_rfc3339("2021-02-01T09:00:00Z").unwrap()
+        );
+
+        let post = update(&schema, token, &post, "status: DRAFT").await;
+        assert!(post["publishedAt"].is_null());
+
+        database.drop().await;
+    }
 }
@@ -0,0 +1,284 @@
+use {
+    super::{authorization, email_verification},
+    crate::{
+        errors::field_error,
+        mailer::Mailer,
+        records::{
+            posts::Post,
+            settings::{registry::SiteSettings, Settings},
+            users::{
+                author::{Author, Profile, ProfileUpdate},
+                data_export::DataExport,
+                email_verification::EmailVerification,
+                is_valid_email,
+                password::PasswordPolicy,
+                session::Session,
+                SimpleUser,
+            },
+        },
+        AuthToken,
+    },
+    async_graphql::{Context, Error, Result},
+    sqlx::PgPool,
+    std::sync::Arc,
+};
+
+const WRONG_PASSWORD: &str = "The password is not correct.";
+
+pub async fn get_author(ctx: &Context<'_>, author_id: i32) -> Result<Option<Author>> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    Author::get(pg_pool, author_id).await
+}
+
+pub async fn get(ctx: &Context<'_>) -> Result<Profile> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let user = authorization::require_session_user(ctx).await?;
+    match Author::get(pg_pool, user.id).await? {
+        Some(author) => Ok(Profile {
+            email: user.email,
+            email_verified_at: user.email_verified_at,
+            author,
+        }),
+        None => Err(Error::from("The user doesn't exist.")),
+    }
+}
+
+pub async fn update(
+    ctx: &Context<'_>,
+    display_name: String,
+    bio: String,
+    avatar_url: Option<String>,
+    website_url: Option<String>,
+    social_links: Vec<String>,
+) -> Result<Author> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let user = authorization::require_session_user(ctx).await?;
+    let update = ProfileUpdate::new(&display_name, &bio, avatar_url, website_url, social_links)?;
+    update.save(pg_pool, user.id).await
+}
+
+pub async fn change_email<'a>(
+    ctx: &'a Context<'_>,
+    new_email: String,
+    password: String,
+) -> Result<&'a str> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let mailer = ctx.data::<Arc<dyn Mailer>>()?;
+    let mut user = authorization::require_session_user(ctx).await?;
+    if !user.password_matches(&password).await? {
+        return Err(field_error("password", WRONG_PASSWORD));
+    }
+
+    let new_email = new_email.trim();
+    if !is_valid_email(new_email)? {
+        return Err(field_error("newEmail", "Email is not valid."));
+    }
+    if new_email == user.email {
+        return Err(field_error(
+            "newEmail",
+            "This is already your email address.",
+        ));
+    }
+
+    // The link sent to the new address needs the site URL.
+    SiteSettings::from_settings(&Settings::all(pg_pool).await?).site_url_for_emails()?;
+
+    // The new address has to be verified again before it counts as verified,
+    // and links sent to the old one must not verify it.
+    let mut transaction = match pg_pool.begin().await {
+        Ok(transaction) => transaction,
+        Err(error) => {
+            println!("{}", error);
+            return Err(Error::from("Unable to change the email."));
+        }
+    };
+    SimpleUser::set_email(&mut transaction, user.id, new_email).await?;
+    EmailVerification::discard_pending(&mut transaction, user.id).await?;
+    if let Err(error) = transaction.commit().await {
+        println!("{}", error);
+        return Err(Error::from("Unable to change the email."));
+    }
+    user.email = new_email.to_string();
+    user.email_verified_at = None;
+    email_verification::send(pg_pool, mailer, &user).await?;
+    Ok("OK")
+}
+
+pub async fn change_password<'a>(
+    ctx: &'a Context<'_>,
+    current_password: String,
+    new_password: String,
+) -> Result<&'a str> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let user = authorization::require_session_user(ctx).await?;
+    let token = ctx.data::<AuthToken>()?;
+    if !user.password_matches(&current_password).await? {
+        return Err(field_error("currentPassword", WRONG_PASSWORD));
+    }
+    if let Err(message) =
+        PasswordPolicy::from_env().check(&new_password, &[&user.email, &user.name])
+    {
+        return Err(field_error("newPassword", message));
+    }
+
+    let mut transaction = match pg_pool.begin().await {
+        Ok(transaction) => transaction,
+        Err(error) => {
+            println!("{}", error);
+            return Err(Error::from("Unable to change the password."));
+        }
+    };
+
+    SimpleUser::set_password(&mut transaction, user.id, &new_password).await?;
+    // Everyone else signed in as this user has to sign in again.
+    Session::revoke_others(&mut transaction, user.id, &token.0).await?;
+
+    match transaction.commit().await {
+        Ok(()) => Ok("OK"),
+        Err(error) => {
+            println!("{}", error);
+            Err(Error::from("Unable to change the password."))
+        }
+    }
+}
+
+pub async fn export_data(ctx: &Context<'_>) -> Result<String> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let user = authorization::require_session_user(ctx).await?;
+    let token = ctx.data::<AuthToken>()?;
+    DataExport::collect(pg_pool, user, &token.0)
+        .await?
+        .to_json()
+}
+
+pub async fn delete_account<'a>(
+    ctx: &'a Context<'_>,
+    password: String,
+    reassign_posts_to: Option<i32>,
+) -> Result<&'a str> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let user = authorization::require_session_user(ctx).await?;
+    if !user.password_matches(&password).await? {
+        return Err(field_error("password", WRONG_PASSWORD));
+    }
+    if let Some(new_owner_id) = reassign_posts_to {
+        if new_owner_id == user.id || SimpleUser::get(pg_pool, new_owner_id).await?.is_none() {
+            return Err(field_error(
+                "reassignPostsTo",
+                "Choose another existing user to take over your posts.",
+            ));
+        }
+    }
+
+    let mut transaction = match pg_pool.begin().await {
+        Ok(transaction) => transaction,
+        Err(error) => {
+            println!("{}", error);
+            return Err(Error::from("Unable to delete the account."));
+        }
+    };
+
+    // Posts that aren't reassigned stay up without an author.
+    if let Some(new_owner_id) = reassign_posts_to {
+        Post::reassign(&mut transaction, user.id, new_owner_id).await?;
+    }
+    SimpleUser::delete(&mut transaction, user.id).await?;
+
+    match transaction.commit().await {
+        Ok(()) => Ok("OK"),
+        Err(error) => {
+            println!("{}", error);
+            Err(Error::from("Unable to delete the account."))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mailer::MemoryMailer;
+    use crate::records::users::SimpleUser;
+    use crate::test_support::{execute, TestDatabase, TestSchema};
+    use std::sync::Arc;
+
+    async fn sign_in(schema: &TestSchema, email: &str) -> String {
+        let sign_in = format!(
+            r#"mutation {{ signIn(email: "{}", password: "violet tugboat anthem") {{ token }} }}"#,
+            email
+        );
+        let response = execute(schema, &sign_in, None).await;
+        response["data"]["signIn"]["token"]
+            .as_str()
+            .unwrap()
+            .to_string()
+    }
+
+    fn verification_token(body: &str) -> String {
+        body.split("/email/verify/")
+            .nth(1)
+            .and_then(|rest| rest.split_whitespace().next())
+            .unwrap()
+            .to_string()
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL, run with --ignored"]
+    async fn refuses_an_email_taken_in_another_case() {
+        let database = TestDatabase::new().await;
+        database.set_site_url().await;
+        database.insert_user("jane@example.com", "Jane").await;
+        database.insert_user("sam@example.com", "Sam").await;
+        let schema = database.schema(Arc::new(MemoryMailer::default()));
+        let token = sign_in(&schema, "sam@example.com").await;
+
+        let change = r#"mutation { changeEmail(newEmail: "Jane@Example.com", password: "violet tugboat anthem") }"#;
+        let response = execute(&schema, change, Some(&token)).await;
+        assert_eq!(
+            response["errors"][0]["extensions"]["fields"]["newEmail"],
+            "There is already an account with this email."
+        );
+
+        database.drop().await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs DATABASE_URL, run with --ignored"]
+    async fn discards_links_sent_to_the_previous_email() {
+        let database = TestDatabase::new().await;
+        database.set_site_url().await;
+        database.insert_user("jane@example.com", "Jane").await;
+        let mailer = Arc::new(MemoryMailer::default());
+        let schema = database.schema(mailer.clone());
+        let token = sign_in(&schema, "jane@example.com").await;
+
+        let resend = "mutation { resendVerificationEmail }";
+        let response = execute(&schema, resend, Some(&token)).await;
+        assert_eq!(response["data"]["resendVerificationEmail"], "OK");
+        let change = r#"mutation { changeEmail(newEmail: "someone@example.com", password: "violet tugboat anthem") }"#;
+        let response = execute(&schema, change, Some(&token)).await;
+        assert_eq!(response["data"]["changeEmail"], "OK");
+        let sent = mailer.sent();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[1].to, "someone@example.com");
+
+        let verify = format!(
+            r#"mutation {{ verifyEmail(token: "{}") }}"#,
+            verification_token(&sent[0].body)
+        );
+        let response = execute(&schema, &verify, None).await;
+        assert!(response["errors"].is_array());
+        let user = SimpleUser::find_by_email(&database.pg_pool, "someone@example.com")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!user.email_verified());
+
+        let verify = format!(
+            r#"mutation {{ verifyEmail(token: "{}") }}"#,
+            verification_token(&sent[1].body)
+        );
+        let response = execute(&schema, &verify, None).await;
+        assert_eq!(response["data"]["verifyEmail"], "OK");
+
+        database.drop().await;
+    }
+}
@@ -1,13 +1,18 @@
 use {
-    crate::records::settings::{NewSetting, Settings},
+    super::authorization,
+    crate::records::{
+        settings::{registry::SiteSettings, NewSetting, Settings},
+        users::api_token::ApiScope,
+    },
     async_graphql::{Context, Result},
     sqlx::PgPool,
     std::collections::HashMap,
 };
 
-pub async fn get_all<'a>(ctx: &'a Context<'_>) -> Result<HashMap<String, String>> {
+pub async fn get_all(ctx: &Context<'_>) -> Result<HashMap<String, String>> {
     let pg_pool = ctx.data::<PgPool>()?;
-    let settings = Settings::all(&pg_pool).await?;
+    authorization::require_scope(ctx, ApiScope::SettingsRead).await?;
+    let settings = Settings::all(pg_pool).await?;
     let mut settings_map = HashMap::new();
 
     for setting in settings {
@@ -17,9 +22,37 @@ pub async fn get_all<'a>(ctx: &'a Context<'_>) -> Result<HashMap<String, String>
     Ok(settings_map)
 }
 
+pub async fn get_site(ctx: &Context<'_>) -> Result<SiteSettings> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    authorization::require_scope(ctx, ApiScope::SettingsRead).await?;
+    let settings = Settings::all(pg_pool).await?;
+    Ok(SiteSettings::from_settings(&settings))
+}
+
 pub async fn add<'a>(ctx: &'a Context<'_>, key: String, value: String) -> Result<&'a str> {
     let pg_pool = ctx.data::<PgPool>()?;
+    authorization::require_admin(ctx, ApiScope::SettingsWrite).await?;
     let new_setting = NewSetting::new(&key, &value)?;
-    let _setting = new_setting.insert(&pg_pool).await?;
+    let _setting = new_setting.insert(pg_pool).await?;
+    Ok("OK")
+}
+
+pub async fn update(ctx: &Context<'_>, key: String, value: String) -> Result<Settings> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    authorization::require_admin(ctx, ApiScope::SettingsWrite).await?;
+    Settings::update(pg_pool, &key, &value).await
+}
+
+pub async fn delete<'a>(ctx: &'a Context<'_>, key: String) -> Result<&'a str> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    authorization::require_admin(ctx, ApiScope::SettingsWrite).await?;
+    Settings::delete(pg_pool, &key).await?;
     Ok("OK")
 }
+
+pub async fn upsert(ctx: &Context<'_>, key: String, value: String) -> Result<Settings> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    authorization::require_admin(ctx, ApiScope::SettingsWrite).await?;
+    let new_setting = NewSetting::new(&key, &value)?;
+    new_setting.upsert(pg_pool).await
+}
@@ -0,0 +1,123 @@
+use {
+    super::authorization,
+    crate::{
+        errors::ApiError,
+        records::{
+            posts::{
+                category::Category,
+                tag::{Label, Tag},
+                Post, Viewer,
+            },
+            users::{api_token::ApiScope, SimpleUser},
+        },
+    },
+    async_graphql::{Context, Error, ErrorExtensions, Result},
+    sqlx::PgPool,
+};
+
+async fn require_editor(ctx: &Context<'_>) -> Result<SimpleUser> {
+    let user = authorization::require_user(ctx, ApiScope::PostsWrite).await?;
+    if !user.role.can_manage_taxonomy() {
+        return Err(ApiError::Forbidden("Only editors can change tags and categories.").extend());
+    }
+    Ok(user)
+}
+
+// Tags and categories of a post are only shown to those who can see the post.
+async fn visible_post(ctx: &Context<'_>, post_id: i32) -> Result<Option<Post>> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let viewer = authorization::current_user(ctx, ApiScope::PostsRead).await?;
+    Post::get(pg_pool, post_id, Viewer::from_user(viewer.as_ref())).await
+}
+
+pub async fn tags(ctx: &Context<'_>) -> Result<Vec<Tag>> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    Tag::all(pg_pool).await
+}
+
+pub async fn tag(ctx: &Context<'_>, slug: String) -> Result<Option<Tag>> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    Tag::by_slug(pg_pool, &slug).await
+}
+
+pub async fn post_tags(ctx: &Context<'_>, post_id: i32) -> Result<Vec<Tag>> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    match visible_post(ctx, post_id).await? {
+        Some(post) => Tag::for_post(pg_pool, post.id).await,
+        None => Ok(vec![]),
+    }
+}
+
+pub async fn create_tag(ctx: &Context<'_>, name: String, slug: Option<String>) -> Result<Tag> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    require_editor(ctx).await?;
+    let label = Label::new(&name, slug.as_deref())?;
+    Tag::insert(pg_pool, &label).await
+}
+
+pub async fn update_tag(
+    ctx: &Context<'_>,
+    tag_id: i32,
+    name: String,
+    slug: Option<String>,
+) -> Result<Tag> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    require_editor(ctx).await?;
+    let label = Label::new(&name, slug.as_deref())?;
+    Tag::update(pg_pool, tag_id, &label).await
+}
+
+pub async fn delete_tag<'a>(ctx: &'a Context<'_>, tag_id: i32) -> Result<&'a str> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    require_editor(ctx).await?;
+    Tag::delete(pg_pool, tag_id).await?;
+    Ok("OK")
+}
+
+pub async fn categories(ctx: &Context<'_>) -> Result<Vec<Category>> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    Category::all(pg_pool).await
+}
+
+pub async fn breadcrumbs(ctx: &Context<'_>, post_id: i32) -> Result<Vec<Category>> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    match visible_post(ctx, post_id).await? {
+        Some(post) => Category::breadcrumbs(pg_pool, post.id).await,
+        None => Ok(vec![]),
+    }
+}
+
+pub async fn create_category(
+    ctx: &Context<'_>,
+    name: String,
+    slug: Option<String>,
+    parent_id: Option<i32>,
+) -> Result<Category> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    require_editor(ctx).await?;
+    let label = Label::new(&name, slug.as_deref())?;
+    Category::insert(pg_pool, &label, parent_id).await
+}
+
+pub async fn update_category(
+    ctx: &Context<'_>,
+    category_id: i32,
+    name: String,
+    slug: Option<String>,
+    parent_id: Option<i32>,
+) -> Result<Category> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    require_editor(ctx).await?;
+    if Category::get(pg_pool, category_id).await?.is_none() {
+        return Err(Error::from("The category doesn't exist."));
+    }
+    let label = Label::new(&name, slug.as_deref())?;
+    Category::update(pg_pool, category_id, &label, parent_id).await
+}
+
+pub async fn delete_category<'a>(ctx: &'a Context<'_>, category_id: i32) -> Result<&'a str> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    require_editor(ctx).await?;
+    Category::delete(pg_pool, category_id).await?;
+    Ok("OK")
+}
@@ -0,0 +1,181 @@
+use {
+    super::authorization,
+    crate::{
+        errors::{field_error, ApiError},
+        records::{
+            settings::{registry::SiteSettings, Settings},
+            sign_in_throttle::{SignInThrottle, ThrottlePolicy},
+            users::{
+                sign_in_challenge::SignInChallenge,
+                two_factor::{
+                    generate_secret, otpauth_uri, qr_code_svg, verify_code, RecoveryCode,
+                    TwoFactor, TwoFactorEnrollment, TwoFactorStatus,
+                },
+            },
+        },
+    },
+    async_graphql::{Context, Error, ErrorExtensions, Result},
+    chrono::Utc,
+    sqlx::PgPool,
+};
+
+const INVALID_CODE: &str = "The code is not valid.";
+
+pub async fn status(ctx: &Context<'_>) -> Result<TwoFactorStatus> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let user = authorization::require_session_user(ctx).await?;
+    TwoFactor::status(pg_pool, user.id).await
+}
+
+pub async fn begin_enrollment(ctx: &Context<'_>) -> Result<TwoFactorEnrollment> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let user = authorization::require_session_user(ctx).await?;
+
+    let secret = generate_secret();
+    TwoFactor::begin(pg_pool, user.id, &secret).await?;
+
+    let settings = SiteSettings::from_settings(&Settings::all(pg_pool).await?);
+    let issuer = if settings.title.is_empty() {
+        String::from("Blog")
+    } else {
+        settings.title
+    };
+    let otpauth_uri = otpauth_uri(&issuer, &user.email, &secret);
+    Ok(TwoFactorEnrollment {
+        qr_code_svg: qr_code_svg(&otpauth_uri)?,
+        otpauth_uri,
+        secret,
+    })
+}
+
+pub async fn confirm_enrollment(ctx: &Context<'_>, code: String) -> Result<Vec<String>> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let user = authorization::require_session_user(ctx).await?;
+
+    let two_factor = TwoFactor::get(pg_pool, user.id).await?;
+    let secret = match two_factor.secret {
+        _ if two_factor.enabled_at.is_some() => {
+            return Err(Error::from("Two-factor authentication is already enabled."))
+        }
+        Some(secret) => secret,
+        None => {
+            return Err(Error::from(
+                "Start the two-factor enrollment before confirming it.",
+            ))
+        }
+    };
+    let valid = match verify_code(&secret, &code, Utc::now()) {
+        Some(step) => TwoFactor::use_step(pg_pool, user.id, step).await?,
+        None => false,
+    };
+    if !valid {
+        return Err(field_error("code", INVALID_CODE));
+    }
+
+    let mut transaction = match pg_pool.begin().await {
+        Ok(transaction) => transaction,
+        Err(error) => {
+            println!("{}", error);
+            return Err(Error::from("Unable to enable two-factor authentication."));
+        }
+    };
+    TwoFactor::enable(&mut transaction, user.id).await?;
+    let recovery_codes = RecoveryCode::replace(&mut transaction, user.id).await?;
+    match transaction.commit().await {
+        Ok(()) => Ok(recovery_codes),
+        Err(error) => {
+            println!("{}", error);
+            Err(Error::from("Unable to enable two-factor authentication."))
+        }
+    }
+}
+
+async fn require_code(ctx: &Context<'_>, code: &str) -> Result<i32> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let user = authorization::require_session_user(ctx).await?;
+
+    let two_factor = TwoFactor::get(pg_pool, user.id).await?;
+    if !two_factor.enabled() {
+        return Err(Error::from("Two-factor authentication is not enabled."));
+    }
+    if !two_factor.check_code(pg_pool, user.id, code).await? {
+        return Err(field_error("code", INVALID_CODE));
+    }
+    Ok(user.id)
+}
+
+pub async fn disable<'a>(ctx: &'a Context<'_>, code: String) -> Result<&'a str> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let user_id = require_code(ctx, &code).await?;
+
+    let mut transaction = match pg_pool.begin().await {
+        Ok(transaction) => transaction,
+        Err(error) => {
+            println!("{}", error);
+            return Err(Error::from("Unable to disable two-factor authentication."));
+        }
+    };
+    TwoFactor::disable(&mut transaction, user_id).await?;
+    match transaction.commit().await {
+        Ok(()) => Ok("OK"),
+        Err(error) => {
+            println!("{}", error);
+            Err(Error::from("Unable to disable two-factor authentication."))
+        }
+    }
+}
+
+pub async fn regenerate_recovery_codes(ctx: &Context<'_>, code: String) -> Result<Vec<String>> {
+    let pg_pool = ctx.data::<PgPool>()?;
+    let user_id = require_code(ctx, &code).await?;
+
+    let mut transaction = match pg_pool.begin().await {
+        Ok(transaction) => transaction,
+        Err(error) => {
+            println!("{}", error);
+            return Err(Error::from("Unable to replace the recovery codes."));
+        }
+    };
+    let recovery_codes = RecoveryCode::replace(&mut transaction, user_id).await?;
+    match transaction.commit().await {
+        Ok(()) => Ok(recovery_codes),
+        Err(error) => {
+            println!("{}", error);
+            Err(Error::from("Unable to replace the recovery codes."))
+        }
+    }
+}
+
+pub async fn complete_sign_in(
+    ctx: &Context<'_>,
+    challenge: String,
+    code: String,
+) -> Result<String> {
+    let pg_pool = ctx.data::<PgPool>()?;
+
+    let sign_in_challenge = SignInChallenge::find(pg_pool, &challenge).await?;
+    let account_key = SignInThrottle::account_key(&sign_in_challenge.email);
+    if let Some(locked_until) =
+        SignInThrottle::locked_until(pg_pool, std::slice::from_ref(&account_key)).await?
+    {
+        return Err(ApiError::TooManyAttempts(locked_until).extend());
+    }
+
+    let two_factor = TwoFactor::get(pg_pool, sign_in_challenge.user_id).await?;
+    if !two_factor
+        .check_code(pg_pool, sign_in_challenge.user_id, &code)
+        .await?
+    {
+        SignInChallenge::record_failure(pg_pool, &challenge).await?;
+        SignInThrottle::record_failure(pg_pool, &account_key, ThrottlePolicy::account()).await?;
+        return Err(field_error("code", INVALID_CODE));
+    }
+    if !SignInChallenge::consume(pg_pool, &challenge).await? {
+        return Err(Error::from(
+            "The sign in challenge is invalid or has expired. Sign in again.",
+        ));
+    }
+    SignInThrottle::clear(pg_pool, &account_key).await?;
+
+    authorization::start_session(ctx, sign_in_challenge.user_id).await
+}
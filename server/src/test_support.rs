@@ -0,0 +1,115 @@
+use crate::mailer::Mailer;
+use crate::oidc::OidcClient;
+use crate::records::settings::NewSetting;
+use crate::{AuthToken, ClientInfo, MutationRoot, QueryRoot};
+use async_graphql::{EmptySubscription, Request, Schema};
+use rand::{distributions::Alphanumeric, thread_rng, Rng};
+use serde_json::Value;
+use sqlx::{postgres::PgPool, Connection, Executor, PgConnection};
+use std::env;
+use std::sync::Arc;
+use url::Url;
+
+pub type TestSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
+
+// A database of its own for one test, created next to the one in
+// DATABASE_URL and migrated from scratch. Tests that need one are ignored by
+// default and run with `cargo test -- --ignored`.
+pub struct TestDatabase {
+    pub pg_pool: PgPool,
+    server_url: Url,
+    name: String,
+}
+
+impl TestDatabase {
+    pub async fn new() -> Self {
+        let database_url =
+            env::var("DATABASE_URL").expect("DATABASE_URL must be set to run the database tests");
+        let server_url = Url::parse(&database_url).expect("DATABASE_URL is not a valid url");
+        let suffix: String = thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(12)
+            .map(char::from)
+            .collect();
+        let name = format!("blog_test_{}", suffix.to_lowercase());
+
+        let mut connection = PgConnection::connect(server_url.as_str())
+            .await
+            .expect("Unable to connect to the test database server");
+        connection
+            .execute(format!(r#"CREATE DATABASE "{}""#, name).as_str())
+            .await
+            .expect("Unable to create the test database");
+
+        let mut database_url = server_url.clone();
+        database_url.set_path(&name);
+        let pg_pool = PgPool::connect(database_url.as_str())
+            .await
+            .expect("Unable to connect to the test database");
+        sqlx::migrate!()
+            .run(&pg_pool)
+            .await
+            .expect("Database migrations failed");
+
+        Self {
+            pg_pool,
+            server_url,
+            name,
+        }
+    }
+
+    pub async fn drop(self) {
+        self.pg_pool.close().await;
+        let mut connection = PgConnection::connect(self.server_url.as_str())
+            .await
+            .expect("Unable to connect to the test database server");
+        connection
+            .execute(format!(r#"DROP DATABASE "{}""#, self.name).as_str())
+            .await
+            .expect("Unable to drop the test database");
+    }
+
+    pub async fn insert_user(&self, email: &str, name: &str) -> i32 {
+        let (id,): (i32,) = sqlx::query_as(
+            "INSERT INTO users (email, name, password, role) VALUES ($1, $2, $3, 'author') RETURNING id",
+        )
+        .bind(email)
+        .bind(name)
+        .bind(bcrypt::hash("violet tugboat anthem", 4).unwrap())
+        .fetch_one(&self.pg_pool)
+        .await
+        .expect("Unable to insert the test user");
+        id
+    }
+
+    // Emailed links are only sent once the site URL is set.
+    pub async fn set_site_url(&self) {
+        NewSetting::new("site_url", "https://blog.example.com")
+            .unwrap()
+            .upsert(&self.pg_pool)
+            .await
+            .expect("Unable to set the site URL");
+    }
+
+    // The schema as main builds it, without single sign-on.
+    pub fn schema(&self, mailer: Arc<dyn Mailer>) -> TestSchema {
+        Schema::build(QueryRoot, MutationRoot, EmptySubscription)
+            .data(self.pg_pool.clone())
+            .data(mailer)
+            .data(None::<Arc<OidcClient>>)
+            .finish()
+    }
+}
+
+// Runs a GraphQL request the way the /graphql route does and returns the
+// response as JSON, with `data` and `errors` like a client would see them.
+pub async fn execute(schema: &TestSchema, query: &str, token: Option<&str>) -> Value {
+    let mut request = Request::new(query).data(ClientInfo {
+        user_agent: Some(String::from("test")),
+        ip_address: Some(String::from("127.0.0.1")),
+    });
+    if let Some(token) = token {
+        request = request.data(AuthToken(token.to_string()));
+    }
+    serde_json::to_value(schema.execute(request).await).unwrap()
+}